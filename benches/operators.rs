@@ -0,0 +1,234 @@
+// Operator micro-benchmarks over synthetic in-memory batches, and Parquet read throughput
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int32Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use parquet::arrow::ArrowWriter;
+
+use mini_query_engine::dataframe::{asc, col, count, lit_int32, sum, ExprBuilder};
+use mini_query_engine::execution::batch::RecordBatch;
+use mini_query_engine::execution::operators::{
+    AggregateOperator, FilterOperator, FilterProjectOperator, HashJoinOperator, Operator,
+    ProjectOperator, SortOperator,
+};
+use mini_query_engine::planner::logical_plan::JoinType;
+use mini_query_engine::storage::parquet_reader::{ParquetReader, ParquetReaderConfig};
+
+const ROWS: usize = 100_000;
+const BATCH_SIZE: usize = 8192;
+
+/// System allocator that counts allocations, to compare operator pipelines
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Number of allocations made by `f`
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    drop(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Deterministic pseudo-random sequence (xorshift), so runs are comparable
+fn values(len: usize, seed: u64) -> Vec<i64> {
+    let mut state = seed.max(1);
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 1) as i64
+        })
+        .collect()
+}
+
+/// `rows` rows split into batches: id (0..rows), key (in 0..cardinality), value, label
+fn batches(rows: usize, cardinality: usize) -> Vec<RecordBatch> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("key", DataType::Int32, false),
+        Field::new("value", DataType::Int64, false),
+        Field::new("label", DataType::Utf8, false),
+    ]));
+    let random = values(rows, 42);
+    (0..rows)
+        .step_by(BATCH_SIZE)
+        .map(|start| {
+            let end = (start + BATCH_SIZE).min(rows);
+            let ids: Vec<i32> = (start as i32..end as i32).collect();
+            let keys: Vec<i32> = random[start..end]
+                .iter()
+                .map(|v| (v % cardinality as i64) as i32)
+                .collect();
+            let labels: Vec<String> = keys.iter().map(|k| format!("label_{}", k % 100)).collect();
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(Int32Array::from(ids)),
+                Arc::new(Int32Array::from(keys)),
+                Arc::new(Int64Array::from(random[start..end].to_vec())),
+                Arc::new(StringArray::from(labels)),
+            ];
+            RecordBatch::try_new(schema.clone(), columns).unwrap()
+        })
+        .collect()
+}
+
+fn bench_filter(c: &mut Criterion) {
+    let input = batches(ROWS, 1000);
+    let schema = input[0].schema().clone();
+    let mut group = c.benchmark_group("filter");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    for selectivity in [1, 10, 50, 90] {
+        // key is uniform in 0..1000, so key < 10 * pct keeps about pct% of rows
+        let predicate = col("key").lt(lit_int32(selectivity * 10));
+        let op = FilterOperator::new(predicate, schema.clone()).unwrap();
+        group.bench_with_input(BenchmarkId::new("selectivity_pct", selectivity), &op, |b, op| {
+            b.iter(|| op.execute_many(&input).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_filter_project(c: &mut Criterion) {
+    let input = batches(ROWS, 1000);
+    let schema = input[0].schema().clone();
+    let predicate = col("key").lt(lit_int32(500));
+    let columns = vec!["id".to_string()];
+    let filter = FilterOperator::new(predicate.clone(), schema.clone()).unwrap();
+    let project = ProjectOperator::new(columns.clone(), schema.clone()).unwrap();
+    let fused = FilterProjectOperator::new(predicate, columns, schema).unwrap();
+    let two_stage = || project.execute_many(&filter.execute_many(&input).unwrap()).unwrap();
+    let one_pass = || fused.execute_many(&input).unwrap();
+    println!(
+        "filter_project allocations: two_stage {}, fused {}",
+        count_allocations(two_stage),
+        count_allocations(one_pass)
+    );
+
+    let mut group = c.benchmark_group("filter_project");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.bench_function("two_stage", |b| b.iter(two_stage));
+    group.bench_function("fused", |b| b.iter(one_pass));
+    group.finish();
+}
+
+fn bench_aggregate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_aggregate");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    for cardinality in [10, 1_000, 100_000] {
+        let input = batches(ROWS, cardinality);
+        let op = AggregateOperator::new(
+            vec![col("key")],
+            vec![count("n"), sum("value", "total")],
+            input[0].schema().clone(),
+        )
+        .unwrap();
+        group.bench_with_input(BenchmarkId::new("groups", cardinality), &op, |b, op| {
+            b.iter(|| op.execute_many(&input).unwrap())
+        });
+        let op = op.with_estimated_groups(Some(cardinality));
+        group.bench_with_input(BenchmarkId::new("groups_hinted", cardinality), &op, |b, op| {
+            b.iter(|| op.execute_many(&input).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_hash_join(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_join");
+    for (build, probe) in [(1_000, 100_000), (10_000, 100_000), (100_000, 100_000)] {
+        let left = batches(probe, build);
+        let right = batches(build, build);
+        let op = HashJoinOperator::new(
+            "key".to_string(),
+            "id".to_string(),
+            JoinType::Inner,
+            left[0].schema().clone(),
+            right[0].schema().clone(),
+        )
+        .unwrap();
+        group.throughput(Throughput::Elements((build + probe) as u64));
+        let id = BenchmarkId::new("build_probe", format!("{}x{}", build, probe));
+        group.bench_with_input(id, &op, |b, op| {
+            b.iter(|| op.execute_join(&left, &right).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_sort(c: &mut Criterion) {
+    let input = batches(ROWS, 1000);
+    let schema = input[0].schema().clone();
+    let mut group = c.benchmark_group("sort");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    let cases = [
+        ("int", vec![asc("value")]),
+        ("string_int", vec![asc("label"), asc("key")]),
+    ];
+    for (name, order_by) in cases {
+        let op = SortOperator::new(order_by, schema.clone()).unwrap();
+        group.bench_with_input(BenchmarkId::new("keys", name), &op, |b, op| {
+            b.iter(|| op.execute_many(&input).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_parquet_read(c: &mut Criterion) {
+    const FILE_ROWS: usize = 2_000_000;
+    let input = batches(FILE_ROWS, 1000);
+    let path = std::env::temp_dir().join(format!("bench_read_{}.parquet", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    let schema = input[0].schema().clone();
+    let mut writer = ArrowWriter::try_new(file, schema, None).unwrap();
+    for batch in &input {
+        writer.write(&batch.to_arrow().unwrap()).unwrap();
+    }
+    writer.close().unwrap();
+
+    let mut group = c.benchmark_group("parquet_read");
+    group.throughput(Throughput::Bytes(std::fs::metadata(&path).unwrap().len()));
+    group.sample_size(10);
+    let cases = [("default", None), ("1MiB", Some(1 << 20))];
+    for (name, read_buffer_size) in cases {
+        let config = ParquetReaderConfig {
+            parallel: false,
+            read_buffer_size,
+            ..ParquetReaderConfig::default()
+        };
+        let reader = ParquetReader::from_path_with_config(&path, config).unwrap();
+        group.bench_with_input(BenchmarkId::new("buffer", name), &reader, |b, reader| {
+            b.iter(|| reader.read_all().unwrap())
+        });
+    }
+    group.finish();
+    let _ = std::fs::remove_file(&path);
+}
+
+criterion_group!(
+    benches,
+    bench_filter,
+    bench_filter_project,
+    bench_aggregate,
+    bench_hash_join,
+    bench_sort,
+    bench_parquet_read
+);
+criterion_main!(benches);
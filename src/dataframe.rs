@@ -1,13 +1,26 @@
 // DataFrame API implementation
 
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::execution::batch::RecordBatch;
+use crate::execution::batch::{RecordBatch, SchemaMismatch, SchemaRef};
+use crate::execution::operators::{Operator, SortOperator, ValuesOperator};
 use crate::execution::Executor;
 use crate::planner::logical_plan::{
-    Aggregation, AggregateFunction, BinaryOp, JoinType, LogicalExpr, LogicalPlan, LogicalValue,
-    OrderByExpr,
+    Aggregation, BinaryOp, JoinType, LogicalExpr, LogicalPlan, LogicalValue, OrderByExpr,
+    RowPredicate, ScanOptions, SetOperation, WindowExpr, WindowFunction,
 };
+use crate::planner::optimizer;
+use crate::types::ScalarValue;
+use arrow::array::{ArrayRef, Int64Array, UInt32Array};
+use arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::util::display::array_value_to_string;
+use arrow_select::take::take;
+
+/// Most distinct values `DataFrame::cross_tab` turns into columns
+pub const MAX_CROSS_TAB_COLUMNS: usize = 1000;
 
 /// DataFrame represents a lazy query plan that can be executed
 /// Operations on DataFrame build up a logical plan tree
@@ -16,11 +29,15 @@ pub struct DataFrame {
     plan: LogicalPlan,
 }
 
+/// Name of the row count column `GroupedDataFrame::count_and_agg` appends
+pub const GROUP_COUNT_COLUMN: &str = "__group_count";
+
 /// Intermediate type for group_by + agg. Call .agg(aggregations) to complete.
 #[derive(Debug, Clone)]
 pub struct GroupedDataFrame {
     input: LogicalPlan,
-    group_by: Vec<String>,
+    group_by: Vec<LogicalExpr>,
+    estimated_groups: Option<usize>,
 }
 
 impl GroupedDataFrame {
@@ -31,31 +48,77 @@ impl GroupedDataFrame {
                 input: Box::new(self.input),
                 group_by: self.group_by,
                 aggs,
+                order_by: vec![],
+                estimated_groups: self.estimated_groups,
             },
         }
     }
+
+    /// Expect about `groups` groups, e.g. from knowing the key's cardinality, so the
+    /// group table is sized once instead of growing. The result is unchanged.
+    pub fn with_estimated_groups(mut self, groups: usize) -> Self {
+        self.estimated_groups = Some(groups);
+        self
+    }
+
+    /// As `agg`, with each group's row count appended as an Int64 `__group_count` column
+    /// (`GROUP_COUNT_COLUMN`), computed in the same pass
+    pub fn count_and_agg(self, mut aggs: Vec<Aggregation>) -> DataFrame {
+        aggs.push(Aggregation::count_star(GROUP_COUNT_COLUMN));
+        self.agg(aggs)
+    }
 }
 
 impl DataFrame {
     /// Create a DataFrame from a Parquet file path
     /// 
     /// # Arguments
-    /// * `path` - Path to the Parquet file
+    /// * `path` - Path to the Parquet file, or a directory of Parquet files with one schema
     /// 
     /// # Returns
     /// A new DataFrame with a Scan operation in the plan
     pub fn from_parquet<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        Self::from_parquet_with_options(path, ScanOptions::default())
+    }
+
+    /// Create a DataFrame reading Parquet file(s) as `schema` instead of inferring it from
+    /// the files: each file must have every column, in a type castable to the given one
+    /// (e.g. Int32 read as Int64). Files with differing schemas can then be read together.
+    pub fn from_parquet_with_schema<P: AsRef<Path>>(
+        path: P,
+        schema: SchemaRef,
+    ) -> Result<Self, String> {
+        Self::from_parquet_with_options(path, ScanOptions::default().with_schema(schema))
+    }
+
+    /// Create a DataFrame from a Parquet file path, with options for how it is scanned
+    pub fn from_parquet_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ScanOptions,
+    ) -> Result<Self, String> {
         let path_buf = path.as_ref().to_path_buf();
         Ok(DataFrame {
             plan: LogicalPlan::Scan {
                 path: path_buf,
                 projection: None,
                 filters: vec![],
+                options,
             },
         })
     }
 
-    /// Select specific columns (projection)
+    /// Create a DataFrame of constant rows, e.g. a small lookup table to join against.
+    /// Each row needs one value of the matching field's type per schema field, with
+    /// `LogicalValue::Null` allowed in nullable fields.
+    pub fn from_values(schema: SchemaRef, rows: Vec<Vec<LogicalValue>>) -> Result<Self, String> {
+        ValuesOperator::new(schema.clone(), &rows)?;
+        Ok(DataFrame {
+            plan: LogicalPlan::Values { schema, rows },
+        })
+    }
+
+    /// Select specific columns (projection). Selecting no columns keeps the rows, e.g.
+    /// to count them.
     /// 
     /// # Arguments
     /// * `columns` - Vector of column names to select
@@ -71,6 +134,33 @@ impl DataFrame {
         }
     }
 
+    /// Select every column except `columns`, in schema order. Fails if a named column
+    /// does not exist.
+    pub fn drop(&self, columns: Vec<String>) -> Result<Self, String> {
+        let schema = self.schema()?;
+        if let Some(missing) = columns.iter().find(|c| schema.index_of(c).is_err()) {
+            return Err(format!("Cannot drop column '{}': not found", missing));
+        }
+        let kept = schema
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .filter(|name| !columns.contains(name))
+            .collect();
+        Ok(self.select(kept))
+    }
+
+    /// Select computed expressions, e.g. `col("a").plus(col("b")).alias("total")`.
+    /// Each output column is named by its alias, or else its column name / rendered expression.
+    pub fn select_exprs(&self, exprs: Vec<LogicalExpr>) -> Self {
+        DataFrame {
+            plan: LogicalPlan::ProjectExprs {
+                input: Box::new(self.plan.clone()),
+                exprs,
+            },
+        }
+    }
+
     /// Filter rows based on a predicate expression
     /// 
     /// # Arguments
@@ -90,11 +180,33 @@ impl DataFrame {
         }
     }
 
+    /// Keep the rows for which `f(batch, row)` is true, for conditions expressions cannot
+    /// state. `f` is called once per row, so this is much slower than `filter`, and it is
+    /// never pushed into a scan.
+    pub fn filter_fn<F>(&self, f: F) -> Self
+    where
+        F: Fn(&RecordBatch, usize) -> bool + Send + Sync + 'static,
+    {
+        DataFrame {
+            plan: LogicalPlan::FilterFn {
+                input: Box::new(self.plan.clone()),
+                predicate: RowPredicate(Arc::new(f)),
+            },
+        }
+    }
+
     /// Group by the given columns. Returns a GroupedDataFrame; call .agg(aggregations) to complete.
     pub fn group_by(&self, columns: Vec<String>) -> GroupedDataFrame {
+        self.group_by_exprs(columns.into_iter().map(LogicalExpr::Column).collect())
+    }
+
+    /// Group by computed expressions, e.g. `col("a").modulo(lit_int32(2))`.
+    /// Each group column is named by its alias, or else its column name / rendered expression.
+    pub fn group_by_exprs(&self, exprs: Vec<LogicalExpr>) -> GroupedDataFrame {
         GroupedDataFrame {
             input: self.plan.clone(),
-            group_by: columns,
+            group_by: exprs,
+            estimated_groups: None,
         }
     }
 
@@ -108,65 +220,606 @@ impl DataFrame {
         }
     }
 
-    /// Execute the query plan and return the results as a vector of RecordBatches
+    /// Append window function columns. Rows are partitioned by `partition_by` and
+    /// ordered by `order_by` within each partition; the output is sorted the same way.
+    /// Use `row_number(..)` and `running_sum(..)` to build WindowExpr.
+    pub fn window(
+        &self,
+        partition_by: Vec<String>,
+        order_by: Vec<OrderByExpr>,
+        functions: Vec<WindowExpr>,
+    ) -> Self {
+        DataFrame {
+            plan: LogicalPlan::Window {
+                input: Box::new(self.plan.clone()),
+                partition_by,
+                order_by,
+                functions,
+            },
+        }
+    }
+
+    /// Drop rows where any of the given columns is null.
+    /// With no columns, drops rows where any column of the current schema is null.
+    pub fn drop_nulls(&self, columns: Vec<String>) -> Result<Self, String> {
+        let columns = if columns.is_empty() {
+            self.schema()?
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect()
+        } else {
+            columns
+        };
+
+        let predicate = columns
+            .iter()
+            .map(|name| col(name).is_not_null())
+            .reduce(|left, right| LogicalExpr::BinaryExpr {
+                left: Box::new(left),
+                op: BinaryOp::And,
+                right: Box::new(right),
+            });
+        Ok(match predicate {
+            Some(predicate) => self.filter(predicate),
+            None => self.clone(),
+        })
+    }
+
+    /// Replace nulls in `column` with `value`. The value's type must match the column's.
+    pub fn fill_null(&self, column: &str, value: ScalarValue) -> Self {
+        DataFrame {
+            plan: LogicalPlan::FillNull {
+                input: Box::new(self.plan.clone()),
+                column: Some(column.to_string()),
+                value,
+            },
+        }
+    }
+
+    /// Replace nulls with `value` in every column of the same type; other columns are unchanged.
+    /// Chain calls to give each column type its own default.
+    pub fn fill_null_all(&self, value: ScalarValue) -> Self {
+        DataFrame {
+            plan: LogicalPlan::FillNull {
+                input: Box::new(self.plan.clone()),
+                column: None,
+                value,
+            },
+        }
+    }
+
+    /// Rename several columns (old name -> new name) in one pass.
+    /// Fails at execution if a new name collides with a column that is not also renamed.
+    pub fn with_column_renamed(&self, renames: HashMap<String, String>) -> Self {
+        DataFrame {
+            plan: LogicalPlan::Rename {
+                input: Box::new(self.plan.clone()),
+                renames,
+            },
+        }
+    }
+
+    /// Rename every column to `f(name)`, e.g. to lowercase names after a scan. Fails if
+    /// two columns would get the same name.
+    pub fn rename_all<F: Fn(&str) -> String>(&self, f: F) -> Result<Self, String> {
+        let schema = self.schema()?;
+        let mut renames = HashMap::new();
+        let mut seen: HashMap<String, &str> = HashMap::new();
+        for field in schema.fields() {
+            let new_name = f(field.name());
+            if let Some(other) = seen.insert(new_name.clone(), field.name()) {
+                return Err(format!(
+                    "Columns '{}' and '{}' would both be renamed to '{}'",
+                    other,
+                    field.name(),
+                    new_name
+                ));
+            }
+            if &new_name != field.name() {
+                renames.insert(field.name().clone(), new_name);
+            }
+        }
+        Ok(self.with_column_renamed(renames))
+    }
+
+    /// Qualify every column with the table alias `alias`, `name` becoming `alias.name`.
+    /// Joining a DataFrame with itself otherwise gives two columns of each name; alias
+    /// the right side to tell them apart, e.g.
+    /// `emp.join(&emp.alias("m"), "manager_id", "m.id", JoinType::Inner)`.
+    pub fn alias(&self, alias: &str) -> Self {
+        DataFrame {
+            plan: LogicalPlan::Alias {
+                input: Box::new(self.plan.clone()),
+                alias: alias.to_string(),
+            },
+        }
+    }
+
+    /// Turn wide columns into rows (melt): each row becomes one row per value column,
+    /// with the id columns, `variable` (the value column's name) and `value`. Value
+    /// columns must share a type or widen to a common numeric or string type.
+    pub fn unpivot(&self, id_columns: Vec<String>, value_columns: Vec<String>) -> Self {
+        DataFrame {
+            plan: LogicalPlan::Unpivot {
+                input: Box::new(self.plan.clone()),
+                id_columns,
+                value_columns,
+            },
+        }
+    }
+
+    /// Distinct rows present in both this DataFrame and `other` (SQL INTERSECT).
+    /// Schemas must have the same column types in order; names come from this DataFrame.
+    pub fn intersect(&self, other: &DataFrame) -> Self {
+        self.set_operation(other, SetOperation::Intersect)
+    }
+
+    /// Distinct rows of this DataFrame that are not in `other` (SQL EXCEPT)
+    pub fn except(&self, other: &DataFrame) -> Self {
+        self.set_operation(other, SetOperation::Except)
+    }
+
+    fn set_operation(&self, other: &DataFrame, op: SetOperation) -> Self {
+        DataFrame {
+            plan: LogicalPlan::SetOperation {
+                left: Box::new(self.plan.clone()),
+                right: Box::new(other.plan.clone()),
+                op,
+            },
+        }
+    }
+
+    /// Keep the first `n` rows. Directly over a scan with `preserve_order` (the default)
+    /// these are the first `n` rows in file order and reading stops once they are found;
+    /// otherwise the result is some `n` rows unless an ordering is specified. Over an
+    /// aggregation every input row is still aggregated, then the first `n` groups (in
+    /// order of first appearance) are kept.
+    pub fn limit(&self, n: usize) -> Self {
+        DataFrame {
+            plan: LogicalPlan::Limit {
+                input: Box::new(self.plan.clone()),
+                limit: n,
+            },
+        }
+    }
+
+    /// Append an Int64 column `name` numbering the rows 0..n in order, continuing across
+    /// batches (e.g. to remember the original order or as a surrogate key)
+    pub fn with_row_index(&self, name: &str) -> Self {
+        DataFrame {
+            plan: LogicalPlan::RowIndex {
+                input: Box::new(self.plan.clone()),
+                name: name.to_string(),
+            },
+        }
+    }
+
+    /// Join with `right` on `left_key = right_key`. Output columns are this DataFrame's
+    /// followed by the right's.
+    pub fn join(
+        &self,
+        right: &DataFrame,
+        left_key: &str,
+        right_key: &str,
+        join_type: JoinType,
+    ) -> Self {
+        self.join_plan(right, left_key, right_key, join_type, false)
+    }
+
+    /// Join with a small `right` side: its hash table is built once and left batches are
+    /// probed one at a time, so the left side is never materialized as a whole
+    pub fn join_broadcast(
+        &self,
+        right: &DataFrame,
+        left_key: &str,
+        right_key: &str,
+        join_type: JoinType,
+    ) -> Self {
+        self.join_plan(right, left_key, right_key, join_type, true)
+    }
+
+    /// Rows of this DataFrame with at least one match in `right` on
+    /// `left_key = right_key` (EXISTS). Output columns are this DataFrame's; each row is
+    /// kept once, however many right rows match it.
+    pub fn semi_join(&self, right: &DataFrame, left_key: &str, right_key: &str) -> Self {
+        self.join(right, left_key, right_key, JoinType::Semi)
+    }
+
+    /// Rows of this DataFrame with no match in `right` on `left_key = right_key`
+    /// (NOT EXISTS). Output columns are this DataFrame's, and duplicate right keys do not
+    /// change the result. Rows with a null key have no match, so they are kept.
+    pub fn anti_join(&self, right: &DataFrame, left_key: &str, right_key: &str) -> Self {
+        self.join(right, left_key, right_key, JoinType::Anti)
+    }
+
+    /// Inner join with `right` on every column both schemas have with the same name and
+    /// type. Output columns are this DataFrame's followed by the right's other columns,
+    /// so each shared column appears once. Errors if there is no such column.
+    pub fn natural_join(&self, right: &DataFrame) -> Result<Self, String> {
+        let (left_schema, right_schema) = (self.schema()?, right.schema()?);
+        let keys: Vec<String> = left_schema
+            .fields()
+            .iter()
+            .filter(|f| {
+                let other = right_schema.field_with_name(f.name());
+                other.is_ok_and(|other| other.data_type() == f.data_type())
+            })
+            .map(|f| f.name().clone())
+            .collect();
+        if keys.is_empty() {
+            let names = |schema: &SchemaRef| {
+                let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+                names.join(", ")
+            };
+            return Err(format!(
+                "Natural join needs a column with the same name and type on both sides; \
+                 left has [{}], right has [{}]",
+                names(&left_schema),
+                names(&right_schema)
+            ));
+        }
+
+        // Join on the first key with the right's copies renamed, then match the rest
+        let right_name = |key: &str| format!("__natural_join_right.{}", key);
+        let renames = keys.iter().map(|k| (k.clone(), right_name(k))).collect();
+        let right_renamed = right.with_column_renamed(renames);
+        let first = right_name(&keys[0]);
+        let mut joined = self.join(&right_renamed, &keys[0], &first, JoinType::Inner);
+        for key in &keys[1..] {
+            joined = joined.filter(col(key).eq(col(&right_name(key))));
+        }
+        let columns = left_schema
+            .fields()
+            .iter()
+            .chain(right_schema.fields().iter().filter(|f| !keys.contains(f.name())))
+            .map(|f| f.name().clone())
+            .collect();
+        Ok(joined.select(columns))
+    }
+
+    fn join_plan(
+        &self,
+        right: &DataFrame,
+        left_key: &str,
+        right_key: &str,
+        join_type: JoinType,
+        broadcast_right: bool,
+    ) -> Self {
+        DataFrame {
+            plan: LogicalPlan::Join {
+                left: Box::new(self.plan.clone()),
+                right: Box::new(right.plan.clone()),
+                join_type,
+                on: (left_key.to_string(), right_key.to_string()),
+                broadcast_right,
+            },
+        }
+    }
+
+    /// Render the plan as an indented tree, either as written or (`optimized`) after the
+    /// optimizer's rewrites, e.g. to check that a filter was pushed into its scan
+    pub fn explain(&self, optimized: bool) -> Result<String, String> {
+        if optimized {
+            Ok(optimizer::optimize(&self.plan)?.to_string())
+        } else {
+            Ok(self.plan.to_string())
+        }
+    }
+
+    /// Copy of this DataFrame with every `param(i)` in its plan replaced by `params[i]`,
+    /// so one plan can be run with different constants (a prepared statement). Running a
+    /// plan with a parameter that has no value is an error.
+    pub fn bind(&self, params: Vec<LogicalValue>) -> Self {
+        DataFrame {
+            plan: bind_plan(&self.plan, &params),
+        }
+    }
+
+    /// Rewrite the plan with the optimizer (e.g. join reordering). Results are unchanged.
+    pub fn optimize(&self) -> Result<Self, String> {
+        Ok(DataFrame {
+            plan: optimizer::optimize(&self.plan)?,
+        })
+    }
+
+    /// Output schema of this DataFrame, resolved by planning without executing
+    pub fn schema(&self) -> Result<SchemaRef, String> {
+        Ok(Executor::new().create_physical_plan(&self.plan)?.schema())
+    }
+
+    /// Check that the output columns have exactly these names and types, in order, by
+    /// planning without executing. Nullability is not checked. On a mismatch the error
+    /// lists each difference, with the actual schema as left and `expected` as right.
+    pub fn assert_schema(&self, expected: &[(&str, DataType)]) -> Result<(), String> {
+        let actual = self.schema()?;
+        let expected = Schema::new(
+            expected
+                .iter()
+                .map(|(name, data_type)| Field::new(*name, data_type.clone(), true))
+                .collect::<Vec<_>>(),
+        );
+        let mut mismatch = SchemaMismatch::between(&actual, &expected);
+        mismatch.nullability_mismatches.clear();
+        if mismatch.types_match() {
+            return Ok(());
+        }
+        Err(format!("Schema differs from the expected one (left is actual): {}", mismatch))
+    }
+
+    /// Get the logical plan built up by this DataFrame
+    pub fn plan(&self) -> &LogicalPlan {
+        &self.plan
+    }
+
+    /// Execute the query plan and return the results as a vector of RecordBatches.
+    /// Scanned rows keep file order (files by name, then row groups), even when row
+    /// groups are read in parallel.
     /// 
     /// # Returns
     /// Vector of RecordBatches containing the query results
     pub fn collect(&self) -> Result<Vec<RecordBatch>, String> {
         Executor::new().execute(&self.plan)
     }
+
+    /// Execute the query plan and return the results concatenated into one RecordBatch
+    /// (a zero-row batch with the output schema if there are no results)
+    pub fn collect_one(&self) -> Result<RecordBatch, String> {
+        Executor::new().execute_single(&self.plan)
+    }
+
+    /// Execute the query plan and return its single value, for queries such as a COUNT(*)
+    /// without GROUP BY. Errors unless the result has exactly one row and one column.
+    pub fn collect_scalar(&self) -> Result<ScalarValue, String> {
+        let batch = self.collect_one()?;
+        if batch.num_rows() != 1 || batch.num_columns() != 1 {
+            return Err(format!(
+                "Expected a single value, but the result has {} row(s) and {} column(s)",
+                batch.num_rows(),
+                batch.num_columns()
+            ));
+        }
+        batch.get_value(batch.schema().field(0).name(), 0)
+    }
+
+    /// Execute the query plan, calling `f` with each result batch as it is produced
+    /// instead of collecting them, e.g. to write results out with bounded memory.
+    /// Stops at the first error `f` returns.
+    pub fn for_each_batch<F: FnMut(RecordBatch) -> Result<(), String>>(
+        &self,
+        mut f: F,
+    ) -> Result<(), String> {
+        Executor::new().execute_each(&self.plan, &mut f)
+    }
+
+    /// Execute the query plan and return `(batch_index, num_rows)` for each result batch,
+    /// e.g. to see how row groups map to batches. Batches are streamed, not kept.
+    pub fn collect_with_batch_info(&self) -> Result<Vec<(usize, usize)>, String> {
+        let mut info = Vec::new();
+        self.for_each_batch(|batch| {
+            info.push((info.len(), batch.num_rows()));
+            Ok(())
+        })?;
+        Ok(info)
+    }
+
+    /// Execute the query plan and convert every result row with `T::from_row`
+    pub fn collect_as<T: FromRow>(&self) -> Result<Vec<T>, String> {
+        let mut rows = Vec::new();
+        for batch in self.collect()? {
+            for row in 0..batch.num_rows() {
+                rows.push(T::from_row(&batch, row)?);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Execute a count contingency table of two columns: one row per distinct `row_col`
+    /// value, then one Int64 column per distinct `col_col` value (named by the value,
+    /// nulls as `NULL`) holding the number of rows with that pair. Rows and columns are
+    /// in ascending order. Errors if `col_col` has more than `MAX_CROSS_TAB_COLUMNS`
+    /// distinct values.
+    pub fn cross_tab(&self, row_col: &str, col_col: &str) -> Result<RecordBatch, String> {
+        if row_col == col_col {
+            return Err(format!("cross_tab needs two different columns, not '{}' twice", row_col));
+        }
+        let counts = self
+            .group_by(vec![row_col.to_string(), col_col.to_string()])
+            .agg(vec![Aggregation::count_star("cross_tab_count")])
+            .order_by(vec![asc(row_col), asc(col_col)])
+            .collect_one()?;
+        // Values are told apart by their Debug form, which keeps the type and matches NaN
+        let key = |value: &ScalarValue| format!("{:?}", value);
+
+        let by_column = SortOperator::new(vec![asc(col_col)], counts.schema().clone())?;
+        let by_column = by_column.execute(&counts)?;
+        let mut names = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for i in 0..by_column.num_rows() {
+            let value = by_column.get_value(col_col, i)?;
+            if index.contains_key(&key(&value)) {
+                continue;
+            }
+            if names.len() == MAX_CROSS_TAB_COLUMNS {
+                return Err(format!(
+                    "cross_tab column '{}' has more than {} distinct values",
+                    col_col, MAX_CROSS_TAB_COLUMNS
+                ));
+            }
+            index.insert(key(&value), names.len());
+            names.push(match value {
+                ScalarValue::Utf8(s) => s,
+                // Dates and timestamps as Arrow prints them, e.g. 2024-03-01, unquoted
+                ScalarValue::Date32(_) | ScalarValue::TimestampMicros(_) => {
+                    let column = by_column
+                        .column_by_name(col_col)
+                        .ok_or("cross_tab column")?;
+                    array_value_to_string(column, i).map_err(|e| e.to_string())?
+                }
+                other => other.to_string(),
+            });
+        }
+
+        // Counts are sorted by row value, so each output row is a run of count rows
+        let count = counts.i64_column("cross_tab_count")?;
+        let mut first_rows: Vec<u32> = Vec::new();
+        let mut cells: Vec<Vec<i64>> = vec![Vec::new(); names.len()];
+        let mut previous = None;
+        for i in 0..counts.num_rows() {
+            let row = key(&counts.get_value(row_col, i)?);
+            if previous.as_ref() != Some(&row) {
+                first_rows.push(i as u32);
+                cells.iter_mut().for_each(|column| column.push(0));
+                previous = Some(row);
+            }
+            let column = index[&key(&counts.get_value(col_col, i)?)];
+            if let Some(cell) = cells[column].last_mut() {
+                *cell = count.value(i);
+            }
+        }
+
+        let rows = take(counts.column(0)?.as_ref(), &UInt32Array::from(first_rows), None)
+            .map_err(|e| format!("Take failed: {}", e))?;
+        let mut fields = vec![counts.schema().field(0).clone()];
+        let mut columns: Vec<ArrayRef> = vec![rows];
+        for (name, column) in names.iter().zip(cells) {
+            if fields.iter().any(|f| f.name() == name) {
+                return Err(format!("cross_tab would have two columns named '{}'", name));
+            }
+            fields.push(Field::new(name, DataType::Int64, false));
+            columns.push(Arc::new(Int64Array::from(column)));
+        }
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+    }
+}
+
+/// `plan` with parameters bound in every expression (see `DataFrame::bind`)
+fn bind_plan(plan: &LogicalPlan, params: &[LogicalValue]) -> LogicalPlan {
+    let Ok(plan) = optimizer::map_children(plan, |input| Ok(bind_plan(input, params))) else {
+        unreachable!("binding parameters cannot fail");
+    };
+    let bind_all = |exprs: Vec<LogicalExpr>| exprs.iter().map(|e| e.bind_params(params)).collect();
+    match plan {
+        LogicalPlan::Scan {
+            path,
+            projection,
+            filters,
+            options,
+        } => LogicalPlan::Scan {
+            path,
+            projection,
+            filters: bind_all(filters),
+            options,
+        },
+        LogicalPlan::ProjectExprs { input, exprs } => LogicalPlan::ProjectExprs {
+            input,
+            exprs: bind_all(exprs),
+        },
+        LogicalPlan::Filter { input, predicate } => LogicalPlan::Filter {
+            input,
+            predicate: predicate.bind_params(params),
+        },
+        LogicalPlan::Aggregate {
+            input,
+            group_by,
+            aggs,
+            order_by,
+            estimated_groups,
+        } => LogicalPlan::Aggregate {
+            input,
+            group_by: bind_all(group_by),
+            aggs: aggs
+                .into_iter()
+                .map(|agg| Aggregation {
+                    filter: agg.filter.map(|f| f.bind_params(params)),
+                    ..agg
+                })
+                .collect(),
+            order_by,
+            estimated_groups,
+        },
+        other => other,
+    }
+}
+
+/// Conversion of one result row into a user type, for `DataFrame::collect_as`.
+///
+/// ```
+/// use mini_query_engine::dataframe::FromRow;
+/// use mini_query_engine::execution::batch::RecordBatch;
+///
+/// struct Person {
+///     id: i32,
+///     name: String,
+/// }
+///
+/// impl FromRow for Person {
+///     fn from_row(batch: &RecordBatch, row: usize) -> Result<Self, String> {
+///         Ok(Person {
+///             id: batch.get_value("id", row)?.try_into()?,
+///             name: batch.get_value("name", row)?.try_into()?,
+///         })
+///     }
+/// }
+/// ```
+pub trait FromRow: Sized {
+    fn from_row(batch: &RecordBatch, row: usize) -> Result<Self, String>;
 }
 
 // Aggregation helper constructors for use with group_by().agg([...])
 /// COUNT(*) - count all rows in each group
 pub fn count(alias: &str) -> Aggregation {
-    Aggregation {
-        function: AggregateFunction::Count,
-        column: None,
-        alias: alias.to_string(),
-    }
+    Aggregation::count_star(alias)
 }
 
 /// COUNT(column) - count non-null values in the column
 pub fn count_column(column: &str, alias: &str) -> Aggregation {
-    Aggregation {
-        function: AggregateFunction::Count,
-        column: Some(column.to_string()),
-        alias: alias.to_string(),
-    }
+    Aggregation::count(column, alias)
 }
 
 /// SUM(column)
 pub fn sum(column: &str, alias: &str) -> Aggregation {
-    Aggregation {
-        function: AggregateFunction::Sum,
-        column: Some(column.to_string()),
-        alias: alias.to_string(),
-    }
+    Aggregation::sum(column, alias)
 }
 
 /// AVG(column)
 pub fn avg(column: &str, alias: &str) -> Aggregation {
-    Aggregation {
-        function: AggregateFunction::Avg,
-        column: Some(column.to_string()),
-        alias: alias.to_string(),
-    }
+    Aggregation::avg(column, alias)
 }
 
 /// MIN(column)
 pub fn min(column: &str, alias: &str) -> Aggregation {
-    Aggregation {
-        function: AggregateFunction::Min,
-        column: Some(column.to_string()),
-        alias: alias.to_string(),
-    }
+    Aggregation::min(column, alias)
 }
 
 /// MAX(column)
 pub fn max(column: &str, alias: &str) -> Aggregation {
-    Aggregation {
-        function: AggregateFunction::Max,
+    Aggregation::max(column, alias)
+}
+
+/// MEDIAN(column) - exact; buffers each group's values
+pub fn median(column: &str, alias: &str) -> Aggregation {
+    Aggregation::median(column, alias)
+}
+
+/// PERCENTILE(column, p) with p in [0, 1] - exact; buffers each group's values
+pub fn percentile(column: &str, p: f64, alias: &str) -> Aggregation {
+    Aggregation::percentile(column, p, alias)
+}
+
+/// ROW_NUMBER() - 1-based position of the row within its partition
+pub fn row_number(alias: &str) -> WindowExpr {
+    WindowExpr {
+        function: WindowFunction::RowNumber,
+        column: None,
+        alias: alias.to_string(),
+    }
+}
+
+/// Running SUM(column) from the start of the partition to the current row
+pub fn running_sum(column: &str, alias: &str) -> WindowExpr {
+    WindowExpr {
+        function: WindowFunction::RunningSum,
         column: Some(column.to_string()),
         alias: alias.to_string(),
     }
@@ -204,6 +857,23 @@ pub trait ExprBuilder {
     fn ge(&self, other: LogicalExpr) -> LogicalExpr;
     fn lt(&self, other: LogicalExpr) -> LogicalExpr;
     fn le(&self, other: LogicalExpr) -> LogicalExpr;
+    fn is_null(&self) -> LogicalExpr;
+    fn is_not_null(&self) -> LogicalExpr;
+    /// Logical AND with SQL three-valued logic: `false AND NULL` is false
+    fn and(&self, other: LogicalExpr) -> LogicalExpr;
+    /// Logical OR with SQL three-valued logic: `true OR NULL` is true
+    fn or(&self, other: LogicalExpr) -> LogicalExpr;
+    fn plus(&self, other: LogicalExpr) -> LogicalExpr;
+    fn minus(&self, other: LogicalExpr) -> LogicalExpr;
+    fn multiply(&self, other: LogicalExpr) -> LogicalExpr;
+    fn divide(&self, other: LogicalExpr) -> LogicalExpr;
+    fn modulo(&self, other: LogicalExpr) -> LogicalExpr;
+    /// String concatenation (`||`); null if either side is null
+    fn concat(&self, other: LogicalExpr) -> LogicalExpr;
+    fn alias(&self, name: &str) -> LogicalExpr;
+    fn in_list(&self, list: Vec<ScalarValue>) -> LogicalExpr;
+    /// SQL NOT IN: a `ScalarValue::Null` in the list makes it exclude every row
+    fn not_in(&self, list: Vec<ScalarValue>) -> LogicalExpr;
 }
 
 impl ExprBuilder for LogicalExpr {
@@ -254,6 +924,101 @@ impl ExprBuilder for LogicalExpr {
             right: Box::new(other),
         }
     }
+
+    fn is_null(&self) -> LogicalExpr {
+        LogicalExpr::IsNull(Box::new(self.clone()))
+    }
+
+    fn is_not_null(&self) -> LogicalExpr {
+        LogicalExpr::IsNotNull(Box::new(self.clone()))
+    }
+
+    fn and(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::And,
+            right: Box::new(other),
+        }
+    }
+
+    fn or(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::Or,
+            right: Box::new(other),
+        }
+    }
+
+    fn plus(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::Plus,
+            right: Box::new(other),
+        }
+    }
+
+    fn minus(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::Minus,
+            right: Box::new(other),
+        }
+    }
+
+    fn multiply(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::Multiply,
+            right: Box::new(other),
+        }
+    }
+
+    fn divide(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::Divide,
+            right: Box::new(other),
+        }
+    }
+
+    fn modulo(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::Modulo,
+            right: Box::new(other),
+        }
+    }
+
+    fn concat(&self, other: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(self.clone()),
+            op: BinaryOp::Concat,
+            right: Box::new(other),
+        }
+    }
+
+    fn alias(&self, name: &str) -> LogicalExpr {
+        LogicalExpr::Alias {
+            expr: Box::new(self.clone()),
+            name: name.to_string(),
+        }
+    }
+
+    fn in_list(&self, list: Vec<ScalarValue>) -> LogicalExpr {
+        LogicalExpr::InList {
+            expr: Box::new(self.clone()),
+            list,
+            negated: false,
+        }
+    }
+
+    fn not_in(&self, list: Vec<ScalarValue>) -> LogicalExpr {
+        LogicalExpr::InList {
+            expr: Box::new(self.clone()),
+            list,
+            negated: true,
+        }
+    }
 }
 
 // Helper functions for literals
@@ -276,3 +1041,31 @@ pub fn lit_string(v: &str) -> LogicalExpr {
 pub fn lit_bool(v: bool) -> LogicalExpr {
     LogicalExpr::Literal(LogicalValue::Boolean(v))
 }
+
+/// Typed NULL literal, e.g. to add an all-null column of `data_type`
+pub fn lit_null(data_type: DataType) -> LogicalExpr {
+    LogicalExpr::Literal(LogicalValue::Null(data_type))
+}
+
+/// Placeholder for the `index`-th value given to `DataFrame::bind`
+pub fn param(index: usize) -> LogicalExpr {
+    LogicalExpr::Param(index)
+}
+
+/// Date literal: days since 1970-01-01
+pub fn lit_date(days: i32) -> LogicalExpr {
+    LogicalExpr::Literal(LogicalValue::Date32(days))
+}
+
+/// Timestamp literal: microseconds since 1970-01-01 00:00:00 UTC
+pub fn lit_timestamp_micros(v: i64) -> LogicalExpr {
+    LogicalExpr::Literal(LogicalValue::TimestampMicros(v))
+}
+
+/// Timestamp literal parsed from an RFC 3339 string such as `2024-03-01T12:00:00Z`.
+/// A string without an offset is taken as UTC.
+pub fn lit_timestamp(rfc3339: &str) -> Result<LogicalExpr, String> {
+    let nanos = string_to_timestamp_nanos(rfc3339)
+        .map_err(|e| format!("Invalid timestamp '{}': {}", rfc3339, e))?;
+    Ok(lit_timestamp_micros(nanos.div_euclid(1000)))
+}
@@ -1,9 +1,17 @@
 // Batch/vector data structure
 
-use arrow::array::ArrayRef;
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, Float64Array, Int32Array, Int64Array, StringArray, UInt64Array,
+};
+use arrow::compute::{cast_with_options, CastOptions};
 use arrow::record_batch::RecordBatch as ArrowRecordBatch;
+use arrow::row::{RowConverter, SortField};
+use crate::storage::parquet_reader::is_supported_type;
+use crate::types::ScalarValue;
 use std::sync::Arc;
 pub use arrow::datatypes::{Schema, SchemaRef};
+use arrow::datatypes::{DataType, Field, TimeUnit};
+use std::fmt;
 
 /// RecordBatch wraps Arrow's columnar data format for vectorized execution
 /// Provides an abstraction layer over Arrow's RecordBatch for later extensions
@@ -56,6 +64,37 @@ impl RecordBatch {
         })
     }
 
+    /// Create a RecordBatch with an explicit row count. Needed for zero-column
+    /// batches, whose row count can't be recovered from the columns.
+    pub fn try_new_with_row_count(
+        schema: SchemaRef,
+        columns: Vec<ArrayRef>,
+        num_rows: usize,
+    ) -> Result<Self, String> {
+        let batch = Self::try_new(schema, columns)?;
+        if !batch.columns.is_empty() && batch.num_rows != num_rows {
+            return Err(format!(
+                "Columns have length {} but expected {}",
+                batch.num_rows, num_rows
+            ));
+        }
+        Ok(Self { num_rows, ..batch })
+    }
+
+    /// Create a RecordBatch with zero rows for the given schema
+    pub fn new_empty(schema: SchemaRef) -> Self {
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| arrow::array::new_empty_array(f.data_type()))
+            .collect();
+        Self {
+            schema,
+            columns,
+            num_rows: 0,
+        }
+    }
+
     /// Create a new RecordBatch from an ArrowRecordBatch
     pub fn from_arrow(batch: ArrowRecordBatch) -> Self {
         Self {
@@ -65,6 +104,28 @@ impl RecordBatch {
         }
     }
 
+    /// Create a RecordBatch from an ArrowRecordBatch from an untrusted source (e.g. IPC
+    /// or the network), checking every array with `validate` first
+    pub fn from_arrow_validated(batch: ArrowRecordBatch) -> Result<Self, String> {
+        let batch = Self::from_arrow(batch);
+        batch.validate()?;
+        Ok(batch)
+    }
+
+    /// Check each column's buffers, offsets, UTF-8 and child data for internal
+    /// consistency. Arrays built by Arrow's safe constructors always pass; a malformed
+    /// array (e.g. offsets past the end of its values) would otherwise panic or read
+    /// garbage deeper in the engine.
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, column) in self.schema.fields().iter().zip(&self.columns) {
+            column
+                .to_data()
+                .validate_full()
+                .map_err(|e| format!("Invalid data in column '{}': {}", field.name(), e))?;
+        }
+        Ok(())
+    }
+
     /// Convert this RecordBatch to an Arrow RecordBatch
     pub fn to_arrow(&self) -> Result<ArrowRecordBatch, String> {
         ArrowRecordBatch::try_new(self.schema.clone(), self.columns.clone())
@@ -108,6 +169,69 @@ impl RecordBatch {
         self.columns.get(index)
     }
 
+    /// The named Int32 column
+    pub fn i32_column(&self, name: &str) -> Result<&Int32Array, String> {
+        self.typed_column(name, DataType::Int32)
+    }
+
+    /// The named Int64 column
+    pub fn i64_column(&self, name: &str) -> Result<&Int64Array, String> {
+        self.typed_column(name, DataType::Int64)
+    }
+
+    /// The named Float64 column
+    pub fn f64_column(&self, name: &str) -> Result<&Float64Array, String> {
+        self.typed_column(name, DataType::Float64)
+    }
+
+    /// The named Utf8 column
+    pub fn str_column(&self, name: &str) -> Result<&StringArray, String> {
+        self.typed_column(name, DataType::Utf8)
+    }
+
+    /// The named Boolean column
+    pub fn bool_column(&self, name: &str) -> Result<&BooleanArray, String> {
+        self.typed_column(name, DataType::Boolean)
+    }
+
+    /// Downcast the named column, naming its actual type if it is not `expected`
+    fn typed_column<T: Array + 'static>(
+        &self,
+        name: &str,
+        expected: DataType,
+    ) -> Result<&T, String> {
+        let column = self
+            .column_by_name(name)
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+        column.as_any().downcast_ref::<T>().ok_or_else(|| {
+            format!("Column '{}' is {:?}, not {:?}", name, column.data_type(), expected)
+        })
+    }
+
+    /// Value of the named column at `row`; nulls become `ScalarValue::Null`
+    pub fn get_value(&self, column: &str, row: usize) -> Result<ScalarValue, String> {
+        let col = self
+            .column_by_name(column)
+            .ok_or_else(|| format!("Column '{}' not found", column))?;
+        if row >= self.num_rows {
+            return Err(format!(
+                "Row {} out of bounds for batch with {} rows",
+                row, self.num_rows
+            ));
+        }
+        scalar_at(col, row).map_err(|e| format!("{} in column '{}'", e, column))
+    }
+
+    /// Iterate over the rows as `Vec<ScalarValue>`, one value per column.
+    /// Every value is downcast and copied individually, so this is much slower than
+    /// working on columns; meant for tests and small results.
+    pub fn rows(&self) -> RowIter<'_> {
+        RowIter {
+            batch: self,
+            row: 0,
+        }
+    }
+
     /// Select a subset of columns by indices
     /// 
     /// # Arguments
@@ -123,7 +247,7 @@ impl RecordBatch {
                     .fields()
                     .get(idx)
                     .ok_or_else(|| format!("Column index {} out of bounds", idx))
-                    .map(|f| f.clone())
+                    .cloned()
             })
             .collect::<Result<_, _>>()?;
 
@@ -133,13 +257,14 @@ impl RecordBatch {
                 self.columns
                     .get(idx)
                     .ok_or_else(|| format!("Column index {} out of bounds", idx))
-                    .map(|c| c.clone())
+                    .cloned()
             })
             .collect::<Result<_, _>>()?;
 
         let schema = Arc::new(Schema::new(fields));
 
-        Self::try_new(schema, columns)
+        // Keep the row count, so selecting no columns still has this batch's rows
+        Self::try_new_with_row_count(schema, columns, self.num_rows)
     }
 
     /// Select a subset of columns by name
@@ -185,27 +310,56 @@ impl RecordBatch {
             .map(|col| col.slice(offset, length))
             .collect();
 
-        Self::try_new(self.schema.clone(), sliced_columns)
+        Self::try_new_with_row_count(self.schema.clone(), sliced_columns, length)
+    }
+
+    /// Keep the rows where `mask` is true (null counts as false)
+    pub fn filter(&self, mask: &BooleanArray) -> Result<Self, String> {
+        if mask.len() != self.num_rows {
+            return Err(format!(
+                "Filter mask has length {} but batch has {} rows",
+                mask.len(),
+                self.num_rows
+            ));
+        }
+        let columns: Vec<ArrayRef> = self
+            .columns
+            .iter()
+            .map(|col| {
+                arrow::compute::filter(col, mask)
+                    .map_err(|e| format!("Failed to filter column: {}", e))
+            })
+            .collect::<Result<_, _>>()?;
+        // Take the row count from the mask so zero-column batches keep it too
+        Self::try_new_with_row_count(self.schema.clone(), columns, mask.true_count())
     }
 
     /// Concatenate multiple RecordBatches together
-    /// All batches must have the same schema
+    /// All batches must have the same schema. A single batch is returned as is (its
+    /// columns shared, not copied), and batches with no rows give a zero-row batch.
     pub fn concat(batches: &[Self]) -> Result<Self, String> {
-        if batches.is_empty() {
-            return Err("Cannot concatenate empty batch list".to_string());
+        match batches {
+            [] => return Err("Cannot concatenate empty batch list".to_string()),
+            [batch] => return Ok(batch.clone()),
+            _ => {}
         }
 
         // Verify all batches have the same schema
         let first_schema = batches[0].schema();
         for (idx, batch) in batches.iter().enumerate().skip(1) {
-            if batch.schema() != first_schema {
+            if let Err(mismatch) = batches[0].schema_matches(batch) {
                 return Err(format!(
-                    "Batch {} has different schema than first batch",
-                    idx
+                    "Batch {} has different schema than first batch: {}",
+                    idx, mismatch
                 ));
             }
         }
 
+        let total_rows: usize = batches.iter().map(|b| b.num_rows).sum();
+        if total_rows == 0 {
+            return Ok(Self::new_empty(first_schema.clone()));
+        }
+
         // Concatenate columns
         let num_columns = first_schema.fields().len();
         let mut concatenated_columns = Vec::with_capacity(num_columns);
@@ -225,13 +379,138 @@ impl RecordBatch {
             concatenated_columns.push(concatenated);
         }
 
-        let total_rows: usize = batches.iter().map(|b| b.num_rows).sum();
+        // With the total row count, so batches of no columns concatenate too
+        Self::try_new_with_row_count(first_schema.clone(), concatenated_columns, total_rows)
+    }
 
-        Self::try_new(first_schema.clone(), concatenated_columns).map(|batch| {
-            // Verify the resulting batch has the expected number of rows
-            debug_assert_eq!(batch.num_rows, total_rows);
-            batch
-        })
+    /// Check that `other` has the same fields as this batch: names, order, types and
+    /// nullability. On a mismatch, returns every difference found.
+    pub fn schema_matches(&self, other: &RecordBatch) -> Result<(), SchemaMismatch> {
+        let mismatch = SchemaMismatch::between(&self.schema, &other.schema);
+        if mismatch.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatch)
+        }
+    }
+
+    /// Return a new batch with `array` appended as the last column, described by `field`
+    ///
+    /// # Errors
+    /// Returns an error if the array length differs from the batch's row count,
+    /// the array type differs from the field type, or the name is already taken
+    pub fn append_column(&self, field: Field, array: ArrayRef) -> Result<Self, String> {
+        if !self.columns.is_empty() && array.len() != self.num_rows {
+            return Err(format!(
+                "Column '{}' has length {} but batch has {} rows",
+                field.name(),
+                array.len(),
+                self.num_rows
+            ));
+        }
+        if field.data_type() != array.data_type() {
+            return Err(format!(
+                "Column '{}' is declared as {:?} but array is {:?}",
+                field.name(),
+                field.data_type(),
+                array.data_type()
+            ));
+        }
+        if self.schema.fields().iter().any(|f| f.name() == field.name()) {
+            return Err(format!("Column '{}' already exists", field.name()));
+        }
+
+        let mut fields: Vec<_> = self.schema.fields().iter().cloned().collect();
+        fields.push(Arc::new(field));
+        let mut columns = self.columns.clone();
+        columns.push(array);
+
+        Self::try_new(Arc::new(Schema::new(fields)), columns)
+    }
+
+    /// Return a new batch without the column named `name`
+    pub fn drop_column(&self, name: &str) -> Result<Self, String> {
+        let index = self
+            .schema
+            .fields()
+            .iter()
+            .position(|f| f.name() == name)
+            .ok_or_else(|| format!("Column '{}' not found in schema", name))?;
+        let indices: Vec<usize> = (0..self.columns.len()).filter(|&i| i != index).collect();
+        let dropped = self.select_columns(&indices)?;
+        // Keep the row count even when the last column is dropped
+        Self::try_new_with_row_count(dropped.schema, dropped.columns, self.num_rows)
+    }
+
+    /// Return a new batch with the column named `name` cast to `to`, keeping its name,
+    /// nullability and position. The target must be a type the engine supports, and a
+    /// value that does not convert (e.g. 'abc' to Int32) is an error, not a null.
+    pub fn cast_column(&self, name: &str, to: DataType) -> Result<Self, String> {
+        let index = self
+            .schema
+            .index_of(name)
+            .map_err(|_| format!("Column '{}' not found in schema", name))?;
+        if !is_supported_type(&to) {
+            return Err(format!("Cannot cast column '{}' to unsupported type {:?}", name, to));
+        }
+        let options = CastOptions {
+            safe: false,
+            ..CastOptions::default()
+        };
+        let cast = cast_with_options(&self.columns[index], &to, &options).map_err(|e| {
+            format!(
+                "Failed to cast column '{}' from {:?} to {:?}: {}",
+                name,
+                self.columns[index].data_type(),
+                to,
+                e
+            )
+        })?;
+
+        let mut fields: Vec<Field> =
+            self.schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+        fields[index] = fields[index].clone().with_data_type(to);
+        let mut columns = self.columns.clone();
+        columns[index] = cast;
+        Self::try_new_with_row_count(Arc::new(Schema::new(fields)), columns, self.num_rows)
+    }
+
+    /// Hash each row over the named columns into a UInt64 column, e.g. for bucketing or
+    /// sampling. Rows with equal values (nulls included) hash equal, and the hash is the
+    /// same across batches, processes and runs: it is FNV-1a over Arrow's row format.
+    pub fn hash_rows(&self, columns: &[&str]) -> Result<ArrayRef, String> {
+        if columns.is_empty() {
+            return Err("hash_rows needs at least one column".to_string());
+        }
+        let arrays = columns
+            .iter()
+            .map(|name| {
+                let array = self
+                    .column_by_name(name)
+                    .ok_or_else(|| format!("Column '{}' not found in schema", name))?;
+                // The row format of a dictionary depends on the order values were seen
+                match array.data_type() {
+                    DataType::Dictionary(_, value) => arrow::compute::cast(array, value)
+                        .map_err(|e| format!("Failed to decode column '{}': {}", name, e)),
+                    _ => Ok(array.clone()),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let converter = RowConverter::new(
+            arrays.iter().map(|a| SortField::new(a.data_type().clone())).collect(),
+        )
+        .map_err(|e| format!("Failed to create row converter: {}", e))?;
+        let rows = converter
+            .convert_columns(&arrays)
+            .map_err(|e| format!("Failed to convert rows: {}", e))?;
+        let hashes: UInt64Array = rows.iter().map(|row| fnv1a(row.as_ref())).collect();
+        Ok(Arc::new(hashes))
+    }
+
+    /// Approximate memory footprint in bytes: the sum of each column's buffer sizes.
+    /// Sliced columns report their full underlying buffers, since that memory stays alive.
+    pub fn num_bytes(&self) -> usize {
+        self.columns.iter().map(|c| c.get_array_memory_size()).sum()
     }
 
     /// Check if the batch is empty (has zero rows)
@@ -240,6 +519,216 @@ impl RecordBatch {
     }
 }
 
+/// 64-bit FNV-1a, a fixed hash function so hashes stay stable between runs and builds
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Concatenate batches as `RecordBatch::concat`, except that no batches give a zero-row
+/// batch with `schema` rather than an error
+pub fn concat_batches(batches: &[RecordBatch], schema: &SchemaRef) -> Result<RecordBatch, String> {
+    if batches.is_empty() {
+        return Ok(RecordBatch::new_empty(schema.clone()));
+    }
+    RecordBatch::concat(batches)
+}
+
+/// Iterator over the rows of a RecordBatch, created by `RecordBatch::rows`.
+/// Yields an error for a row holding a value of an unsupported type.
+pub struct RowIter<'a> {
+    batch: &'a RecordBatch,
+    row: usize,
+}
+
+impl Iterator for RowIter<'_> {
+    type Item = Result<Vec<ScalarValue>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.row >= self.batch.num_rows {
+            return None;
+        }
+        let row = self.row;
+        self.row += 1;
+        Some(
+            self.batch
+                .columns
+                .iter()
+                .zip(self.batch.schema.fields())
+                .map(|(col, field)| {
+                    scalar_at(col, row).map_err(|e| format!("{} in column '{}'", e, field.name()))
+                })
+                .collect(),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.batch.num_rows - self.row;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for RowIter<'_> {}
+
+/// Value of `col` at `row`; nulls become `ScalarValue::Null`
+fn scalar_at(col: &ArrayRef, row: usize) -> Result<ScalarValue, String> {
+    use arrow::array::*;
+    if col.is_null(row) {
+        return Ok(ScalarValue::Null);
+    }
+    let any = col.as_any();
+    let value = match col.data_type() {
+        DataType::Int32 => any
+            .downcast_ref::<Int32Array>()
+            .map(|a| ScalarValue::Int32(a.value(row))),
+        DataType::Int64 => any
+            .downcast_ref::<Int64Array>()
+            .map(|a| ScalarValue::Int64(a.value(row))),
+        DataType::Float64 => any
+            .downcast_ref::<Float64Array>()
+            .map(|a| ScalarValue::Float64(a.value(row))),
+        DataType::Utf8 => any
+            .downcast_ref::<StringArray>()
+            .map(|a| ScalarValue::Utf8(a.value(row).to_string())),
+        DataType::LargeUtf8 => any
+            .downcast_ref::<LargeStringArray>()
+            .map(|a| ScalarValue::Utf8(a.value(row).to_string())),
+        DataType::Boolean => any
+            .downcast_ref::<BooleanArray>()
+            .map(|a| ScalarValue::Boolean(a.value(row))),
+        DataType::Date32 => any
+            .downcast_ref::<Date32Array>()
+            .map(|a| ScalarValue::Date32(a.value(row))),
+        // Any unit or timezone, as the UTC instant (nanoseconds are truncated)
+        DataType::Timestamp(unit, _) => timestamp_at(col, row).map(|v| {
+            ScalarValue::TimestampMicros(match unit {
+                TimeUnit::Second => v * 1_000_000,
+                TimeUnit::Millisecond => v * 1_000,
+                TimeUnit::Microsecond => v,
+                TimeUnit::Nanosecond => v.div_euclid(1_000),
+            })
+        }),
+        other => return Err(format!("Unsupported type {:?}", other)),
+    };
+    value.ok_or_else(|| "Array does not match its declared type".to_string())
+}
+
+/// Value of a timestamp column at `row`, in the column's own unit
+pub(crate) fn timestamp_at(col: &ArrayRef, row: usize) -> Option<i64> {
+    use arrow::array::*;
+    let any = col.as_any();
+    match col.data_type() {
+        DataType::Timestamp(TimeUnit::Second, _) => any
+            .downcast_ref::<TimestampSecondArray>()
+            .map(|a| a.value(row)),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => any
+            .downcast_ref::<TimestampMillisecondArray>()
+            .map(|a| a.value(row)),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => any
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .map(|a| a.value(row)),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => any
+            .downcast_ref::<TimestampNanosecondArray>()
+            .map(|a| a.value(row)),
+        _ => None,
+    }
+}
+
+/// Differences between two schemas, with fields matched by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// Fields of the left schema absent from the right
+    pub missing_in_right: Vec<String>,
+    /// Fields of the right schema absent from the left
+    pub missing_in_left: Vec<String>,
+    /// Fields present in both with different types: (name, left type, right type)
+    pub type_mismatches: Vec<(String, DataType, DataType)>,
+    /// Fields present in both with different nullability: (name, left, right)
+    pub nullability_mismatches: Vec<(String, bool, bool)>,
+    /// Fields present in both appear in a different order
+    pub order_differs: bool,
+}
+
+impl SchemaMismatch {
+    /// Compare two schemas field by field
+    pub fn between(left: &Schema, right: &Schema) -> Self {
+        let mut mismatch = SchemaMismatch::default();
+        let mut common_order = Vec::new();
+        for l in left.fields() {
+            match right.fields().iter().position(|r| r.name() == l.name()) {
+                None => mismatch.missing_in_right.push(l.name().clone()),
+                Some(index) => {
+                    let r = &right.fields()[index];
+                    if l.data_type() != r.data_type() {
+                        mismatch.type_mismatches.push((
+                            l.name().clone(),
+                            l.data_type().clone(),
+                            r.data_type().clone(),
+                        ));
+                    }
+                    if l.is_nullable() != r.is_nullable() {
+                        mismatch.nullability_mismatches.push((
+                            l.name().clone(),
+                            l.is_nullable(),
+                            r.is_nullable(),
+                        ));
+                    }
+                    common_order.push(index);
+                }
+            }
+        }
+        for r in right.fields() {
+            if left.field_with_name(r.name()).is_err() {
+                mismatch.missing_in_left.push(r.name().clone());
+            }
+        }
+        mismatch.order_differs = common_order.windows(2).any(|w| w[0] > w[1]);
+        mismatch
+    }
+
+    /// True when the schemas match exactly
+    pub fn is_empty(&self) -> bool {
+        self.nullability_mismatches.is_empty() && self.types_match()
+    }
+
+    /// True when the schemas differ at most in nullability
+    pub fn types_match(&self) -> bool {
+        self.missing_in_right.is_empty()
+            && self.missing_in_left.is_empty()
+            && self.type_mismatches.is_empty()
+            && !self.order_differs
+    }
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nullability = |nullable: bool| if nullable { "nullable" } else { "non-nullable" };
+        let mut parts = Vec::new();
+        if !self.missing_in_right.is_empty() {
+            parts.push(format!("missing in right: {}", self.missing_in_right.join(", ")));
+        }
+        if !self.missing_in_left.is_empty() {
+            parts.push(format!("missing in left: {}", self.missing_in_left.join(", ")));
+        }
+        for (name, left, right) in &self.type_mismatches {
+            parts.push(format!("'{}' is {:?} vs {:?}", name, left, right));
+        }
+        for (name, left, right) in &self.nullability_mismatches {
+            parts.push(format!(
+                "'{}' is {} vs {}",
+                name,
+                nullability(*left),
+                nullability(*right)
+            ));
+        }
+        if self.order_differs {
+            parts.push("columns are in a different order".to_string());
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
 impl From<ArrowRecordBatch> for RecordBatch {
     fn from(batch: ArrowRecordBatch) -> Self {
         Self::from_arrow(batch)
@@ -258,7 +747,6 @@ impl TryFrom<RecordBatch> for ArrowRecordBatch {
 mod tests {
     use super::*;
     use arrow::array::{BooleanArray, Int32Array, StringArray};
-    use arrow::datatypes::{Field, DataType};
 
     fn create_test_schema() -> SchemaRef {
         Arc::new(Schema::new(vec![
@@ -338,6 +826,201 @@ mod tests {
         assert_eq!(concatenated.num_columns(), 3);
     }
 
+    #[test]
+    fn test_concat_single_and_empty_batches() {
+        let batch = create_test_batch();
+        let single = RecordBatch::concat(std::slice::from_ref(&batch)).unwrap();
+        assert!(Arc::ptr_eq(single.column(1).unwrap(), batch.column(1).unwrap()));
+
+        let empty = batch.slice(0, 0).unwrap();
+        let concatenated = RecordBatch::concat(&[empty.clone(), empty]).unwrap();
+        assert_eq!(concatenated.num_rows(), 0);
+        assert_eq!(concatenated.schema(), batch.schema());
+    }
+
+    #[test]
+    fn test_concat_batches_with_schema() {
+        let batch = create_test_batch();
+        let schema = batch.schema().clone();
+        let empty = concat_batches(&[], &schema).unwrap();
+        assert_eq!(empty.num_rows(), 0);
+        assert_eq!(empty.schema(), &schema);
+
+        let concatenated = concat_batches(&[batch.clone(), batch], &schema).unwrap();
+        assert_eq!(concatenated.num_rows(), 6);
+        assert_eq!(concatenated.schema(), &schema);
+    }
+
+    fn batch_with_fields(fields: Vec<Field>) -> RecordBatch {
+        RecordBatch::new_empty(Arc::new(Schema::new(fields)))
+    }
+
+    #[test]
+    fn test_schema_matches() {
+        let batch = create_test_batch();
+        assert!(batch.schema_matches(&create_test_batch()).is_ok());
+
+        // Missing fields on either side
+        let other = batch_with_fields(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("extra", DataType::Int64, false),
+        ]);
+        let mismatch = batch.schema_matches(&other).unwrap_err();
+        assert_eq!(mismatch.missing_in_right, vec!["active".to_string()]);
+        assert_eq!(mismatch.missing_in_left, vec!["extra".to_string()]);
+        assert!(!mismatch.types_match());
+
+        // Type mismatch
+        let other = batch_with_fields(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("active", DataType::Boolean, false),
+        ]);
+        let mismatch = batch.schema_matches(&other).unwrap_err();
+        assert_eq!(
+            mismatch.type_mismatches,
+            vec![("id".to_string(), DataType::Int32, DataType::Int64)]
+        );
+        assert_eq!(mismatch.to_string(), "'id' is Int32 vs Int64");
+
+        // Nullability only: types still match
+        let other = batch_with_fields(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("active", DataType::Boolean, false),
+        ]);
+        let mismatch = batch.schema_matches(&other).unwrap_err();
+        assert_eq!(
+            mismatch.nullability_mismatches,
+            vec![("name".to_string(), false, true)]
+        );
+        assert!(mismatch.types_match());
+
+        // Same fields, different order
+        let other = batch_with_fields(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("id", DataType::Int32, false),
+            Field::new("active", DataType::Boolean, false),
+        ]);
+        let mismatch = batch.schema_matches(&other).unwrap_err();
+        assert!(mismatch.order_differs);
+        assert!(mismatch.missing_in_left.is_empty() && mismatch.type_mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_concat_reports_schema_mismatch() {
+        let other = batch_with_fields(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, false),
+        ]);
+        let err = RecordBatch::concat(&[create_test_batch(), other]).unwrap_err();
+        assert!(err.contains("missing in right: active"), "{}", err);
+    }
+
+    #[test]
+    fn test_num_bytes() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(arrow::array::Int64Array::from(vec![0i64; 1000])),
+                Arc::new(Int32Array::from(vec![0; 1000])),
+            ],
+        )
+        .unwrap();
+        // 12 bytes of values per row, plus buffer and array overhead
+        let bytes = batch.num_bytes();
+        assert!((12_000..16_000).contains(&bytes), "{} bytes", bytes);
+        assert_eq!(batch.slice(0, 10).unwrap().num_bytes(), bytes);
+        assert!(RecordBatch::new_empty(schema).num_bytes() < 1_000);
+    }
+
+    #[test]
+    fn test_get_value() {
+        let batch = create_test_batch();
+        assert_eq!(batch.get_value("id", 1).unwrap(), ScalarValue::Int32(2));
+        assert_eq!(
+            batch.get_value("name", 2).unwrap(),
+            ScalarValue::Utf8("Charlie".to_string())
+        );
+        assert_eq!(batch.get_value("active", 1).unwrap(), ScalarValue::Boolean(false));
+        assert!(batch.get_value("id", 3).is_err());
+        assert!(batch.get_value("missing", 0).is_err());
+
+        let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, true)]));
+        let nulls = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(arrow::array::Int64Array::from(vec![None, Some(5)]))],
+        )
+        .unwrap();
+        assert_eq!(nulls.get_value("n", 0).unwrap(), ScalarValue::Null);
+        assert_eq!(i64::try_from(nulls.get_value("n", 1).unwrap()), Ok(5));
+        assert!(i32::try_from(nulls.get_value("n", 1).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_rows() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("score", DataType::Float64, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("active", DataType::Boolean, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(arrow::array::Int64Array::from(vec![1, 2])),
+                Arc::new(arrow::array::Float64Array::from(vec![Some(0.5), None])),
+                Arc::new(StringArray::from(vec![None, Some("b")])),
+                Arc::new(BooleanArray::from(vec![true, false])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(batch.rows().len(), 2);
+        let rows: Vec<Vec<ScalarValue>> = batch.rows().collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    ScalarValue::Int64(1),
+                    ScalarValue::Float64(0.5),
+                    ScalarValue::Null,
+                    ScalarValue::Boolean(true),
+                ],
+                vec![
+                    ScalarValue::Int64(2),
+                    ScalarValue::Null,
+                    ScalarValue::Utf8("b".to_string()),
+                    ScalarValue::Boolean(false),
+                ],
+            ]
+        );
+
+        let temporal = Arc::new(Schema::new(vec![
+            Field::new("d", DataType::Date32, false),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Nanosecond, None), false),
+        ]));
+        let batch = RecordBatch::try_new(
+            temporal,
+            vec![
+                Arc::new(arrow::array::Date32Array::from(vec![19_783])),
+                Arc::new(arrow::array::TimestampNanosecondArray::from(vec![-1_500])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            batch.rows().next().unwrap().unwrap(),
+            vec![
+                ScalarValue::Date32(19_783),
+                ScalarValue::TimestampMicros(-2)
+            ]
+        );
+    }
+
     #[test]
     fn test_arrow_conversion() {
         let batch = create_test_batch();
@@ -364,6 +1047,68 @@ mod tests {
         assert_eq!(batch.num_rows(), 0);
     }
 
+    #[test]
+    fn test_append_column() {
+        let batch = create_test_batch();
+        let ids = batch.column_by_name("id").unwrap();
+        let doubled = arrow::compute::kernels::numeric::add(ids, ids).unwrap();
+
+        let appended = batch
+            .append_column(Field::new("id2", DataType::Int32, false), doubled.clone())
+            .unwrap();
+        assert_eq!(appended.num_columns(), 4);
+        assert_eq!(appended.schema().field(3).name(), "id2");
+        let id2 = appended.column_by_name("id2").unwrap();
+        let id2 = id2.as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(id2.values().to_vec(), vec![2, 4, 6]);
+        // The original batch is unchanged
+        assert_eq!(batch.num_columns(), 3);
+
+        // Wrong length, wrong type and duplicate names are rejected
+        let short: ArrayRef = Arc::new(Int32Array::from(vec![1]));
+        assert!(batch.append_column(Field::new("x", DataType::Int32, false), short).is_err());
+        assert!(batch
+            .append_column(Field::new("x", DataType::Utf8, false), doubled.clone())
+            .is_err());
+        assert!(batch.append_column(Field::new("id", DataType::Int32, false), doubled).is_err());
+    }
+
+    #[test]
+    fn test_drop_column() {
+        let batch = create_test_batch();
+        let dropped = batch.drop_column("name").unwrap();
+        assert_eq!(dropped.num_columns(), 2);
+        assert_eq!(dropped.num_rows(), 3);
+        assert!(dropped.column_by_name("name").is_none());
+        assert_eq!(dropped.schema().field(1).name(), "active");
+
+        assert!(batch.drop_column("nonexistent").is_err());
+    }
+
+    #[test]
+    fn test_cast_column() {
+        let batch = create_test_batch();
+        let cast = batch.cast_column("id", DataType::Float64).unwrap();
+        assert_eq!(cast.schema().field(0).data_type(), &DataType::Float64);
+        assert_eq!(cast.f64_column("id").unwrap().values().to_vec(), vec![1.0, 2.0, 3.0]);
+        // Other columns are untouched
+        assert_eq!(cast.schema().field(1), batch.schema().field(1));
+        assert!(Arc::ptr_eq(&cast.columns()[2], &batch.columns()[2]));
+
+        let err = batch.cast_column("name", DataType::Int32).unwrap_err();
+        assert!(err.starts_with("Failed to cast column 'name' from Utf8 to Int32"), "{}", err);
+        assert!(batch.cast_column("id", DataType::UInt8).is_err());
+        assert!(batch.cast_column("missing", DataType::Int64).is_err());
+    }
+
+    #[test]
+    fn test_new_empty() {
+        let batch = RecordBatch::new_empty(create_test_schema());
+        assert!(batch.is_empty());
+        assert_eq!(batch.num_columns(), 3);
+        assert!(batch.to_arrow().is_ok());
+    }
+
     #[test]
     fn test_invalid_batch() {
         let schema = create_test_schema();
@@ -382,4 +1127,103 @@ mod tests {
         ];
         assert!(RecordBatch::try_new(schema, columns).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate() {
+        let batch = create_test_batch();
+        assert!(batch.validate().is_ok());
+        assert!(RecordBatch::from_arrow_validated(batch.to_arrow().unwrap()).is_ok());
+
+        // A string array whose offsets point past its 3 value bytes, as an untrusted
+        // source could send; the array's length checks alone do not notice
+        let offsets = arrow::buffer::Buffer::from_slice_ref([0i32, 2, 10]);
+        let values = arrow::buffer::Buffer::from_slice_ref(b"abc");
+        let data = unsafe {
+            arrow::array::ArrayData::builder(DataType::Utf8)
+                .len(2)
+                .add_buffer(offsets)
+                .add_buffer(values)
+                .build_unchecked()
+        };
+        let schema = Arc::new(Schema::new(vec![Field::new("s", DataType::Utf8, false)]));
+        let column = arrow::array::make_array(data);
+        let batch = RecordBatch::try_new(schema, vec![column]).unwrap();
+        let err = batch.validate().unwrap_err();
+        assert!(err.starts_with("Invalid data in column 's'"), "{}", err);
+        assert!(RecordBatch::from_arrow_validated(batch.to_arrow().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_hash_rows() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), Some(2), Some(1), Some(1), None])),
+                Arc::new(StringArray::from(vec!["a", "b", "a", "c", "a"])),
+            ],
+        )
+        .unwrap();
+        let hashes = |batch: &RecordBatch, columns: &[&str]| -> Vec<u64> {
+            let array = batch.hash_rows(columns).unwrap();
+            let array = array.as_any().downcast_ref::<UInt64Array>().unwrap();
+            array.values().to_vec()
+        };
+
+        let both = hashes(&batch, &["id", "name"]);
+        assert_eq!(both[0], both[2]);
+        for other in [1, 3, 4] {
+            assert_ne!(both[0], both[other]);
+        }
+        let ids = hashes(&batch, &["id"]);
+        assert_eq!((ids[0], ids[0]), (ids[2], ids[3]));
+        assert_ne!(ids[0], ids[4]);
+
+        // Stable across batches and dictionary encoding, and pinned across runs
+        let later = batch.slice(2, 3).unwrap();
+        assert_eq!(hashes(&later, &["id", "name"]), both[2..].to_vec());
+        let dict: arrow::array::DictionaryArray<arrow::datatypes::Int32Type> =
+            vec!["c", "a", "b"].into_iter().collect();
+        let dict_batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("name", dict.data_type().clone(), false)])),
+            vec![Arc::new(dict)],
+        )
+        .unwrap();
+        let names = hashes(&batch, &["name"]);
+        assert_eq!(hashes(&dict_batch, &["name"]), vec![names[3], names[0], names[1]]);
+        assert_eq!(both[0], 14142968061209050245);
+
+        // Many distinct rows give distinct hashes
+        let many = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from((0..10_000).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(vec!["x"; 10_000])),
+            ],
+        )
+        .unwrap();
+        let distinct: std::collections::HashSet<u64> =
+            hashes(&many, &["id", "name"]).into_iter().collect();
+        assert_eq!(distinct.len(), 10_000);
+
+        assert!(batch.hash_rows(&[]).is_err());
+        assert!(batch.hash_rows(&["missing"]).is_err());
+    }
+
+    #[test]
+    fn test_typed_column_accessors() {
+        let batch = create_test_batch();
+        assert_eq!(batch.i32_column("id").unwrap().values().to_vec(), vec![1, 2, 3]);
+        assert_eq!(batch.str_column("name").unwrap().value(1), "Bob");
+        assert!(batch.bool_column("active").unwrap().value(0));
+
+        let err = batch.f64_column("id").unwrap_err();
+        assert_eq!(err, "Column 'id' is Int32, not Float64");
+        let err = batch.i64_column("name").unwrap_err();
+        assert_eq!(err, "Column 'name' is Utf8, not Int64");
+        assert_eq!(batch.i32_column("missing").unwrap_err(), "Column 'missing' not found");
+    }
+}
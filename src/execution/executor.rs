@@ -1,23 +1,80 @@
 // Execution engine coordinator
 
-use crate::execution::batch::RecordBatch;
+use crate::execution::batch::{concat_batches, RecordBatch};
 use crate::execution::operators::{
-    AggregateOperator, FilterOperator, HashJoinOperator, Operator, ProjectOperator, ScanOperator,
-    SortOperator,
+    AggregateOperator, FillNullOperator, FilterFnOperator, FilterOperator, FilterProjectOperator,
+    HashJoinOperator, LimitOperator, Operator, ProjectOperator, RenameOperator, RowIndexOperator,
+    ScanOperator, SetOperator, SortMergeJoinOperator, SortOperator, UnpivotOperator,
+    ValuesOperator, WindowOperator,
 };
-use crate::planner::logical_plan::{AggregateFunction, JoinType, LogicalPlan};
-use crate::storage::parquet_reader::ParquetReader;
-use arrow::datatypes::{DataType, Field, Schema};
-use std::sync::Arc;
+use crate::execution::physical_plan::PhysicalPlan;
+use crate::planner::logical_plan::{unbound_param, LogicalExpr, LogicalPlan};
+use crate::storage::schema_cache::SchemaCache;
+use arrow::datatypes::Schema;
+
+/// Configuration for planning and executing queries
+#[derive(Debug, Clone)]
+pub struct ExecutorConfig {
+    /// Use sort-merge join instead of hash join for every join (default: false).
+    /// Sort-merge join is always used when both join inputs are already sorted on their keys.
+    pub prefer_sort_merge_join: bool,
+    /// Let null join keys match each other (default: false, as in SQL where
+    /// `NULL = NULL` is not true)
+    pub null_equals_null: bool,
+    /// Resolve column names ignoring ASCII case (default: false), in projections,
+    /// filters, group-by, sort, window, fill-null, rename and join keys. A name matching
+    /// a column exactly always resolves to it; otherwise it must match exactly one
+    /// column ignoring case.
+    pub case_insensitive: bool,
+    /// Run a projection directly over a filter as one fused operator, which filters only
+    /// the columns the projection reads (default: true)
+    pub fuse_filter_project: bool,
+    /// Report integer overflow in +, - and * as an error (default: false, wrapping
+    /// around as Arrow's default kernels do)
+    pub checked_arithmetic: bool,
+    /// Merge adjacent output batches of filters (and filtered scans) with fewer than
+    /// this many rows into larger ones (default: None, batches are left as produced)
+    pub coalesce_batch_rows: Option<usize>,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            prefer_sort_merge_join: false,
+            null_equals_null: false,
+            case_insensitive: false,
+            fuse_filter_project: true,
+            checked_arithmetic: false,
+            coalesce_batch_rows: None,
+        }
+    }
+}
 
 /// Executor that coordinates the execution of logical plans
 /// Converts logical plans to physical operators and executes them
-pub struct Executor;
+pub struct Executor {
+    config: ExecutorConfig,
+    /// Parquet schemas parsed while planning, reused by later scans of unchanged files
+    schema_cache: SchemaCache,
+}
 
 impl Executor {
     /// Create a new executor
     pub fn new() -> Self {
-        Self
+        Self::with_config(ExecutorConfig::default())
+    }
+
+    /// Create a new executor with configuration
+    pub fn with_config(config: ExecutorConfig) -> Self {
+        Self {
+            config,
+            schema_cache: SchemaCache::new(),
+        }
+    }
+
+    /// The Parquet schema cache shared by all scans planned by this executor
+    pub fn schema_cache(&self) -> &SchemaCache {
+        &self.schema_cache
     }
 
     /// Execute a logical plan and return the results
@@ -28,180 +85,451 @@ impl Executor {
     /// # Returns
     /// Result containing vector of RecordBatches with the query results
     pub fn execute(&self, plan: &LogicalPlan) -> Result<Vec<RecordBatch>, String> {
+        self.plan_to_run(plan)?.execute()
+    }
+
+    /// Execute a logical plan and concatenate the results into one RecordBatch.
+    /// An empty result is a zero-row batch with the plan's output schema.
+    pub fn execute_single(&self, plan: &LogicalPlan) -> Result<RecordBatch, String> {
+        let physical_plan = self.plan_to_run(plan)?;
+        concat_batches(&physical_plan.execute()?, &physical_plan.schema())
+    }
+
+    /// Execute a logical plan, passing each result batch to `f` as it is produced
+    /// (see `PhysicalPlan::execute_each`). Stops at the first error from `f`.
+    pub fn execute_each(
+        &self,
+        plan: &LogicalPlan,
+        f: &mut dyn FnMut(RecordBatch) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.plan_to_run(plan)?.execute_each(f)
+    }
+
+    /// The physical plan, with small filter outputs coalesced if configured
+    fn plan_to_run(&self, plan: &LogicalPlan) -> Result<PhysicalPlan, String> {
+        let physical_plan = self.create_physical_plan(plan)?;
+        Ok(match self.config.coalesce_batch_rows {
+            Some(min_rows) => physical_plan.coalesce_filtered_batches(min_rows),
+            None => physical_plan,
+        })
+    }
+
+    /// Plan a logical plan into a tree of physical operators
+    ///
+    /// Each node is bound to its input's output schema, so schema errors (unknown
+    /// columns, etc.) surface here rather than mid-execution.
+    pub fn create_physical_plan(&self, plan: &LogicalPlan) -> Result<PhysicalPlan, String> {
         match plan {
-            LogicalPlan::Scan { path, projection, .. } => {
-                // Create and execute Scan operator
-                let scan_op = ScanOperator::new(path, projection.clone())?;
-                scan_op.read_all()
+            LogicalPlan::Scan {
+                path,
+                projection,
+                filters,
+                options,
+            } => {
+                // Flattening renames columns, so it comes before projection and filters
+                let scan_op = match options.schema {
+                    Some(ref schema) => {
+                        ScanOperator::new_with_schema(path, schema.clone(), &self.schema_cache)?
+                    }
+                    None => ScanOperator::new_with_cache(path, None, &self.schema_cache)?,
+                };
+                let scan_op = scan_op.with_flatten_structs(options.flatten_structs)?;
+                let file_schema = scan_op.schema();
+                let projection = match projection {
+                    Some(columns) => Some(self.resolve_columns(columns, &file_schema)?),
+                    None => None,
+                };
+                let filters = filters
+                    .iter()
+                    .map(|filter| self.resolve_expr(filter, &file_schema))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let scan_op = scan_op
+                    .with_projection(projection)?
+                    .with_filters(filters)?
+                    .with_batch_size(options.batch_size)?
+                    .with_skip_files(&options.skip_files)
+                    .with_skip_unsupported_columns(options.skip_unsupported_columns)?
+                    .with_checked_arithmetic(self.config.checked_arithmetic);
+                Ok(PhysicalPlan::Scan(scan_op))
+            }
+            LogicalPlan::Values { schema, rows } => {
+                Ok(PhysicalPlan::Values(ValuesOperator::new(schema.clone(), rows)?))
             }
             LogicalPlan::Project { input, columns } => {
-                // Execute input first
-                let input_batches = self.execute(input)?;
-                
-                if input_batches.is_empty() {
-                    return Ok(Vec::new());
+                // Push the columns into an unprojected scan, so only they are read
+                let input = match with_scan_projection(input, columns.clone()) {
+                    Some(pruned) => self.create_physical_plan(&pruned)?,
+                    None => self.create_physical_plan(input)?,
+                };
+                let columns = self.resolve_columns(columns, &input.schema())?;
+                match input {
+                    PhysicalPlan::Filter { input, op } if self.config.fuse_filter_project => {
+                        let predicate = op.predicate().clone();
+                        let op = FilterProjectOperator::new(predicate, columns, input.schema())?
+                            .with_checked_arithmetic(self.config.checked_arithmetic);
+                        Ok(PhysicalPlan::FilterProject { input, op })
+                    }
+                    input => {
+                        let op = ProjectOperator::new(columns, input.schema())?;
+                        Ok(PhysicalPlan::Project {
+                            input: Box::new(input),
+                            op,
+                        })
+                    }
                 }
-
-                // Create Project operator using the input schema
-                let input_schema = input_batches[0].schema().clone();
-                let project_op = ProjectOperator::new(columns.clone(), input_schema)?;
-
-                // Apply projection to each batch
-                let projected_batches: Result<Vec<RecordBatch>, String> = input_batches
-                    .iter()
-                    .map(|batch| project_op.execute(batch))
-                    .collect();
-
-                projected_batches
             }
-            LogicalPlan::Filter { input, predicate } => {
-                // Execute input first
-                let input_batches = self.execute(input)?;
-                
-                if input_batches.is_empty() {
-                    return Ok(Vec::new());
+            LogicalPlan::ProjectExprs { input, exprs } => {
+                // As for Project: read only the columns the expressions use
+                let mut columns: Vec<String> = Vec::new();
+                for name in exprs.iter().flat_map(|e| e.column_names()) {
+                    if !columns.contains(&name) {
+                        columns.push(name);
+                    }
                 }
-
-                // Create Filter operator using the input schema
-                let input_schema = input_batches[0].schema().clone();
-                let filter_op = FilterOperator::new(predicate.clone(), input_schema)?;
-
-                // Apply filter to each batch
-                let filtered_batches: Result<Vec<RecordBatch>, String> = input_batches
+                let input = match with_scan_projection(input, columns) {
+                    Some(pruned) => self.create_physical_plan(&pruned)?,
+                    None => self.create_physical_plan(input)?,
+                };
+                let exprs = exprs
                     .iter()
-                    .map(|batch| filter_op.execute(batch))
-                    .collect();
-
-                // Filter out empty batches
-                let filtered_batches: Vec<RecordBatch> = filtered_batches?
-                    .into_iter()
-                    .filter(|b| !b.is_empty())
-                    .collect();
-
-                Ok(filtered_batches)
+                    .map(|expr| self.resolve_expr(expr, &input.schema()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match input {
+                    PhysicalPlan::Filter { input, op } if self.config.fuse_filter_project => {
+                        let predicate = op.predicate().clone();
+                        let schema = input.schema();
+                        let op = FilterProjectOperator::with_exprs(predicate, exprs, schema)?
+                            .with_checked_arithmetic(self.config.checked_arithmetic);
+                        Ok(PhysicalPlan::FilterProject { input, op })
+                    }
+                    input => {
+                        let op = ProjectOperator::with_exprs(exprs, input.schema())?
+                            .with_checked_arithmetic(self.config.checked_arithmetic);
+                        Ok(PhysicalPlan::Project {
+                            input: Box::new(input),
+                            op,
+                        })
+                    }
+                }
+            }
+            LogicalPlan::Filter { input, predicate } => {
+                let input = self.create_physical_plan(input)?;
+                let predicate = self.resolve_expr(predicate, &input.schema())?;
+                let op = FilterOperator::new(predicate, input.schema())?
+                    .with_checked_arithmetic(self.config.checked_arithmetic);
+                Ok(PhysicalPlan::Filter {
+                    input: Box::new(input),
+                    op,
+                })
+            }
+            LogicalPlan::FilterFn { input, predicate } => {
+                let input = self.create_physical_plan(input)?;
+                let op = FilterFnOperator::new(predicate.clone(), input.schema());
+                Ok(PhysicalPlan::FilterFn {
+                    input: Box::new(input),
+                    op,
+                })
             }
             LogicalPlan::Aggregate {
                 input,
                 group_by,
                 aggs,
+                order_by,
+                estimated_groups,
             } => {
-                let input_batches = self.execute(input)?;
-                if input_batches.is_empty() {
-                    // Build empty result with correct output schema (placeholder types for group cols)
-                    let mut fields: Vec<Field> = group_by
-                        .iter()
-                        .map(|n| Field::new(n, DataType::Utf8, true))
-                        .collect();
-                    for a in aggs {
-                        let dt = match a.function {
-                            AggregateFunction::Count => DataType::Int64,
-                            _ => DataType::Float64,
-                        };
-                        fields.push(Field::new(a.alias.as_str(), dt, true));
+                let input = self.create_physical_plan(input)?;
+                let schema = input.schema();
+                let group_by = group_by
+                    .iter()
+                    .map(|expr| self.resolve_expr(expr, &schema))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut aggs = aggs.clone();
+                for agg in &mut aggs {
+                    agg.column = self.resolve_optional(agg.column.take(), &schema)?;
+                    if let Some(filter) = &agg.filter {
+                        agg.filter = Some(self.resolve_expr(filter, &schema)?);
                     }
-                    let schema = Arc::new(Schema::new(fields));
-                    let columns: Vec<_> = schema
-                        .fields()
-                        .iter()
-                        .map(|f| arrow::array::new_empty_array(f.data_type()))
-                        .collect();
-                    let batch = RecordBatch::try_new(schema, columns)
-                        .map_err(|e| e.to_string())?;
-                    return Ok(vec![batch]);
                 }
-                let input_schema = input_batches[0].schema().clone();
-                let agg_op =
-                    AggregateOperator::new(group_by.clone(), aggs.clone(), input_schema)
-                        .map_err(|e| e.to_string())?;
-                agg_op.execute_many(&input_batches)
+                let op = AggregateOperator::new(group_by, aggs, schema)?;
+                let mut order_by = order_by.clone();
+                for expr in &mut order_by {
+                    expr.column = self.resolve(&expr.column, &op.schema())?;
+                }
+                let op = op
+                    .with_order_by(order_by)?
+                    .with_estimated_groups(*estimated_groups)
+                    .with_checked_arithmetic(self.config.checked_arithmetic);
+                Ok(PhysicalPlan::Aggregate {
+                    input: Box::new(input),
+                    op,
+                })
             }
             LogicalPlan::Sort { input, order_by } => {
-                let input_batches = self.execute(input)?;
-                if input_batches.is_empty() {
-                    return Ok(Vec::new());
+                let input = self.create_physical_plan(input)?;
+                let mut order_by = order_by.clone();
+                for expr in &mut order_by {
+                    expr.column = self.resolve(&expr.column, &input.schema())?;
+                }
+                let op = SortOperator::new(order_by, input.schema())?;
+                Ok(PhysicalPlan::Sort {
+                    input: Box::new(input),
+                    op,
+                })
+            }
+            LogicalPlan::FillNull {
+                input,
+                column,
+                value,
+            } => {
+                let input = self.create_physical_plan(input)?;
+                let column = self.resolve_optional(column.clone(), &input.schema())?;
+                let op = FillNullOperator::new(column, value.clone(), input.schema())?;
+                Ok(PhysicalPlan::FillNull {
+                    input: Box::new(input),
+                    op,
+                })
+            }
+            LogicalPlan::SetOperation { left, right, op } => {
+                let left = self.create_physical_plan(left)?;
+                let right = self.create_physical_plan(right)?;
+                let op = SetOperator::new(*op, left.schema(), right.schema())?;
+                Ok(PhysicalPlan::SetOperation {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    op,
+                })
+            }
+            LogicalPlan::Limit { input, limit } => {
+                let mut input_plan = self.create_physical_plan(input)?;
+                // Push the limit into an order-preserving scan so it stops reading early
+                if let LogicalPlan::Scan { options, .. } = input.as_ref() {
+                    if options.preserve_order {
+                        if let PhysicalPlan::Scan(scan_op) = input_plan {
+                            input_plan = PhysicalPlan::Scan(scan_op.with_limit(*limit));
+                        }
+                    }
+                }
+                // and into a sort, so it only orders and copies the rows it keeps
+                if let PhysicalPlan::Sort { input, op } = input_plan {
+                    input_plan = PhysicalPlan::Sort {
+                        input,
+                        op: op.with_limit(*limit),
+                    };
+                }
+                let op = LimitOperator::new(*limit, input_plan.schema());
+                Ok(PhysicalPlan::Limit {
+                    input: Box::new(input_plan),
+                    op,
+                })
+            }
+            LogicalPlan::RowIndex { input, name } => {
+                let input = self.create_physical_plan(input)?;
+                let op = RowIndexOperator::new(name, input.schema())?;
+                Ok(PhysicalPlan::RowIndex {
+                    input: Box::new(input),
+                    op,
+                })
+            }
+            LogicalPlan::Unpivot {
+                input,
+                id_columns,
+                value_columns,
+            } => {
+                let input = self.create_physical_plan(input)?;
+                let schema = input.schema();
+                let id_columns = self.resolve_columns(id_columns, &schema)?;
+                let value_columns = self.resolve_columns(value_columns, &schema)?;
+                let op = UnpivotOperator::new(&id_columns, &value_columns, schema)?;
+                Ok(PhysicalPlan::Unpivot {
+                    input: Box::new(input),
+                    op,
+                })
+            }
+            LogicalPlan::Rename { input, renames } => {
+                let input = self.create_physical_plan(input)?;
+                let renames = renames
+                    .iter()
+                    .map(|(old, new)| Ok((self.resolve(old, &input.schema())?, new.clone())))
+                    .collect::<Result<_, String>>()?;
+                let op = RenameOperator::new(&renames, input.schema())?;
+                Ok(PhysicalPlan::Rename {
+                    input: Box::new(input),
+                    op,
+                })
+            }
+            LogicalPlan::Alias { input, alias } => {
+                let input = self.create_physical_plan(input)?;
+                let renames = input
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|f| (f.name().clone(), format!("{}.{}", alias, f.name())))
+                    .collect();
+                let op = RenameOperator::new(&renames, input.schema())?;
+                Ok(PhysicalPlan::Rename {
+                    input: Box::new(input),
+                    op,
+                })
+            }
+            LogicalPlan::Window {
+                input,
+                partition_by,
+                order_by,
+                functions,
+            } => {
+                let input = self.create_physical_plan(input)?;
+                let schema = input.schema();
+                let partition_by = self.resolve_columns(partition_by, &schema)?;
+                let mut order_by = order_by.clone();
+                for expr in &mut order_by {
+                    expr.column = self.resolve(&expr.column, &schema)?;
                 }
-                let input_schema = input_batches[0].schema().clone();
-                let sort_op = SortOperator::new(order_by.clone(), input_schema)
-                    .map_err(|e| e.to_string())?;
-                sort_op.execute_many(&input_batches)
+                let mut functions = functions.clone();
+                for function in &mut functions {
+                    function.column = self.resolve_optional(function.column.take(), &schema)?;
+                }
+                let op = WindowOperator::new(partition_by, order_by, functions, schema)?;
+                Ok(PhysicalPlan::Window {
+                    input: Box::new(input),
+                    op,
+                })
             }
             LogicalPlan::Join {
                 left,
                 right,
                 join_type,
                 on: (left_key, right_key),
+                broadcast_right,
             } => {
-                let left_batches = self.execute(left)?;
-                let right_batches = self.execute(right)?;
-
-                if left_batches.is_empty() {
-                    return Ok(Vec::new());
+                let left_input = self.create_physical_plan(left)?;
+                let right_input = self.create_physical_plan(right)?;
+                let left_key = &self.resolve(left_key, &left_input.schema())?;
+                let right_key = &self.resolve(right_key, &right_input.schema())?;
+                // A broadcast hint always gets a streaming hash join
+                let use_sort_merge = !broadcast_right
+                    && (self.config.prefer_sort_merge_join
+                        || (self.is_sorted_on(left, left_key, &left_input.schema())?
+                            && self.is_sorted_on(right, right_key, &right_input.schema())?));
+                let (left, right) = (left_input, right_input);
+                if use_sort_merge {
+                    let op = SortMergeJoinOperator::new(
+                        left_key.clone(),
+                        right_key.clone(),
+                        *join_type,
+                        left.schema(),
+                        right.schema(),
+                    )?
+                    .with_null_equals_null(self.config.null_equals_null);
+                    return Ok(PhysicalPlan::SortMergeJoin {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        op,
+                    });
                 }
-                let left_schema = left_batches[0].schema().clone();
-                let right_schema = right_batches
-                    .first()
-                    .map(|b| b.schema().clone())
-                    .or_else(|| self.get_schema(right).ok())
-                    .ok_or("Join right side has no batches and schema could not be determined")?;
-
-                let join_op = HashJoinOperator::new(
+                let op = HashJoinOperator::new(
                     left_key.clone(),
                     right_key.clone(),
                     *join_type,
-                    left_schema,
-                    right_schema,
-                )
-                .map_err(|e| e.to_string())?;
-                join_op.execute_join(&left_batches, &right_batches)
+                    left.schema(),
+                    right.schema(),
+                )?
+                .with_streaming_probe(*broadcast_right)
+                .with_null_equals_null(self.config.null_equals_null);
+                Ok(PhysicalPlan::HashJoin {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    op,
+                })
             }
         }
     }
 
-    /// Get the output schema of a plan without fully executing it (e.g. for Scan, read metadata only).
-    fn get_schema(&self, plan: &LogicalPlan) -> Result<SchemaRef, String> {
+    /// Whether a plan's output, with columns `schema`, is sorted ascending on the column
+    /// `key` (its first sort column), resolving the sort column's name as `resolve` does
+    fn is_sorted_on(&self, plan: &LogicalPlan, key: &str, schema: &Schema) -> Result<bool, String> {
         match plan {
-            LogicalPlan::Scan { path, projection, .. } => {
-                let s = ParquetReader::from_path(path)
-                    .map_err(|e| e.to_string())?
-                    .schema()
-                    .map_err(|e| e.to_string())?;
-                let schema = if let Some(ref cols) = projection {
-                    let fields: Vec<Field> = cols
-                        .iter()
-                        .map(|n| {
-                            s.fields()
-                                .iter()
-                                .find(|f| f.name().as_ref() == n.as_str())
-                                .ok_or_else(|| format!("Column '{}' not found", n))
-                                .map(|f| f.as_ref().clone())
-                        })
-                        .collect::<Result<_, _>>()?;
-                    Arc::new(Schema::new(fields))
-                } else {
-                    Arc::new(s)
-                };
-                Ok(schema)
-            }
-            LogicalPlan::Project { input, columns } => {
-                let in_s = self.get_schema(input)?;
-                let fields: Vec<Field> = columns
-                    .iter()
-                    .map(|n| {
-                        in_s
-                            .fields()
-                            .iter()
-                            .find(|f| f.name().as_ref() == n.as_str())
-                            .ok_or_else(|| format!("Column '{}' not found", n))
-                            .map(|f| f.as_ref().clone())
-                    })
-                    .collect::<Result<_, _>>()?;
-                Ok(Arc::new(Schema::new(fields)))
-            }
-            LogicalPlan::Filter { input, .. } | LogicalPlan::Sort { input, .. } => self.get_schema(input),
-            LogicalPlan::Aggregate { .. } | LogicalPlan::Join { .. } => {
-                Err("get_schema not supported for Aggregate/Join".to_string())
+            LogicalPlan::Sort { order_by, .. } => match order_by.first() {
+                Some(e) if e.ascending => Ok(self.resolve(&e.column, schema)? == key),
+                _ => Ok(false),
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// The column of `schema` that `name` refers to (see `ExecutorConfig::case_insensitive`).
+    /// Unknown names are returned unchanged, for the operator to report.
+    pub(crate) fn resolve(&self, name: &str, schema: &Schema) -> Result<String, String> {
+        if !self.config.case_insensitive || schema.field_with_name(name).is_ok() {
+            return Ok(name.to_string());
+        }
+        let matches: Vec<&String> = schema
+            .fields()
+            .iter()
+            .map(|f| f.name())
+            .filter(|n| n.eq_ignore_ascii_case(name))
+            .collect();
+        match matches.as_slice() {
+            [] => Ok(name.to_string()),
+            [column] => Ok(column.to_string()),
+            _ => Err(format!(
+                "Column name '{}' is ambiguous: matches {:?} ignoring case",
+                name, matches
+            )),
+        }
+    }
+
+    fn resolve_optional(
+        &self,
+        name: Option<String>,
+        schema: &Schema,
+    ) -> Result<Option<String>, String> {
+        name.map(|name| self.resolve(&name, schema)).transpose()
+    }
+
+    fn resolve_columns(&self, names: &[String], schema: &Schema) -> Result<Vec<String>, String> {
+        names.iter().map(|name| self.resolve(name, schema)).collect()
+    }
+
+    /// `expr` as operators should run it: names resolved
+    fn resolve_expr(&self, expr: &LogicalExpr, schema: &Schema) -> Result<LogicalExpr, String> {
+        if let Some(i) = expr.first_param() {
+            return Err(unbound_param(i));
+        }
+        if !self.config.case_insensitive {
+            return Ok(expr.clone());
+        }
+        expr.try_map_columns(&mut |name| self.resolve(name, schema))
+    }
+}
+
+/// `plan` reading only `columns` from its unprojected scan, if it is one, possibly under
+/// filters. The columns those filters use are read as well, so a projection above can
+/// drop them once the rows are filtered (late projection).
+fn with_scan_projection(plan: &LogicalPlan, mut columns: Vec<String>) -> Option<LogicalPlan> {
+    match plan {
+        LogicalPlan::Scan {
+            path,
+            projection: None,
+            filters,
+            options,
+        } => Some(LogicalPlan::Scan {
+            path: path.clone(),
+            projection: Some(columns),
+            filters: filters.clone(),
+            options: options.clone(),
+        }),
+        LogicalPlan::Filter { input, predicate } => {
+            for name in predicate.column_names() {
+                if !columns.contains(&name) {
+                    columns.push(name);
+                }
             }
+            Some(LogicalPlan::Filter {
+                input: Box::new(with_scan_projection(input, columns)?),
+                predicate: predicate.clone(),
+            })
         }
+        _ => None,
     }
 }
 
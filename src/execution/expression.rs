@@ -0,0 +1,739 @@
+// Vectorized expression evaluation
+
+use crate::execution::batch::RecordBatch;
+use crate::planner::logical_plan::{unbound_param, BinaryOp, LogicalExpr, LogicalValue};
+use crate::types::{numeric_coercion, string_coercion, temporal_coercion, ScalarValue};
+use arrow::array::{
+    new_null_array, Array, ArrayRef, BooleanArray, Date32Array, Float64Array, Int32Array,
+    Int64Array, StringArray, TimestampMicrosecondArray,
+};
+use arrow::compute::kernels::concat_elements::concat_elements_dyn;
+use arrow::compute::kernels::numeric::{
+    add, add_wrapping, div, mul, mul_wrapping, rem, sub, sub_wrapping,
+};
+use arrow::datatypes::DataType;
+use arrow_ord::cmp::{eq, gt, gt_eq, lt, lt_eq, neq};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Evaluate an expression against a batch, producing one value per row.
+/// This is the only evaluator: the DataFrame and executor paths both run plans through it.
+/// Arithmetic and comparisons on mixed numeric types widen both sides first
+/// (Int32 < Int64 < Float64);
+/// integer +, -, * wrap on overflow (see `evaluate_with` to report it instead), and
+/// integer division by zero is an error. AND / OR use SQL three-valued logic, and skip
+/// their right side for a batch when the left side alone decides every row.
+/// A subexpression occurring more than once is evaluated once per batch.
+pub fn evaluate(expr: &LogicalExpr, batch: &RecordBatch) -> Result<ArrayRef, String> {
+    evaluate_with(expr, batch, false)
+}
+
+/// `evaluate`, reporting integer overflow in +, - and * as an error if `checked_arithmetic`
+pub fn evaluate_with(
+    expr: &LogicalExpr,
+    batch: &RecordBatch,
+    checked_arithmetic: bool,
+) -> Result<ArrayRef, String> {
+    let mut cache = SubexprCache::new(expr);
+    evaluate_cached(expr, batch, checked_arithmetic, &mut cache)
+}
+
+/// Results of the repeated subexpressions of one expression for one batch. Nodes are
+/// grouped by structure (a hash of each subtree built bottom-up, confirmed by comparing),
+/// and a node belongs to the cache only if its group has more than one member. Only
+/// compound expressions are cached: a column or literal is as cheap to fetch as a
+/// cached result.
+struct SubexprCache {
+    /// Group of each repeated node, keyed by the node's address in the expression
+    repeated: HashMap<*const LogicalExpr, usize>,
+    values: HashMap<usize, ArrayRef>,
+}
+
+impl SubexprCache {
+    fn new(expr: &LogicalExpr) -> Self {
+        let mut nodes = Vec::new();
+        structural_hash(expr, &mut nodes);
+
+        // Group equal subtrees; nodes with the same hash are compared to tell them apart
+        let mut groups: Vec<(&LogicalExpr, usize)> = Vec::new();
+        let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut members = Vec::with_capacity(nodes.len());
+        for (node, hash) in nodes {
+            let candidates = by_hash.entry(hash).or_default();
+            let group = match candidates
+                .iter()
+                .find(|&&g| same_structure(groups[g].0, node))
+            {
+                Some(&group) => group,
+                None => {
+                    candidates.push(groups.len());
+                    groups.push((node, 0));
+                    groups.len() - 1
+                }
+            };
+            groups[group].1 += 1;
+            members.push((node as *const LogicalExpr, group));
+        }
+        Self {
+            repeated: members
+                .into_iter()
+                .filter(|(_, g)| groups[*g].1 > 1)
+                .collect(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// Cache slot of `expr`, if it is one of the repeated subexpressions
+    fn key(&self, expr: &LogicalExpr) -> Option<usize> {
+        if self.repeated.is_empty() {
+            return None;
+        }
+        self.repeated.get(&(expr as *const LogicalExpr)).copied()
+    }
+}
+
+/// Hash of the structure of `expr`, pushing each compound node below it (with its own
+/// hash) to `nodes`. Children are hashed once, so this is linear in the tree size.
+fn structural_hash<'a>(expr: &'a LogicalExpr, nodes: &mut Vec<(&'a LogicalExpr, u64)>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    std::mem::discriminant(expr).hash(&mut hasher);
+    let compound = match expr {
+        LogicalExpr::Column(name) => {
+            name.hash(&mut hasher);
+            false
+        }
+        LogicalExpr::Literal(value) => {
+            value.to_string().hash(&mut hasher);
+            false
+        }
+        LogicalExpr::Param(i) => {
+            i.hash(&mut hasher);
+            false
+        }
+        LogicalExpr::Alias { expr, name } => {
+            structural_hash(expr, nodes).hash(&mut hasher);
+            name.hash(&mut hasher);
+            false
+        }
+        LogicalExpr::BinaryExpr { left, op, right } => {
+            structural_hash(left, nodes).hash(&mut hasher);
+            op.hash(&mut hasher);
+            structural_hash(right, nodes).hash(&mut hasher);
+            true
+        }
+        LogicalExpr::IsNull(inner) | LogicalExpr::IsNotNull(inner) => {
+            structural_hash(inner, nodes).hash(&mut hasher);
+            true
+        }
+        LogicalExpr::InList {
+            expr: inner,
+            list,
+            negated,
+        } => {
+            structural_hash(inner, nodes).hash(&mut hasher);
+            list.len().hash(&mut hasher);
+            negated.hash(&mut hasher);
+            true
+        }
+    };
+    let hash = hasher.finish();
+    if compound {
+        nodes.push((expr, hash));
+    }
+    hash
+}
+
+/// Whether two expressions have the same structure, and so the same value on any batch
+fn same_structure(a: &LogicalExpr, b: &LogicalExpr) -> bool {
+    match (a, b) {
+        (LogicalExpr::Column(a), LogicalExpr::Column(b)) => a == b,
+        (LogicalExpr::Literal(a), LogicalExpr::Literal(b)) => a == b,
+        (LogicalExpr::Param(a), LogicalExpr::Param(b)) => a == b,
+        (LogicalExpr::Alias { expr: a, name: x }, LogicalExpr::Alias { expr: b, name: y }) => {
+            x == y && same_structure(a, b)
+        }
+        (
+            LogicalExpr::BinaryExpr {
+                left: l1,
+                op: o1,
+                right: r1,
+            },
+            LogicalExpr::BinaryExpr {
+                left: l2,
+                op: o2,
+                right: r2,
+            },
+        ) => o1 == o2 && same_structure(l1, l2) && same_structure(r1, r2),
+        (LogicalExpr::IsNull(a), LogicalExpr::IsNull(b))
+        | (LogicalExpr::IsNotNull(a), LogicalExpr::IsNotNull(b)) => same_structure(a, b),
+        (
+            LogicalExpr::InList {
+                expr: a,
+                list: l1,
+                negated: n1,
+            },
+            LogicalExpr::InList {
+                expr: b,
+                list: l2,
+                negated: n2,
+            },
+        ) => n1 == n2 && l1 == l2 && same_structure(a, b),
+        _ => false,
+    }
+}
+
+fn evaluate_cached(
+    expr: &LogicalExpr,
+    batch: &RecordBatch,
+    checked: bool,
+    cache: &mut SubexprCache,
+) -> Result<ArrayRef, String> {
+    let key = cache.key(expr);
+    if let Some(array) = key.as_ref().and_then(|key| cache.values.get(key)) {
+        return Ok(array.clone());
+    }
+    let array = evaluate_node(expr, batch, checked, cache)?;
+    if let Some(key) = key {
+        cache.values.insert(key, array.clone());
+    }
+    Ok(array)
+}
+
+fn evaluate_node(
+    expr: &LogicalExpr,
+    batch: &RecordBatch,
+    checked: bool,
+    cache: &mut SubexprCache,
+) -> Result<ArrayRef, String> {
+    match expr {
+        LogicalExpr::Column(name) => batch
+            .column_by_name(name)
+            .cloned()
+            .ok_or_else(|| format!("Column '{}' not found", name)),
+        LogicalExpr::Literal(value) => Ok(literal_to_array(value, batch.num_rows())),
+        LogicalExpr::BinaryExpr { left, op, right } => {
+            let left_array = evaluate_cached(left, batch, checked, cache)?;
+            if decides_every_row(&left_array, *op) {
+                return Ok(left_array);
+            }
+            let right_array = evaluate_cached(right, batch, checked, cache)?;
+            if *op == BinaryOp::Concat {
+                evaluate_concat(&left_array, &right_array)
+            } else if op.is_arithmetic() {
+                evaluate_arithmetic(&left_array, *op, &right_array, checked)
+            } else if !comparable(left_array.data_type(), *op, right_array.data_type()) {
+                Err(format!(
+                    "Cannot compare {} with {} \u{2014} add a cast or matching literal",
+                    describe_operand(left, left_array.data_type()),
+                    describe_operand(right, right_array.data_type())
+                ))
+            } else {
+                Ok(Arc::new(evaluate_boolean_op(&left_array, *op, &right_array)?))
+            }
+        }
+        LogicalExpr::IsNull(inner) => {
+            let array = evaluate_cached(inner, batch, checked, cache)?;
+            let result = arrow::compute::is_null(&array)
+                .map_err(|e| format!("Failed to evaluate IS NULL: {}", e))?;
+            Ok(Arc::new(result))
+        }
+        LogicalExpr::IsNotNull(inner) => {
+            let array = evaluate_cached(inner, batch, checked, cache)?;
+            let result = arrow::compute::is_not_null(&array)
+                .map_err(|e| format!("Failed to evaluate IS NOT NULL: {}", e))?;
+            Ok(Arc::new(result))
+        }
+        LogicalExpr::Alias { expr, .. } => evaluate_cached(expr, batch, checked, cache),
+        LogicalExpr::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            let array = evaluate_cached(expr, batch, checked, cache)?;
+            let found = evaluate_in_list(&array, list)?;
+            if !negated {
+                return Ok(Arc::new(found));
+            }
+            let result = arrow::compute::not(&found)
+                .map_err(|e| format!("Failed to evaluate NOT IN: {}", e))?;
+            Ok(Arc::new(result))
+        }
+        LogicalExpr::Param(i) => Err(unbound_param(*i)),
+    }
+}
+
+/// Whether the left side of `op` fixes its result for every row, so the right side
+/// need not be evaluated: all false for AND, all true for OR (nulls decide nothing)
+fn decides_every_row(left: &ArrayRef, op: BinaryOp) -> bool {
+    let Some(left) = left.as_any().downcast_ref::<BooleanArray>() else {
+        return false;
+    };
+    match op {
+        BinaryOp::And => left.false_count() == left.len(),
+        BinaryOp::Or => left.true_count() == left.len(),
+        _ => false,
+    }
+}
+
+/// Whether `op` can compare values of these two types: equal types, or types with a
+/// common numeric, string or temporal type. AND / OR are checked by the kernel.
+fn comparable(left: &DataType, op: BinaryOp, right: &DataType) -> bool {
+    matches!(op, BinaryOp::And | BinaryOp::Or)
+        || left == right
+        || numeric_coercion(left, right).is_some()
+        || string_coercion(left, right).is_some()
+        || temporal_coercion(left, right).is_some()
+}
+
+/// An operand of a comparison for error messages, e.g. `column 'age' (Int64)`
+fn describe_operand(expr: &LogicalExpr, data_type: &DataType) -> String {
+    match expr {
+        LogicalExpr::Column(name) => format!("column '{}' ({:?})", name, data_type),
+        LogicalExpr::Literal(_) => format!("literal of type {:?}", data_type),
+        other => format!("'{}' ({:?})", other, data_type),
+    }
+}
+
+/// Three-valued `array IN (list)`: true where a list value equals the row; otherwise null
+/// if the row or any list value is null, else false (null rows get null from `=`). Negating this (NOT IN) keeps the
+/// nulls, so a null in the list means no row passes a NOT IN filter.
+fn evaluate_in_list(array: &ArrayRef, list: &[ScalarValue]) -> Result<BooleanArray, String> {
+    let len = array.len();
+    // Non-matches start as null when the list holds a null, since `x = NULL` is unknown
+    let mut found = if list.iter().any(|v| v.is_null()) {
+        BooleanArray::from(vec![None; len])
+    } else {
+        BooleanArray::from(vec![false; len])
+    };
+    for value in list.iter().filter(|v| !v.is_null()) {
+        let matches = evaluate_boolean_op(array, BinaryOp::Eq, &value.to_array(len))?;
+        found = arrow::compute::or_kleene(&found, &matches)
+            .map_err(|e| format!("Failed to evaluate IN: {}", e))?;
+    }
+    Ok(found)
+}
+
+/// Evaluate a predicate to a boolean mask
+pub fn evaluate_predicate(expr: &LogicalExpr, batch: &RecordBatch) -> Result<BooleanArray, String> {
+    evaluate_predicate_with(expr, batch, false)
+}
+
+/// `evaluate_predicate` with overflow checking as in `evaluate_with`
+pub fn evaluate_predicate_with(
+    expr: &LogicalExpr,
+    batch: &RecordBatch,
+    checked_arithmetic: bool,
+) -> Result<BooleanArray, String> {
+    let array = evaluate_with(expr, batch, checked_arithmetic)?;
+    array
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Predicate '{}' evaluates to {:?}, not Boolean",
+                expr,
+                array.data_type()
+            )
+        })
+}
+
+/// Materialize a literal as an array of `len` copies
+pub(crate) fn literal_to_array(value: &LogicalValue, len: usize) -> ArrayRef {
+    match value {
+        LogicalValue::Int32(v) => Arc::new(Int32Array::from(vec![*v; len])),
+        LogicalValue::Int64(v) => Arc::new(Int64Array::from(vec![*v; len])),
+        LogicalValue::Float64(v) => Arc::new(Float64Array::from(vec![*v; len])),
+        LogicalValue::String(v) => Arc::new(StringArray::from(vec![v.as_str(); len])),
+        LogicalValue::Boolean(v) => Arc::new(BooleanArray::from(vec![*v; len])),
+        LogicalValue::Date32(v) => Arc::new(Date32Array::from(vec![*v; len])),
+        LogicalValue::TimestampMicros(v) => {
+            Arc::new(TimestampMicrosecondArray::from(vec![*v; len]))
+        }
+        LogicalValue::Null(data_type) => new_null_array(data_type, len),
+    }
+}
+
+/// Comparison and logical operators (cmp kernels work with &dyn Datum)
+fn evaluate_boolean_op(
+    left: &ArrayRef,
+    op: BinaryOp,
+    right: &ArrayRef,
+) -> Result<BooleanArray, String> {
+    // The cmp kernels need both sides to have one type, so widen mixed numeric operands,
+    // compare Utf8 against LargeUtf8 as LargeUtf8 and dates and timestamps as instants
+    let common = numeric_coercion(left.data_type(), right.data_type())
+        .or_else(|| string_coercion(left.data_type(), right.data_type()))
+        .or_else(|| temporal_coercion(left.data_type(), right.data_type()));
+    let (left, right) = match common {
+        Some(target) if left.data_type() != right.data_type() => {
+            (&cast_to(left, &target)?, &cast_to(right, &target)?)
+        }
+        _ => (left, right),
+    };
+    match op {
+        BinaryOp::Eq => eq(left, right).map_err(|e| format!("Failed to evaluate equality: {}", e)),
+        BinaryOp::Neq => {
+            neq(left, right).map_err(|e| format!("Failed to evaluate inequality: {}", e))
+        }
+        BinaryOp::Lt => lt(left, right).map_err(|e| format!("Failed to evaluate less than: {}", e)),
+        BinaryOp::Le => lt_eq(left, right)
+            .map_err(|e| format!("Failed to evaluate less than or equal: {}", e)),
+        BinaryOp::Gt => {
+            gt(left, right).map_err(|e| format!("Failed to evaluate greater than: {}", e))
+        }
+        BinaryOp::Ge => gt_eq(left, right)
+            .map_err(|e| format!("Failed to evaluate greater than or equal: {}", e)),
+        // Three-valued: false AND NULL is false, true OR NULL is true
+        BinaryOp::And => {
+            arrow::compute::and_kleene(as_boolean_array(left)?, as_boolean_array(right)?)
+                .map_err(|e| format!("Failed to evaluate AND: {}", e))
+        }
+        BinaryOp::Or => {
+            arrow::compute::or_kleene(as_boolean_array(left)?, as_boolean_array(right)?)
+                .map_err(|e| format!("Failed to evaluate OR: {}", e))
+        }
+        _ => Err(format!("Operator '{}' does not produce a boolean", op)),
+    }
+}
+
+/// Arithmetic operators, after widening both sides to a common numeric type;
+/// integer overflow in +, - and * is an error if `checked`, else the result wraps
+fn evaluate_arithmetic(
+    left: &ArrayRef,
+    op: BinaryOp,
+    right: &ArrayRef,
+    checked: bool,
+) -> Result<ArrayRef, String> {
+    let target = numeric_coercion(left.data_type(), right.data_type()).ok_or_else(|| {
+        format!(
+            "Cannot apply '{}' to {:?} and {:?}",
+            op,
+            left.data_type(),
+            right.data_type()
+        )
+    })?;
+    let left = cast_to(left, &target)?;
+    let right = cast_to(right, &target)?;
+    let result = match op {
+        BinaryOp::Plus if checked => add(&left, &right),
+        BinaryOp::Minus if checked => sub(&left, &right),
+        BinaryOp::Multiply if checked => mul(&left, &right),
+        BinaryOp::Plus => add_wrapping(&left, &right),
+        BinaryOp::Minus => sub_wrapping(&left, &right),
+        BinaryOp::Multiply => mul_wrapping(&left, &right),
+        BinaryOp::Divide => div(&left, &right),
+        BinaryOp::Modulo => rem(&left, &right),
+        _ => return Err(format!("Operator '{}' is not arithmetic", op)),
+    };
+    result.map_err(|e| format!("Failed to evaluate '{}': {}", op, e))
+}
+
+/// String concatenation; a null on either side gives a null row
+fn evaluate_concat(left: &ArrayRef, right: &ArrayRef) -> Result<ArrayRef, String> {
+    let target = string_coercion(left.data_type(), right.data_type()).ok_or_else(|| {
+        format!(
+            "Cannot apply '||' to {:?} and {:?}",
+            left.data_type(),
+            right.data_type()
+        )
+    })?;
+    let left = cast_to(left, &target)?;
+    let right = cast_to(right, &target)?;
+    concat_elements_dyn(&left, &right).map_err(|e| format!("Failed to evaluate '||': {}", e))
+}
+
+fn cast_to(array: &ArrayRef, data_type: &DataType) -> Result<ArrayRef, String> {
+    if array.data_type() == data_type {
+        return Ok(array.clone());
+    }
+    arrow::compute::cast(array, data_type)
+        .map_err(|e| format!("Failed to cast {:?} to {:?}: {}", array.data_type(), data_type, e))
+}
+
+/// Convert an array to a boolean array reference
+fn as_boolean_array(array: &ArrayRef) -> Result<&BooleanArray, String> {
+    array
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .ok_or_else(|| "Array is not a boolean array".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Schema};
+
+    fn batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int64, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(i32::MAX)])),
+                Arc::new(Int64Array::from(vec![10, 20, 30])),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn binary(left: LogicalExpr, op: BinaryOp, right: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    fn col(name: &str) -> LogicalExpr {
+        LogicalExpr::Column(name.to_string())
+    }
+
+    #[test]
+    fn test_arithmetic_widens_mixed_types() {
+        let batch = batch();
+        let sum = binary(col("a"), BinaryOp::Plus, col("b"));
+        assert_eq!(sum.data_type(batch.schema()).unwrap(), DataType::Int64);
+
+        let result = evaluate(&sum, &batch).unwrap();
+        let result = result.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(result.value(0), 11);
+        assert!(result.is_null(1));
+        // Widened before adding, so no Int32 overflow
+        assert_eq!(result.value(2), i32::MAX as i64 + 30);
+
+        let half = binary(
+            col("b"),
+            BinaryOp::Divide,
+            LogicalExpr::Literal(LogicalValue::Float64(4.0)),
+        );
+        let result = evaluate(&half, &batch).unwrap();
+        let result = result.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(result.values().to_vec(), vec![2.5, 5.0, 7.5]);
+    }
+
+    #[test]
+    fn test_repeated_subexpression_shares_one_cache_slot() {
+        let batch = batch();
+        let literal = |v: i64| LogicalExpr::Literal(LogicalValue::Int64(v));
+        // (b * b) > 100 AND (b * b) < 900
+        let square = binary(col("b"), BinaryOp::Multiply, col("b"));
+        let above = binary(square.clone(), BinaryOp::Gt, literal(100));
+        let predicate = binary(above, BinaryOp::And, binary(square, BinaryOp::Lt, literal(900)));
+
+        // Both occurrences of b * b, and nothing else, share one cache slot
+        let mut cache = SubexprCache::new(&predicate);
+        assert_eq!(cache.repeated.len(), 2);
+        let slot = *cache.repeated.values().next().unwrap();
+        assert!(cache.repeated.values().all(|&s| s == slot));
+        let mask = evaluate_cached(&predicate, &batch, false, &mut cache).unwrap();
+        assert_eq!(mask.as_ref(), &BooleanArray::from(vec![false, true, false]));
+        assert_eq!(cache.values.len(), 1);
+        let cached = cache.values[&slot].as_ref();
+        assert_eq!(cached, &Int64Array::from(vec![100, 400, 900]));
+
+        // Both read the slot once it is filled: a stand-in value decides the result
+        let mut cache = SubexprCache::new(&predicate);
+        let stand_in = Arc::new(Int64Array::from(vec![500; 3]));
+        cache.values.insert(slot, stand_in);
+        let mask = evaluate_cached(&predicate, &batch, false, &mut cache).unwrap();
+        assert_eq!(mask.as_ref(), &BooleanArray::from(vec![true; 3]));
+
+        // Literals of different types print alike but are different subexpressions
+        let plus = |v: LogicalValue| binary(col("b"), BinaryOp::Plus, LogicalExpr::Literal(v));
+        let both = binary(
+            binary(plus(LogicalValue::Int32(1)), BinaryOp::Gt, literal(0)),
+            BinaryOp::And,
+            binary(plus(LogicalValue::Int64(1)), BinaryOp::Gt, literal(0)),
+        );
+        assert!(SubexprCache::new(&both).repeated.is_empty());
+    }
+
+    #[test]
+    fn test_checked_arithmetic_overflow() {
+        let batch = batch();
+        let one = LogicalExpr::Literal(LogicalValue::Int32(1));
+        let wrapping = binary(col("a"), BinaryOp::Plus, one);
+        let result = evaluate(&wrapping, &batch).unwrap();
+        let result = result.as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(result.value(2), i32::MIN);
+
+        let err = evaluate_with(&wrapping, &batch, true).unwrap_err();
+        assert!(err.contains("Overflow"), "{}", err);
+        // Without an overflow checking gives the same values
+        let small = binary(col("b"), BinaryOp::Multiply, col("b"));
+        let result = evaluate_with(&small, &batch, true).unwrap();
+        let result = result.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(result.values().to_vec(), vec![100, 400, 900]);
+    }
+
+    #[test]
+    fn test_concat_strings_with_separator() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("first", DataType::Utf8, true),
+            Field::new("last", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("Ada"), None])),
+                Arc::new(StringArray::from(vec!["Lovelace", "Hopper"])),
+            ],
+        )
+        .unwrap();
+        let space = LogicalExpr::Literal(LogicalValue::String(" ".to_string()));
+        let first_space = binary(col("first"), BinaryOp::Concat, space);
+        let full = binary(first_space, BinaryOp::Concat, col("last"));
+        assert_eq!(full.to_string(), "first || ' ' || last");
+        assert_eq!(full.data_type(batch.schema()).unwrap(), DataType::Utf8);
+
+        let result = evaluate(&full, &batch).unwrap();
+        let result = result.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(result.value(0), "Ada Lovelace");
+        assert!(result.is_null(1));
+
+        let one = LogicalExpr::Literal(LogicalValue::Int32(1));
+        let bad = binary(col("first"), BinaryOp::Concat, one);
+        assert!(bad.data_type(batch.schema()).is_err());
+        assert!(evaluate(&bad, &batch).is_err());
+    }
+
+    #[test]
+    fn test_predicate_must_be_boolean() {
+        let batch = batch();
+        let mask = evaluate_predicate(
+            &binary(col("b"), BinaryOp::Ge, LogicalExpr::Literal(LogicalValue::Int64(20))),
+            &batch,
+        )
+        .unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![false, true, true]));
+
+        assert!(evaluate_predicate(&col("a"), &batch).is_err());
+        let not_numeric = binary(
+            col("a"),
+            BinaryOp::Plus,
+            LogicalExpr::Literal(LogicalValue::String("x".to_string())),
+        );
+        assert!(evaluate(&not_numeric, &batch).is_err());
+    }
+
+    #[test]
+    fn test_and_or_skip_right_side_when_decided() {
+        let batch = batch();
+        let b_ge = |v: i64| {
+            binary(col("b"), BinaryOp::Ge, LogicalExpr::Literal(LogicalValue::Int64(v)))
+        };
+        // Evaluating the right side would fail on the missing column
+        let missing = binary(col("missing"), BinaryOp::Eq, col("b"));
+        let and = binary(b_ge(100), BinaryOp::And, missing.clone());
+        assert_eq!(
+            evaluate_predicate(&and, &batch).unwrap(),
+            BooleanArray::from(vec![false; 3])
+        );
+        let or = binary(b_ge(0), BinaryOp::Or, missing.clone());
+        assert_eq!(evaluate_predicate(&or, &batch).unwrap(), BooleanArray::from(vec![true; 3]));
+        // Not decided by the left side: the right side runs
+        assert!(evaluate(&binary(b_ge(20), BinaryOp::And, missing.clone()), &batch).is_err());
+        assert!(evaluate(&binary(b_ge(20), BinaryOp::Or, missing), &batch).is_err());
+
+        // Nulls on the left decide nothing; with the right side they follow SQL logic
+        let a_gt_0 = binary(col("a"), BinaryOp::Gt, LogicalExpr::Literal(LogicalValue::Int32(0)));
+        let mask = evaluate_predicate(&binary(a_gt_0.clone(), BinaryOp::And, b_ge(100)), &batch);
+        assert_eq!(mask.unwrap(), BooleanArray::from(vec![false; 3]));
+        let mask = evaluate_predicate(&binary(a_gt_0, BinaryOp::Or, b_ge(20)), &batch);
+        assert_eq!(mask.unwrap(), BooleanArray::from(vec![true; 3]));
+    }
+
+    fn in_list(expr: LogicalExpr, list: Vec<ScalarValue>, negated: bool) -> LogicalExpr {
+        LogicalExpr::InList {
+            expr: Box::new(expr),
+            list,
+            negated,
+        }
+    }
+
+    #[test]
+    fn test_in_and_not_in() {
+        let batch = batch();
+        // a = [1, NULL, i32::MAX]; Int64 list values are widened against the Int32 column
+        let list = vec![ScalarValue::Int64(1), ScalarValue::Int64(5)];
+        let mask = evaluate_predicate(&in_list(col("a"), list.clone(), false), &batch).unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![Some(true), None, Some(false)]));
+        let mask = evaluate_predicate(&in_list(col("a"), list, true), &batch).unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![Some(false), None, Some(true)]));
+
+        // With a null in the list nothing is ever NOT IN: non-matches become null
+        let list = vec![ScalarValue::Int32(1), ScalarValue::Null];
+        let mask = evaluate_predicate(&in_list(col("a"), list.clone(), false), &batch).unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![Some(true), None, None]));
+        let mask = evaluate_predicate(&in_list(col("a"), list, true), &batch).unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![Some(false), None, None]));
+        assert_eq!(mask.true_count(), 0);
+    }
+
+    #[test]
+    fn test_comparison_widens_mixed_types() {
+        let batch = batch();
+        // Int32 column against Int64 column, and Int64 column against Int32 literal
+        let mask = evaluate_predicate(&binary(col("a"), BinaryOp::Lt, col("b")), &batch).unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![Some(true), None, Some(false)]));
+        let mask = evaluate_predicate(
+            &binary(col("b"), BinaryOp::Eq, LogicalExpr::Literal(LogicalValue::Int32(20))),
+            &batch,
+        )
+        .unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![false, true, false]));
+    }
+
+    #[test]
+    fn test_temporal_comparisons() {
+        use arrow::datatypes::TimeUnit;
+        let day = 86_400_000_000i64;
+        let ts_type = DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("day", DataType::Date32, false),
+            Field::new("ts", ts_type, false),
+        ]));
+        let timestamps = TimestampMicrosecondArray::from(vec![0, day, 2 * day + 1])
+            .with_timezone("UTC");
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Date32Array::from(vec![0, 1, 2])), Arc::new(timestamps)],
+        )
+        .unwrap();
+
+        let date = |v| LogicalExpr::Literal(LogicalValue::Date32(v));
+        let micros = |v| LogicalExpr::Literal(LogicalValue::TimestampMicros(v));
+        let mask = evaluate_predicate(&binary(col("day"), BinaryOp::Ge, date(1)), &batch);
+        assert_eq!(mask.unwrap(), BooleanArray::from(vec![false, true, true]));
+        // A timezone-less literal compares against a UTC column as a UTC instant
+        let mask = evaluate_predicate(&binary(col("ts"), BinaryOp::Gt, micros(day)), &batch);
+        assert_eq!(mask.unwrap(), BooleanArray::from(vec![false, false, true]));
+        // Dates compare with timestamps as midnight UTC
+        let mask = evaluate_predicate(&binary(col("ts"), BinaryOp::Eq, col("day")), &batch);
+        assert_eq!(mask.unwrap(), BooleanArray::from(vec![true, true, false]));
+
+        assert_eq!(date(19_783).to_string(), "DATE '2024-03-01'");
+        assert_eq!(micros(day + 1_500_000).to_string(), "TIMESTAMP '1970-01-02 00:00:01.500'");
+    }
+
+    #[test]
+    fn test_incomparable_types_name_operands() {
+        let batch = batch();
+        let text = LogicalExpr::Literal(LogicalValue::String("x".to_string()));
+        let err = evaluate(&binary(col("b"), BinaryOp::Eq, text), &batch).unwrap_err();
+        assert_eq!(
+            err,
+            "Cannot compare column 'b' (Int64) with literal of type Utf8 \u{2014} add a cast or \
+             matching literal"
+        );
+        let flag = LogicalExpr::Literal(LogicalValue::Boolean(true));
+        let sum = binary(col("a"), BinaryOp::Plus, col("b"));
+        let err = evaluate(&binary(sum, BinaryOp::Lt, flag), &batch).unwrap_err();
+        assert!(err.starts_with("Cannot compare 'a + b' (Int64) with literal of type Boolean"));
+    }
+}
@@ -1,5 +1,12 @@
 pub mod batch;
 pub mod executor;
+pub mod expression;
 pub mod operators;
+pub mod physical_plan;
+pub mod testing;
 
-pub use executor::Executor;
+pub use batch::concat_batches;
+pub use executor::{Executor, ExecutorConfig};
+pub use operators::filter::apply_predicate;
+pub use physical_plan::PhysicalPlan;
+pub use testing::{assert_batches_eq, assert_batches_eq_unordered, compare_batches};
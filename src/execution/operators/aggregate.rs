@@ -1,9 +1,10 @@
 // GROUP BY aggregations
 
-use crate::execution::batch::{RecordBatch, SchemaRef};
-use crate::execution::operators::Operator;
-use crate::planner::logical_plan::{AggregateFunction, Aggregation};
-use arrow::array::ArrayRef;
+use crate::execution::batch::{timestamp_at, RecordBatch, SchemaRef};
+use crate::execution::expression::{evaluate_predicate_with, evaluate_with};
+use crate::execution::operators::{Operator, SortOperator};
+use crate::planner::logical_plan::{AggregateFunction, Aggregation, LogicalExpr, OrderByExpr};
+use arrow::array::{Array, ArrayRef};
 use arrow::datatypes::{DataType, Field, Schema};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -16,6 +17,10 @@ enum GroupValue {
     F64(f64),
     Str(String),
     Bool(bool),
+    /// Days since 1970-01-01
+    Date32(i32),
+    /// Timestamp in the group column's own unit
+    Timestamp(i64),
     Null,
 }
 
@@ -27,6 +32,8 @@ impl GroupValue {
             GroupValue::F64(v) => format!("f64:{}", v),
             GroupValue::Str(v) => format!("str:{}", v),
             GroupValue::Bool(v) => format!("bool:{}", v),
+            GroupValue::Date32(v) => format!("date:{}", v),
+            GroupValue::Timestamp(v) => format!("ts:{}", v),
             GroupValue::Null => "null".to_string(),
         }
     }
@@ -36,46 +43,83 @@ impl GroupValue {
 #[derive(Clone, Debug)]
 enum AggState {
     Count(u64),
-    Sum(f64),
+    /// None until a non-null value is seen, so a sum over no values is null
+    Sum(Option<f64>),
     Avg { sum: f64, count: u64 },
-    Min(f64),
-    Max(f64),
+    /// Smallest value seen; `has_value` tells "no rows" apart from a real +inf
+    Min { value: f64, has_value: bool },
+    Max { value: f64, has_value: bool },
+    /// Every non-null value seen, for order statistics (median, percentiles)
+    Collect(Vec<f64>),
 }
 
 /// Aggregate operator implementing GROUP BY with COUNT, SUM, AVG, MIN, MAX
-/// Uses vectorized hash aggregation: builds a hash map of group key -> aggregate states
+/// Uses vectorized hash aggregation: builds a hash map of group key -> aggregate states.
+/// Group keys are expressions, evaluated to one array per batch before hashing.
+/// Groups are output in order of first appearance in the input, so the result (and a
+/// limit over it) is deterministic for a given input order, or sorted by group columns
+/// with `with_order_by`.
+/// Float values follow IEEE rules in SUM and AVG (a NaN makes the result NaN), while
+/// MIN, MAX and percentiles use a total order in which NaN is greater than +inf.
+/// Without GROUP BY the result is always one row: over no input rows, counts are 0 and
+/// the other aggregates null.
 pub struct AggregateOperator {
-    group_by: Vec<String>,
+    group_by: Vec<LogicalExpr>,
     aggs: Vec<Aggregation>,
     schema: SchemaRef,
+    /// Sort applied to the groups before they are emitted
+    sort: Option<SortOperator>,
+    /// Expected number of groups, used to pre-size the group table
+    estimated_groups: Option<usize>,
+    checked_arithmetic: bool,
 }
 
 impl AggregateOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Aggregate";
+
     /// Create a new Aggregate operator
     pub fn new(
-        group_by: Vec<String>,
+        group_by: Vec<LogicalExpr>,
         aggs: Vec<Aggregation>,
         input_schema: SchemaRef,
     ) -> Result<Self, String> {
-        // Build output schema: group_by columns + agg result columns
+        // Build output schema: group_by expressions + agg result columns
         let mut fields: Vec<Field> = Vec::new();
 
-        for name in &group_by {
-            let field = input_schema
-                .fields()
-                .iter()
-                .find(|f| f.name() == name)
-                .ok_or_else(|| format!("Group column '{}' not found", name))?
-                .as_ref()
-                .clone();
-            fields.push(field);
+        for expr in &group_by {
+            for name in expr.column_names() {
+                input_schema
+                    .field_with_name(&name)
+                    .map_err(|_| format!("Group column '{}' not found", name))?;
+            }
+            fields.push(expr.to_field(&input_schema)?);
         }
 
         for agg in &aggs {
+            agg.validate()?;
+            if let Some(ref column) = agg.column {
+                input_schema
+                    .field_with_name(column)
+                    .map_err(|_| format!("Aggregate column '{}' not found", column))?;
+            }
+            if let Some(ref filter) = agg.filter {
+                let data_type = filter.data_type(&input_schema)?;
+                if data_type != DataType::Boolean {
+                    return Err(format!(
+                        "Filter of aggregation '{}' is {:?}, not Boolean",
+                        agg.alias, data_type
+                    ));
+                }
+            }
             let data_type = match agg.function {
                 AggregateFunction::Count => DataType::Int64,
-                AggregateFunction::Sum | AggregateFunction::Avg | AggregateFunction::Min
-                | AggregateFunction::Max => DataType::Float64,
+                AggregateFunction::Sum
+                | AggregateFunction::Avg
+                | AggregateFunction::Min
+                | AggregateFunction::Max
+                | AggregateFunction::Median
+                | AggregateFunction::Percentile(_) => DataType::Float64,
             };
             fields.push(Field::new(agg.alias.as_str(), data_type, true));
         }
@@ -86,32 +130,49 @@ impl AggregateOperator {
             group_by,
             aggs,
             schema,
+            sort: None,
+            estimated_groups: None,
+            checked_arithmetic: false,
         })
     }
 
-    /// Extract group key from a row as string (for hashing)
-    fn get_group_key(&self, batch: &RecordBatch, row: usize) -> Result<String, String> {
-        let mut parts = Vec::with_capacity(self.group_by.len());
-        for name in &self.group_by {
-            let col = batch
-                .column_by_name(name)
-                .ok_or_else(|| format!("Column '{}' not found", name))?;
-            let gv = extract_group_value(col, row)?;
-            parts.push(gv.to_key_string());
+    /// Emit the groups sorted by `order_by`, which may only name group columns. The
+    /// result is the same as a Sort over this aggregate, without a separate operator.
+    pub fn with_order_by(mut self, order_by: Vec<OrderByExpr>) -> Result<Self, String> {
+        if order_by.is_empty() {
+            self.sort = None;
+            return Ok(self);
+        }
+        let group_fields = &self.schema.fields()[..self.group_by.len()];
+        if let Some(e) = order_by
+            .iter()
+            .find(|e| !group_fields.iter().any(|f| f.name() == &e.column))
+        {
+            return Err(format!("Aggregate can only sort by group columns, not '{}'", e.column));
         }
-        Ok(parts.join("|"))
+        self.sort = Some(SortOperator::new(order_by, self.schema.clone())?);
+        Ok(self)
     }
 
-    /// Extract group values from a row (for output)
-    fn get_group_values(&self, batch: &RecordBatch, row: usize) -> Result<Vec<GroupValue>, String> {
+    /// Pre-size the group table for about `groups` groups (for example from column
+    /// statistics), avoiding rehashing as groups are found. The hint never changes the
+    /// result, and is capped at the number of input rows.
+    pub fn with_estimated_groups(mut self, groups: Option<usize>) -> Self {
+        self.estimated_groups = groups;
+        self
+    }
+
+    /// Report integer overflow in group keys and filters as an error instead of wrapping
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Self {
+        self.checked_arithmetic = checked;
+        self
+    }
+
+    /// Evaluate each group expression against a batch
+    fn group_arrays(&self, batch: &RecordBatch) -> Result<Vec<ArrayRef>, String> {
         self.group_by
             .iter()
-            .map(|name| {
-                let col = batch
-                    .column_by_name(name)
-                    .ok_or_else(|| format!("Column '{}' not found", name))?;
-                extract_group_value(col, row)
-            })
+            .map(|expr| evaluate_with(expr, batch, self.checked_arithmetic))
             .collect()
     }
 
@@ -127,26 +188,47 @@ impl AggregateOperator {
 
     /// Process all batches and produce one aggregated batch
     fn hash_aggregate(&self, inputs: &[RecordBatch]) -> Result<RecordBatch, String> {
-        // Map: group_key_string -> (group_values, agg_states)
-        // We keep group_values from first occurrence for output
-        let mut map: HashMap<String, (Vec<GroupValue>, Vec<AggState>)> = HashMap::new();
+        // Groups in first-seen order: (group_values, agg_states), with group_values
+        // from the first occurrence; `index` maps group_key_string -> position
+        let rows: usize = inputs.iter().map(|b| b.num_rows()).sum();
+        let capacity = self.estimated_groups.map_or(0, |n| n.min(rows));
+        let mut groups: Vec<(Vec<GroupValue>, Vec<AggState>)> = Vec::with_capacity(capacity);
+        let mut index: HashMap<String, usize> = HashMap::with_capacity(capacity);
 
         for batch in inputs {
             if batch.num_rows() == 0 {
                 continue;
             }
 
+            let group_arrays = self.group_arrays(batch)?;
+            // Per aggregation, the rows its FILTER keeps
+            let masks = self
+                .aggs
+                .iter()
+                .map(|agg| {
+                    let filter = agg.filter.as_ref();
+                    filter
+                        .map(|f| evaluate_predicate_with(f, batch, self.checked_arithmetic))
+                        .transpose()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
             for row in 0..batch.num_rows() {
-                let key = self.get_group_key(batch, row)?;
-                let group_vals = self.get_group_values(batch, row)?;
+                let group_vals = group_values(&group_arrays, row)?;
+                let key = group_key(&group_vals);
 
-                let entry = map
-                    .entry(key)
-                    .or_insert_with(|| (group_vals.clone(), self.initial_states()));
+                let position = *index.entry(key).or_insert_with(|| {
+                    groups.push((group_vals, self.initial_states()));
+                    groups.len() - 1
+                });
 
-                let states = &mut entry.1;
+                let states = &mut groups[position].1;
 
                 for (i, agg) in self.aggs.iter().enumerate() {
+                    if let Some(mask) = &masks[i] {
+                        if mask.is_null(row) || !mask.value(row) {
+                            continue;
+                        }
+                    }
                     match agg.function {
                         AggregateFunction::Count => {
                             let v = if agg.column.is_none() {
@@ -164,7 +246,7 @@ impl AggregateOperator {
                         AggregateFunction::Sum => {
                             if let Some(v) = self.get_agg_value(batch, agg, row) {
                                 if let AggState::Sum(ref mut s) = states[i] {
-                                    *s += v;
+                                    *s = Some(s.unwrap_or(0.0) + v);
                                 }
                             }
                         }
@@ -178,28 +260,46 @@ impl AggregateOperator {
                         }
                         AggregateFunction::Min => {
                             if let Some(v) = self.get_agg_value(batch, agg, row) {
-                                if let AggState::Min(ref mut m) = states[i] {
-                                    if *m > v {
-                                        *m = v;
+                                if let AggState::Min { value, has_value } = &mut states[i] {
+                                    if !*has_value || v.total_cmp(value).is_lt() {
+                                        *value = v;
+                                        *has_value = true;
                                     }
                                 }
                             }
                         }
                         AggregateFunction::Max => {
                             if let Some(v) = self.get_agg_value(batch, agg, row) {
-                                if let AggState::Max(ref mut m) = states[i] {
-                                    if *m < v {
-                                        *m = v;
+                                if let AggState::Max { value, has_value } = &mut states[i] {
+                                    if !*has_value || v.total_cmp(value).is_gt() {
+                                        *value = v;
+                                        *has_value = true;
                                     }
                                 }
                             }
                         }
+                        AggregateFunction::Median | AggregateFunction::Percentile(_) => {
+                            if let Some(v) = self.get_agg_value(batch, agg, row) {
+                                if let AggState::Collect(ref mut values) = states[i] {
+                                    values.push(v);
+                                }
+                            }
+                        }
                     }
                 }
             }
         }
 
-        self.build_output_batch(map)
+        if groups.is_empty() && self.group_by.is_empty() {
+            // Without GROUP BY there is always one row, even over no input rows
+            groups.push((vec![], self.initial_states()));
+        }
+
+        let batch = self.build_output_batch(groups)?;
+        match &self.sort {
+            Some(sort) => sort.execute(&batch),
+            None => Ok(batch),
+        }
     }
 
     fn initial_states(&self) -> Vec<AggState> {
@@ -207,19 +307,28 @@ impl AggregateOperator {
             .iter()
             .map(|a| match a.function {
                 AggregateFunction::Count => AggState::Count(0),
-                AggregateFunction::Sum => AggState::Sum(0.0),
+                AggregateFunction::Sum => AggState::Sum(None),
                 AggregateFunction::Avg => AggState::Avg { sum: 0.0, count: 0 },
-                AggregateFunction::Min => AggState::Min(f64::INFINITY),
-                AggregateFunction::Max => AggState::Max(f64::NEG_INFINITY),
+                AggregateFunction::Min => AggState::Min {
+                    value: f64::INFINITY,
+                    has_value: false,
+                },
+                AggregateFunction::Max => AggState::Max {
+                    value: f64::NEG_INFINITY,
+                    has_value: false,
+                },
+                AggregateFunction::Median | AggregateFunction::Percentile(_) => {
+                    AggState::Collect(Vec::new())
+                }
             })
             .collect()
     }
 
     fn build_output_batch(
         &self,
-        map: HashMap<String, (Vec<GroupValue>, Vec<AggState>)>,
+        groups: Vec<(Vec<GroupValue>, Vec<AggState>)>,
     ) -> Result<RecordBatch, String> {
-        let n = map.len();
+        let n = groups.len();
         if n == 0 {
             let empty_cols: Vec<ArrayRef> = self
                 .schema
@@ -240,7 +349,7 @@ impl AggregateOperator {
         for g in 0..num_group {
             let dt = self.schema.fields()[g].data_type().clone();
             let arr = collect_group_column(
-                map.values().map(|(vals, _)| &vals[g]),
+                groups.iter().map(|(vals, _)| &vals[g]),
                 &dt,
             )?;
             columns.push(arr);
@@ -250,7 +359,7 @@ impl AggregateOperator {
         for a in 0..num_aggs {
             let arr = collect_agg_column(
                 &self.aggs[a],
-                map.values().map(|(_, sts)| &sts[a]),
+                groups.iter().map(|(_, sts)| &sts[a]),
             )?;
             columns.push(arr);
         }
@@ -259,6 +368,20 @@ impl AggregateOperator {
     }
 }
 
+/// Extract group values from a row of the evaluated group arrays (for output)
+fn group_values(arrays: &[ArrayRef], row: usize) -> Result<Vec<GroupValue>, String> {
+    arrays.iter().map(|col| extract_group_value(col, row)).collect()
+}
+
+/// Group key of a row as a string (for hashing)
+fn group_key(values: &[GroupValue]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_key_string())
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
 fn extract_group_value(col: &ArrayRef, row: usize) -> Result<GroupValue, String> {
     use arrow::array::*;
     if col.is_null(row) {
@@ -277,19 +400,30 @@ fn extract_group_value(col: &ArrayRef, row: usize) -> Result<GroupValue, String>
             let arr = col.as_any().downcast_ref::<Float64Array>().ok_or("Float64")?;
             Ok(GroupValue::F64(arr.value(row)))
         }
-        DataType::Utf8 | DataType::LargeUtf8 => {
+        DataType::Utf8 => {
             let arr = col.as_any().downcast_ref::<StringArray>().ok_or("Utf8")?;
             Ok(GroupValue::Str(arr.value(row).to_string()))
         }
+        DataType::LargeUtf8 => {
+            let arr = col.as_any().downcast_ref::<LargeStringArray>().ok_or("LargeUtf8")?;
+            Ok(GroupValue::Str(arr.value(row).to_string()))
+        }
         DataType::Boolean => {
             let arr = col.as_any().downcast_ref::<BooleanArray>().ok_or("Boolean")?;
             Ok(GroupValue::Bool(arr.value(row)))
         }
+        DataType::Date32 => {
+            let arr = col.as_any().downcast_ref::<Date32Array>().ok_or("Date32")?;
+            Ok(GroupValue::Date32(arr.value(row)))
+        }
+        DataType::Timestamp(_, _) => Ok(GroupValue::Timestamp(
+            timestamp_at(col, row).ok_or("Timestamp")?,
+        )),
         _ => Err(format!("Unsupported group type: {:?}", col.data_type())),
     }
 }
 
-fn extract_numeric(col: &ArrayRef, row: usize) -> Option<f64> {
+pub(crate) fn extract_numeric(col: &ArrayRef, row: usize) -> Option<f64> {
     use arrow::array::*;
     if col.is_null(row) {
         return None;
@@ -319,7 +453,12 @@ where
     if vec.is_empty() {
         return Err("empty".to_string());
     }
-    let first = vec[0];
+    // Pick the array type from a non-null value: a null group key may come first
+    let first = vec
+        .iter()
+        .copied()
+        .find(|v| !matches!(v, GroupValue::Null))
+        .unwrap_or(vec[0]);
     match first {
         GroupValue::I32(_) => {
             let arr: Vec<Option<i32>> = vec
@@ -371,6 +510,9 @@ where
                     }
                 })
                 .collect();
+            if *default_type == DataType::LargeUtf8 {
+                return Ok(Arc::new(arrow::array::LargeStringArray::from(arr)) as ArrayRef);
+            }
             Ok(Arc::new(arrow::array::StringArray::from(arr)) as ArrayRef)
         }
         GroupValue::Bool(_) => {
@@ -386,6 +528,34 @@ where
                 .collect();
             Ok(Arc::new(arrow::array::BooleanArray::from(arr)) as ArrayRef)
         }
+        GroupValue::Date32(_) => {
+            let arr: Vec<Option<i32>> = vec
+                .iter()
+                .map(|v| {
+                    if let GroupValue::Date32(x) = v {
+                        Some(*x)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            Ok(Arc::new(arrow::array::Date32Array::from(arr)) as ArrayRef)
+        }
+        GroupValue::Timestamp(_) => {
+            let arr: Vec<Option<i64>> = vec
+                .iter()
+                .map(|v| {
+                    if let GroupValue::Timestamp(x) = v {
+                        Some(*x)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            // Values are in the column's unit, so casting only attaches its unit and zone
+            arrow::compute::cast(&arrow::array::Int64Array::from(arr), default_type)
+                .map_err(|e| format!("Failed to build timestamp group column: {}", e))
+        }
         GroupValue::Null => {
             let len = vec.len();
             Ok(arrow::array::new_null_array(default_type, len))
@@ -415,13 +585,7 @@ where
         AggregateFunction::Sum => {
             let arr: Vec<Option<f64>> = vec
                 .iter()
-                .map(|s| {
-                    if let AggState::Sum(v) = s {
-                        Some(*v)
-                    } else {
-                        None
-                    }
-                })
+                .map(|s| if let AggState::Sum(v) = s { *v } else { None })
                 .collect();
             Ok(Arc::new(arrow::array::Float64Array::from(arr)) as ArrayRef)
         }
@@ -446,14 +610,12 @@ where
             let arr: Vec<Option<f64>> = vec
                 .iter()
                 .map(|s| {
-                    if let AggState::Min(v) = s {
-                        if v.is_finite() {
-                            Some(*v)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
+                    match s {
+                        AggState::Min {
+                            value,
+                            has_value: true,
+                        } => Some(*value),
+                        _ => None,
                     }
                 })
                 .collect();
@@ -463,12 +625,27 @@ where
             let arr: Vec<Option<f64>> = vec
                 .iter()
                 .map(|s| {
-                    if let AggState::Max(v) = s {
-                        if v.is_finite() {
-                            Some(*v)
-                        } else {
-                            None
-                        }
+                    match s {
+                        AggState::Max {
+                            value,
+                            has_value: true,
+                        } => Some(*value),
+                        _ => None,
+                    }
+                })
+                .collect();
+            Ok(Arc::new(arrow::array::Float64Array::from(arr)) as ArrayRef)
+        }
+        AggregateFunction::Median | AggregateFunction::Percentile(_) => {
+            let p = match agg.function {
+                AggregateFunction::Percentile(p) => p,
+                _ => 0.5,
+            };
+            let arr: Vec<Option<f64>> = vec
+                .iter()
+                .map(|s| {
+                    if let AggState::Collect(values) = s {
+                        percentile(values, p)
                     } else {
                         None
                     }
@@ -479,6 +656,23 @@ where
     }
 }
 
+/// Percentile `p` of `values` by linear interpolation between the closest ranks
+/// (so p = 0.5 averages the two middle values of an even count); None when empty
+fn percentile(values: &[f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    if sorted[lower] == sorted[upper] {
+        // Nothing to interpolate; interpolating would turn an infinite value into NaN
+        return Some(sorted[lower]);
+    }
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
 impl Operator for AggregateOperator {
     fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
         self.hash_aggregate(std::slice::from_ref(input))
@@ -488,8 +682,398 @@ impl Operator for AggregateOperator {
         self.schema.clone()
     }
 
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     fn execute_many(&self, inputs: &[RecordBatch]) -> Result<Vec<RecordBatch>, String> {
         let batch = self.hash_aggregate(inputs)?;
         Ok(if batch.is_empty() { vec![] } else { vec![batch] })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{col, lit_int32, ExprBuilder};
+    use crate::types::ScalarValue;
+    use arrow::array::{Array, Int32Array, Int64Array};
+
+    fn input_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, false),
+            Field::new("amount", DataType::Int32, true),
+        ]))
+    }
+
+    fn without_column(function: AggregateFunction) -> Aggregation {
+        Aggregation {
+            function,
+            column: None,
+            alias: "out".to_string(),
+            filter: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_aggregations() {
+        let aggs = vec![
+            Aggregation::count_star("n"),
+            Aggregation::count("amount", "non_null"),
+            Aggregation::sum("amount", "total"),
+            Aggregation::avg("amount", "mean"),
+            Aggregation::min("amount", "lo"),
+            Aggregation::max("amount", "hi"),
+        ];
+        let op = AggregateOperator::new(vec![col("region")], aggs, input_schema()).unwrap();
+        assert_eq!(op.schema().fields().len(), 7);
+    }
+
+    #[test]
+    fn test_count_star_mixed_with_other_aggregates() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("g", DataType::Utf8, false),
+            Field::new("maybe", DataType::Int32, true),
+            Field::new("x", DataType::Int32, false),
+            Field::new("y", DataType::Float64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["a", "b", "a", "a", "b"])),
+                Arc::new(Int32Array::from(vec![Some(1), None, None, Some(4), None])),
+                Arc::new(Int32Array::from(vec![10, 20, 30, 40, 50])),
+                Arc::new(arrow::array::Float64Array::from(vec![
+                    Some(1.0),
+                    Some(2.0),
+                    None,
+                    Some(5.0),
+                    Some(4.0),
+                ])),
+            ],
+        )
+        .unwrap();
+        let op = AggregateOperator::new(
+            vec![col("g")],
+            vec![
+                Aggregation::count_star("n"),
+                Aggregation::count("maybe", "non_null"),
+                Aggregation::sum("x", "total"),
+                Aggregation::avg("y", "mean"),
+            ],
+            schema,
+        )
+        .unwrap();
+        // Split so the states carry across batches
+        let out = op.execute_many(&[batch.slice(0, 2).unwrap(), batch.slice(2, 3).unwrap()]);
+        let rows: Vec<String> = out.unwrap()[0]
+            .rows()
+            .map(|row| {
+                let row: Vec<String> = row.unwrap().iter().map(|v| v.to_string()).collect();
+                row.join(" ")
+            })
+            .collect();
+        // a: 3 rows, 2 non-null, 10+30+40, avg of 1 and 5; b: 2 rows, 0 non-null
+        assert_eq!(rows, vec!["'a' 3 2 80.0 3.0", "'b' 2 0 70.0 3.0"]);
+    }
+
+    #[test]
+    fn test_global_aggregate_over_no_rows() {
+        let aggs = vec![
+            Aggregation::count_star("n"),
+            Aggregation::sum("amount", "total"),
+            Aggregation::avg("amount", "mean"),
+            Aggregation::min("amount", "low"),
+        ];
+        let op = AggregateOperator::new(vec![], aggs.clone(), input_schema()).unwrap();
+        let empty = RecordBatch::new_empty(input_schema());
+        for inputs in [vec![], vec![empty.clone()]] {
+            let out = op.execute_many(&inputs).unwrap();
+            assert_eq!(out.len(), 1);
+            let row = out[0].rows().next().unwrap().unwrap();
+            assert_eq!(
+                row,
+                vec![
+                    ScalarValue::Int64(0),
+                    ScalarValue::Null,
+                    ScalarValue::Null,
+                    ScalarValue::Null
+                ]
+            );
+        }
+
+        // With GROUP BY there are no groups, so no rows
+        let grouped = AggregateOperator::new(vec![col("region")], aggs, input_schema()).unwrap();
+        assert!(grouped.execute_many(&[empty]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_estimated_groups_does_not_change_result() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("g", DataType::Int32, false),
+            Field::new("x", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from((0..100).map(|i| i % 7).collect::<Vec<_>>())),
+                Arc::new(Int32Array::from((0..100).collect::<Vec<_>>())),
+            ],
+        )
+        .unwrap();
+        let run = |hint: Option<usize>| {
+            let op = AggregateOperator::new(
+                vec![col("g")],
+                vec![Aggregation::count_star("n"), Aggregation::sum("x", "total")],
+                schema.clone(),
+            )
+            .unwrap()
+            .with_estimated_groups(hint);
+            let out = op.execute_many(&[batch.slice(0, 40).unwrap(), batch.slice(40, 60).unwrap()]);
+            out.unwrap()[0].to_arrow().unwrap()
+        };
+        let expected = run(None);
+        assert_eq!(expected.num_rows(), 7);
+        // Too small, exact and far too large hints all give the same groups
+        for hint in [0, 1, 7, usize::MAX] {
+            assert_eq!(run(Some(hint)), expected);
+        }
+    }
+
+    #[test]
+    fn test_filter_per_aggregation() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("g", DataType::Utf8, false),
+            Field::new("x", DataType::Int32, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["a", "a", "b", "a", "b"])),
+                Arc::new(Int32Array::from(vec![Some(1), Some(5), Some(2), None, Some(8)])),
+            ],
+        )
+        .unwrap();
+        let op = AggregateOperator::new(
+            vec![col("g")],
+            vec![
+                Aggregation::sum("x", "small").with_filter(col("x").lt(lit_int32(3))),
+                Aggregation::sum("x", "large").with_filter(col("x").ge(lit_int32(3))),
+                // The null x gives a null predicate, so that row is not counted
+                Aggregation::count_star("n").with_filter(col("x").lt(lit_int32(3))),
+            ],
+            schema.clone(),
+        )
+        .unwrap();
+        let out = op.execute_many(&[batch.slice(0, 2).unwrap(), batch.slice(2, 3).unwrap()]);
+        let rows: Vec<String> = out.unwrap()[0]
+            .rows()
+            .map(|row| {
+                let row: Vec<String> = row.unwrap().iter().map(|v| v.to_string()).collect();
+                row.join(" ")
+            })
+            .collect();
+        assert_eq!(rows, vec!["'a' 1.0 5.0 1", "'b' 2.0 8.0 1"]);
+
+        let bad = Aggregation::sum("x", "total").with_filter(col("x"));
+        let err = AggregateOperator::new(vec![], vec![bad], schema).err().unwrap();
+        assert!(err.contains("not Boolean"), "{}", err);
+    }
+
+    #[test]
+    fn test_group_by_expression() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = |values: Vec<i32>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))]).unwrap()
+        };
+        let parity = col("a").modulo(lit_int32(2));
+        let op = AggregateOperator::new(
+            vec![parity.clone()],
+            vec![Aggregation::count_star("n")],
+            schema.clone(),
+        )
+        .unwrap();
+        assert_eq!(op.schema().field(0).name(), &parity.output_name());
+
+        let out = op
+            .execute_many(&[batch(vec![1, 2, 3]), batch(vec![4, 5, 7, 9])])
+            .unwrap();
+        let keys = out[0].column(0).unwrap();
+        let keys = keys.as_any().downcast_ref::<Int32Array>().unwrap();
+        let counts = out[0].column(1).unwrap();
+        let counts = counts.as_any().downcast_ref::<Int64Array>().unwrap();
+        let mut groups: Vec<(i32, i64)> =
+            (0..out[0].num_rows()).map(|i| (keys.value(i), counts.value(i))).collect();
+        groups.sort();
+        assert_eq!(groups, vec![(0, 2), (1, 5)]);
+
+        // Columns referenced by group expressions must exist
+        let missing = col("b").modulo(lit_int32(2));
+        assert!(AggregateOperator::new(vec![missing], vec![], schema).is_err());
+    }
+
+    #[test]
+    fn test_median_and_percentile() {
+        let batch = RecordBatch::try_new(
+            input_schema(),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["a", "b", "a", "b", "a", "b", "a"])),
+                Arc::new(Int32Array::from(vec![
+                    Some(7),
+                    Some(3),
+                    Some(1),
+                    None,
+                    Some(4),
+                    Some(9),
+                    Some(10),
+                ])),
+            ],
+        )
+        .unwrap();
+        let op = AggregateOperator::new(
+            vec![col("region")],
+            vec![
+                Aggregation::median("amount", "median"),
+                Aggregation::percentile("amount", 0.9, "p90"),
+            ],
+            input_schema(),
+        )
+        .unwrap();
+        let out = op.execute_many(&[batch.slice(0, 4).unwrap(), batch.slice(4, 3).unwrap()]);
+        let out = &out.unwrap()[0];
+
+        let regions = out.column(0).unwrap();
+        let regions = regions.as_any().downcast_ref::<arrow::array::StringArray>().unwrap();
+        let median = out.column(1).unwrap();
+        let median = median.as_any().downcast_ref::<arrow::array::Float64Array>().unwrap();
+        let p90 = out.column(2).unwrap();
+        let p90 = p90.as_any().downcast_ref::<arrow::array::Float64Array>().unwrap();
+        let mut rows: Vec<(&str, f64, f64)> = (0..out.num_rows())
+            .map(|i| (regions.value(i), median.value(i), p90.value(i)))
+            .collect();
+        rows.sort_by(|x, y| x.0.cmp(y.0));
+        // a: [1, 4, 7, 10] -> even count averages 4 and 7; b: [3, 9] (null ignored)
+        assert!((rows[0].1 - 5.5).abs() < 1e-9);
+        assert!((rows[0].2 - 9.1).abs() < 1e-9);
+        assert!((rows[1].1 - 6.0).abs() < 1e-9);
+        assert!((rows[1].2 - 8.4).abs() < 1e-9);
+
+        assert_eq!(percentile(&[], 0.5), None);
+        assert!(Aggregation::percentile("amount", 1.5, "bad").validate().is_err());
+    }
+
+    #[test]
+    fn test_infinity_and_nan() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("g", DataType::Utf8, false),
+            Field::new("x", DataType::Float64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["inf", "inf", "nan", "nan", "none"])),
+                Arc::new(arrow::array::Float64Array::from(vec![
+                    Some(f64::INFINITY),
+                    Some(f64::INFINITY),
+                    Some(f64::NAN),
+                    Some(1.0),
+                    None,
+                ])),
+            ],
+        )
+        .unwrap();
+        let op = AggregateOperator::new(
+            vec![col("g")],
+            vec![
+                Aggregation::min("x", "lo"),
+                Aggregation::max("x", "hi"),
+                Aggregation::sum("x", "total"),
+                Aggregation::median("x", "mid"),
+            ],
+            schema,
+        )
+        .unwrap();
+        let out = op.execute(&batch).unwrap();
+        let groups = out.column(0).unwrap();
+        let groups = groups.as_any().downcast_ref::<arrow::array::StringArray>().unwrap();
+        let value = |group: &str, column: usize| -> Option<f64> {
+            let row = (0..out.num_rows()).find(|&i| groups.value(i) == group).unwrap();
+            let array = out.column(column).unwrap();
+            let array = array.as_any().downcast_ref::<arrow::array::Float64Array>().unwrap();
+            array.is_valid(row).then(|| array.value(row))
+        };
+
+        // A real infinity is a value, not "no rows"
+        assert_eq!(value("inf", 1), Some(f64::INFINITY));
+        assert_eq!(value("inf", 2), Some(f64::INFINITY));
+        assert_eq!(value("inf", 4), Some(f64::INFINITY));
+        // NaN is greater than every number for MIN / MAX, and poisons SUM
+        assert_eq!(value("nan", 1), Some(1.0));
+        assert!(value("nan", 2).unwrap().is_nan());
+        assert!(value("nan", 3).unwrap().is_nan());
+        // Only nulls: no value at all
+        assert_eq!(value("none", 1), None);
+        assert_eq!(value("none", 2), None);
+    }
+
+    #[test]
+    fn test_column_functions_require_column() {
+        for function in [
+            AggregateFunction::Sum,
+            AggregateFunction::Avg,
+            AggregateFunction::Min,
+            AggregateFunction::Max,
+            AggregateFunction::Median,
+            AggregateFunction::Percentile(0.95),
+        ] {
+            let agg = without_column(function);
+            assert!(agg.validate().is_err(), "{:?} without column", function);
+            assert!(AggregateOperator::new(vec![], vec![agg], input_schema()).is_err());
+        }
+        assert!(without_column(AggregateFunction::Count).validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_alias_and_column() {
+        assert!(Aggregation::count_star("").validate().is_err());
+        assert!(Aggregation::sum("", "total").validate().is_err());
+
+        // Column must exist in the input
+        let missing = Aggregation::sum("missing", "total");
+        assert!(missing.validate().is_ok());
+        assert!(AggregateOperator::new(vec![], vec![missing], input_schema()).is_err());
+    }
+
+    #[test]
+    fn test_with_order_by_sorts_groups() {
+        let batch = RecordBatch::try_new(
+            input_schema(),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["b", "c", "a", "b"])),
+                Arc::new(Int32Array::from(vec![1, 2, 3, 4])),
+            ],
+        )
+        .unwrap();
+        let op = || {
+            AggregateOperator::new(
+                vec![col("region")],
+                vec![Aggregation::sum("amount", "total")],
+                input_schema(),
+            )
+            .unwrap()
+        };
+        let order = |column: &str| OrderByExpr {
+            column: column.to_string(),
+            ascending: true,
+        };
+        let sorted = op().with_order_by(vec![order("region")]).unwrap();
+        let out = sorted.execute(&batch).unwrap();
+        let regions = out.column(0).unwrap();
+        let regions = regions.as_any().downcast_ref::<arrow::array::StringArray>().unwrap();
+        assert_eq!(regions.iter().flatten().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+
+        // Only group columns can be sorted on before the aggregates are final
+        let err = op().with_order_by(vec![order("total")]).err().unwrap();
+        assert!(err.contains("'total'"), "{}", err);
+    }
+}
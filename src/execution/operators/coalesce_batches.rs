@@ -0,0 +1,128 @@
+// Merging of small adjacent batches
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::Operator;
+
+/// Coalesce operator: concatenates runs of adjacent batches with fewer than `min_rows`
+/// rows until each run reaches `min_rows`, e.g. after a selective filter leaves many
+/// tiny batches. Larger batches pass through unchanged, and row order is kept.
+pub struct CoalesceBatchesOperator {
+    min_rows: usize,
+    schema: SchemaRef,
+}
+
+impl CoalesceBatchesOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "CoalesceBatches";
+
+    /// Create a new Coalesce operator
+    pub fn new(min_rows: usize, input_schema: SchemaRef) -> Self {
+        Self {
+            min_rows,
+            schema: input_schema,
+        }
+    }
+
+    /// Batches below this many rows are merged with their neighbours
+    pub fn min_rows(&self) -> usize {
+        self.min_rows
+    }
+
+    /// Add `batch` to the run in `pending`, returning the batches now ready to emit.
+    /// Call `finish` after the last batch to emit what is left.
+    pub fn push(
+        &self,
+        pending: &mut Vec<RecordBatch>,
+        batch: RecordBatch,
+    ) -> Result<Vec<RecordBatch>, String> {
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+        if batch.num_rows() >= self.min_rows {
+            let mut ready: Vec<RecordBatch> = self.finish(pending)?.into_iter().collect();
+            ready.push(batch);
+            return Ok(ready);
+        }
+        pending.push(batch);
+        if pending.iter().map(|b| b.num_rows()).sum::<usize>() >= self.min_rows {
+            return Ok(self.finish(pending)?.into_iter().collect());
+        }
+        Ok(Vec::new())
+    }
+
+    /// Concatenate and clear the run in `pending`, if any
+    pub fn finish(&self, pending: &mut Vec<RecordBatch>) -> Result<Option<RecordBatch>, String> {
+        match pending.len() {
+            0 => Ok(None),
+            1 => Ok(pending.pop()),
+            _ => {
+                let merged = RecordBatch::concat(pending)?;
+                pending.clear();
+                Ok(Some(merged))
+            }
+        }
+    }
+}
+
+impl Operator for CoalesceBatchesOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        Ok(input.clone())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn execute_many(&self, inputs: &[RecordBatch]) -> Result<Vec<RecordBatch>, String> {
+        let mut pending = Vec::new();
+        let mut out = Vec::new();
+        for batch in inputs {
+            out.extend(self.push(&mut pending, batch.clone())?);
+        }
+        out.extend(self.finish(&mut pending)?);
+        Ok(out)
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_merges_small_neighbours_only() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = |values: Vec<i32>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))]).unwrap()
+        };
+        let op = CoalesceBatchesOperator::new(3, schema.clone());
+        let inputs = vec![
+            batch(vec![1]),
+            batch(vec![2]),
+            batch(vec![]),
+            batch(vec![3]),
+            batch(vec![4]),
+            batch(vec![5, 6, 7, 8]),
+            batch(vec![9]),
+        ];
+        let out = op.execute_many(&inputs).unwrap();
+        let sizes: Vec<usize> = out.iter().map(|b| b.num_rows()).collect();
+        // 1+2+3 reach the threshold, 4 is flushed by the large batch, 9 is left over
+        assert_eq!(sizes, vec![3, 1, 4, 1]);
+        let values: Vec<i32> = out
+            .iter()
+            .flat_map(|b| b.i32_column("a").unwrap().values().to_vec())
+            .collect();
+        assert_eq!(values, (1..=9).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,121 @@
+// Null replacement
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::Operator;
+use crate::types::ScalarValue;
+use arrow::array::{Array, ArrayRef, Scalar};
+use arrow::datatypes::{Field, Schema};
+use std::sync::Arc;
+
+/// FillNull operator: replaces nulls with a constant.
+/// With a column name, only that column is filled and its type must match the value.
+/// Without one, every column whose type matches the value is filled; others pass through.
+pub struct FillNullOperator {
+    /// Indices of the columns to fill
+    column_indices: Vec<usize>,
+    value: ScalarValue,
+    schema: SchemaRef,
+}
+
+impl FillNullOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "FillNull";
+
+    /// Create a new FillNull operator
+    pub fn new(
+        column: Option<String>,
+        value: ScalarValue,
+        input_schema: SchemaRef,
+    ) -> Result<Self, String> {
+        if value.is_null() {
+            return Err("Fill value cannot be null".to_string());
+        }
+
+        let column_indices: Vec<usize> = match &column {
+            Some(name) => {
+                let idx = input_schema
+                    .index_of(name)
+                    .map_err(|_| format!("Column '{}' not found", name))?;
+                let data_type = input_schema.field(idx).data_type();
+                if *data_type != value.data_type() {
+                    return Err(format!(
+                        "Fill value type {:?} does not match column '{}' of type {:?}",
+                        value.data_type(),
+                        name,
+                        data_type
+                    ));
+                }
+                vec![idx]
+            }
+            None => input_schema
+                .fields()
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| *f.data_type() == value.data_type())
+                .map(|(i, _)| i)
+                .collect(),
+        };
+
+        // Filled columns can no longer contain nulls
+        let fields: Vec<Field> = input_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let field = f.as_ref().clone();
+                if column_indices.contains(&i) {
+                    field.with_nullable(false)
+                } else {
+                    field
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            column_indices,
+            value,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    fn fill(&self, col: &ArrayRef) -> Result<ArrayRef, String> {
+        if col.null_count() == 0 {
+            return Ok(col.clone());
+        }
+        let mask = arrow::compute::is_null(col.as_ref())
+            .map_err(|e| format!("Failed to compute null mask: {}", e))?;
+        let fill = Scalar::new(self.value.to_array(1));
+        arrow::compute::kernels::zip::zip(&mask, &fill, col)
+            .map_err(|e| format!("Failed to fill nulls: {}", e))
+    }
+}
+
+impl Operator for FillNullOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        let columns: Vec<ArrayRef> = input
+            .columns()
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                if self.column_indices.contains(&i) {
+                    self.fill(col)
+                } else {
+                    Ok(col.clone())
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        RecordBatch::try_new(self.schema.clone(), columns)
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows)
+    }
+}
@@ -2,19 +2,22 @@
 
 use crate::execution::batch::{RecordBatch, SchemaRef};
 use crate::execution::operators::Operator;
-use crate::planner::logical_plan::{BinaryOp, LogicalExpr, LogicalValue};
-use arrow::array::{ArrayRef, BooleanArray};
-use arrow_ord::comparison::{eq_dyn, gt_dyn, gt_eq_dyn, lt_dyn, lt_eq_dyn, neq_dyn};
-use std::sync::Arc;
+use crate::execution::expression::{evaluate_predicate, evaluate_predicate_with};
+use crate::planner::logical_plan::LogicalExpr;
+use crate::planner::optimizer::selectivity;
 
 /// Filter operator that applies a predicate expression to filter rows
 /// Uses vectorized execution with Arrow's compute kernels
 pub struct FilterOperator {
     predicate: LogicalExpr,
     schema: SchemaRef,
+    checked_arithmetic: bool,
 }
 
 impl FilterOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Filter";
+
     /// Create a new Filter operator
     /// 
     /// # Arguments
@@ -28,115 +31,32 @@ impl FilterOperator {
         Ok(Self {
             predicate,
             schema: input_schema,
+            checked_arithmetic: false,
         })
     }
 
-    /// Evaluate a logical expression to a boolean array
-    /// This is the core of vectorized expression evaluation
-    fn evaluate_expr(
-        &self,
-        batch: &RecordBatch,
-        expr: &LogicalExpr,
-    ) -> Result<BooleanArray, String> {
-        match expr {
-            LogicalExpr::Column(_) => {
-                Err("Cannot evaluate column as boolean without comparison".to_string())
-            }
-            LogicalExpr::Literal(LogicalValue::Boolean(value)) => {
-                // Create a boolean array with all values set to the literal
-                let len = batch.num_rows();
-                Ok(BooleanArray::from(vec![*value; len]))
-            }
-            LogicalExpr::BinaryExpr { left, op, right } => {
-                // Evaluate left and right sides to arrays
-                let left_array = self.evaluate_to_array(batch, left)?;
-                let right_array = self.evaluate_to_array(batch, right)?;
-
-                // Apply binary operation using Arrow's vectorized compute (eq_dyn works with &dyn Array)
-                match op {
-                    BinaryOp::Eq => eq_dyn(left_array.as_ref(), right_array.as_ref())
-                        .map_err(|e| format!("Failed to evaluate equality: {}", e)),
-                    BinaryOp::Neq => neq_dyn(left_array.as_ref(), right_array.as_ref())
-                        .map_err(|e| format!("Failed to evaluate inequality: {}", e)),
-                    BinaryOp::Lt => lt_dyn(left_array.as_ref(), right_array.as_ref())
-                        .map_err(|e| format!("Failed to evaluate less than: {}", e)),
-                    BinaryOp::Le => lt_eq_dyn(left_array.as_ref(), right_array.as_ref())
-                        .map_err(|e| format!("Failed to evaluate less than or equal: {}", e)),
-                    BinaryOp::Gt => gt_dyn(left_array.as_ref(), right_array.as_ref())
-                        .map_err(|e| format!("Failed to evaluate greater than: {}", e)),
-                    BinaryOp::Ge => gt_eq_dyn(left_array.as_ref(), right_array.as_ref())
-                        .map_err(|e| format!("Failed to evaluate greater than or equal: {}", e)),
-                    BinaryOp::And => {
-                        let left_bool = self.as_boolean_array(&left_array)?;
-                        let right_bool = self.as_boolean_array(&right_array)?;
-                        arrow::compute::and(left_bool, right_bool)
-                            .map_err(|e| format!("Failed to evaluate AND: {}", e))
-                    }
-                    BinaryOp::Or => {
-                        let left_bool = self.as_boolean_array(&left_array)?;
-                        let right_bool = self.as_boolean_array(&right_array)?;
-                        arrow::compute::or(left_bool, right_bool)
-                            .map_err(|e| format!("Failed to evaluate OR: {}", e))
-                    }
-                }
-            }
-            LogicalExpr::Literal(LogicalValue::Int32(_))
-            | LogicalExpr::Literal(LogicalValue::Int64(_))
-            | LogicalExpr::Literal(LogicalValue::Float64(_))
-            | LogicalExpr::Literal(LogicalValue::String(_)) => {
-                Err("Non-boolean literal cannot be used as predicate".to_string())
-            }
-        }
+    /// Report integer overflow in the predicate's arithmetic as an error instead of wrapping
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Self {
+        self.checked_arithmetic = checked;
+        self
     }
 
-    /// Evaluate an expression to an Arrow array (not boolean)
-    fn evaluate_to_array(
-        &self,
-        batch: &RecordBatch,
-        expr: &LogicalExpr,
-    ) -> Result<ArrayRef, String> {
-        match expr {
-            LogicalExpr::Column(name) => {
-                batch
-                    .column_by_name(name)
-                    .ok_or_else(|| format!("Column '{}' not found", name))
-                    .map(|col| col.clone())
-            }
-            LogicalExpr::Literal(value) => {
-                let len = batch.num_rows();
-                match value {
-                    LogicalValue::Int32(v) => {
-                        Ok(Arc::new(arrow::array::Int32Array::from(vec![*v; len])))
-                    }
-                    LogicalValue::Int64(v) => {
-                        Ok(Arc::new(arrow::array::Int64Array::from(vec![*v; len])))
-                    }
-                    LogicalValue::Float64(v) => {
-                        Ok(Arc::new(arrow::array::Float64Array::from(vec![*v; len])))
-                    }
-                    LogicalValue::String(v) => {
-                        Ok(Arc::new(arrow::array::StringArray::from(vec![v.as_str(); len])))
-                    }
-                    LogicalValue::Boolean(v) => {
-                        Ok(Arc::new(arrow::array::BooleanArray::from(vec![*v; len])))
-                    }
-                }
-            }
-            LogicalExpr::BinaryExpr { .. } => {
-                // For binary expressions, evaluate to boolean first
-                let bool_array = self.evaluate_expr(batch, expr)?;
-                Ok(Arc::new(bool_array))
-            }
-        }
+    /// The predicate rows must satisfy
+    pub fn predicate(&self) -> &LogicalExpr {
+        &self.predicate
     }
+}
 
-    /// Convert an array to a boolean array reference
-    fn as_boolean_array<'a>(&self, array: &'a ArrayRef) -> Result<&'a BooleanArray, String> {
-        array
-            .as_any()
-            .downcast_ref::<BooleanArray>()
-            .ok_or_else(|| "Array is not a boolean array".to_string())
-    }
+/// Filter already-collected batches by `predicate`, without building a plan.
+/// Returns one (possibly empty) batch per input batch.
+pub fn apply_predicate(
+    batches: &[RecordBatch],
+    predicate: &LogicalExpr,
+) -> Result<Vec<RecordBatch>, String> {
+    batches
+        .iter()
+        .map(|batch| batch.filter(&evaluate_predicate(predicate, batch)?))
+        .collect()
 }
 
 impl Operator for FilterOperator {
@@ -144,24 +64,103 @@ impl Operator for FilterOperator {
     /// Uses vectorized filtering with Arrow's compute kernels
     fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
         // Evaluate the predicate to get a boolean mask
-        let boolean_mask = self.evaluate_expr(input, &self.predicate)?;
+        let boolean_mask =
+            evaluate_predicate_with(&self.predicate, input, self.checked_arithmetic)?;
+
+        // Apply the mask to all columns at once with Arrow's filter kernel
+        input.filter(&boolean_mask)
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Input rows scaled by the optimizer's assumed selectivity for the predicate
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some((input_rows as f64 * selectivity(&self.predicate)).round() as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{col, lit_int32, ExprBuilder};
+    use crate::planner::logical_plan::BinaryOp;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_filter_zero_column_batch() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3, 4]))],
+        )
+        .unwrap();
+        let empty = batch.drop_column("a").unwrap();
+        assert_eq!(empty.num_columns(), 0);
+        assert_eq!(empty.num_rows(), 4);
+
+        let always = FilterOperator::new(lit_int32(1).eq(lit_int32(1)), empty.schema().clone())
+            .unwrap();
+        assert_eq!(always.execute(&empty).unwrap().num_rows(), 4);
+
+        let never = FilterOperator::new(lit_int32(1).eq(lit_int32(2)), empty.schema().clone())
+            .unwrap();
+        assert_eq!(never.execute(&empty).unwrap().num_rows(), 0);
 
-        // Use Arrow's vectorized filter function to apply the mask to all columns
-        // This is a vectorized operation processing the entire columns at once
-        let filtered_columns: Vec<ArrayRef> = input
-            .columns()
+        // Column predicates still keep the mask's count on a regular batch
+        let op = FilterOperator::new(col("a").gt(lit_int32(2)), batch.schema().clone()).unwrap();
+        assert_eq!(op.execute(&batch).unwrap().num_rows(), 2);
+    }
+
+    #[test]
+    fn test_apply_predicate() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let batches: Vec<RecordBatch> = [vec![Some(1), Some(5)], vec![None, Some(7), Some(3)]]
+            .into_iter()
+            .map(|values| {
+                RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+                    .unwrap()
+            })
+            .collect();
+
+        let filtered = apply_predicate(&batches, &col("a").gt(lit_int32(2))).unwrap();
+        assert_eq!(filtered.len(), 2);
+        let values: Vec<i32> = filtered
             .iter()
-            .map(|col| {
-                arrow::compute::filter(col, &boolean_mask)
-                    .map_err(|e| format!("Failed to filter column: {}", e))
+            .flat_map(|b| {
+                let a = b.column(0).unwrap().as_any().downcast_ref::<Int32Array>().unwrap();
+                a.values().to_vec()
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect();
+        assert_eq!(values, vec![5, 7, 3]);
 
-        // Create new batch with filtered columns
-        RecordBatch::try_new(self.schema.clone(), filtered_columns)
+        assert!(apply_predicate(&batches, &col("a")).is_err());
     }
 
-    fn schema(&self) -> SchemaRef {
-        self.schema.clone()
+    #[test]
+    fn test_estimated_output_rows() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let estimate = |predicate: LogicalExpr| {
+            FilterOperator::new(predicate, schema.clone())
+                .unwrap()
+                .estimated_output_rows(900)
+        };
+        assert_eq!(estimate(col("a").eq(lit_int32(1))), Some(90));
+        assert_eq!(estimate(col("a").gt(lit_int32(1))), Some(300));
+        assert_eq!(estimate(col("a").is_not_null()), Some(810));
+        // AND multiplies selectivities
+        let both = LogicalExpr::BinaryExpr {
+            left: Box::new(col("a").gt(lit_int32(1))),
+            op: BinaryOp::And,
+            right: Box::new(col("a").neq(lit_int32(5))),
+        };
+        assert_eq!(estimate(both), Some(270));
     }
 }
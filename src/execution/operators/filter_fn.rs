@@ -0,0 +1,49 @@
+// Filtering with a row closure
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::Operator;
+use crate::planner::logical_plan::RowPredicate;
+use arrow::array::BooleanArray;
+
+/// Filter operator that calls a closure once per row to decide whether to keep it. An
+/// escape hatch for conditions expressions cannot state; much slower than FilterOperator,
+/// which evaluates its predicate a whole column at a time.
+pub struct FilterFnOperator {
+    predicate: RowPredicate,
+    schema: SchemaRef,
+}
+
+impl FilterFnOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "FilterFn";
+
+    /// Create a new FilterFn operator
+    pub fn new(predicate: RowPredicate, input_schema: SchemaRef) -> Self {
+        Self {
+            predicate,
+            schema: input_schema,
+        }
+    }
+}
+
+impl Operator for FilterFnOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        let mask: BooleanArray = (0..input.num_rows())
+            .map(|row| Some((self.predicate.0)(input, row)))
+            .collect();
+        input.filter(&mask)
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Half the input rows, as nothing is known about the closure
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows / 2)
+    }
+}
@@ -0,0 +1,167 @@
+// Fused filter-then-project
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::expression::evaluate_predicate_with;
+use crate::execution::operators::{Operator, ProjectOperator};
+use crate::planner::logical_plan::LogicalExpr;
+use crate::planner::optimizer::selectivity;
+use std::sync::Arc;
+
+/// Filter and projection applied in one pass over each batch. Only the columns the
+/// projection reads are filtered, so no intermediate batch of every input column is
+/// built; the output matches a FilterOperator followed by a ProjectOperator.
+pub struct FilterProjectOperator {
+    predicate: LogicalExpr,
+    /// Input column indices read by the projection, and their schema
+    input_columns: Vec<usize>,
+    narrowed_schema: SchemaRef,
+    /// Projection over just those columns
+    project: ProjectOperator,
+    checked_arithmetic: bool,
+}
+
+impl FilterProjectOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "FilterProject";
+
+    /// Keep rows matching `predicate`, then select `columns` (as `ProjectOperator::new`)
+    pub fn new(
+        predicate: LogicalExpr,
+        columns: Vec<String>,
+        input_schema: SchemaRef,
+    ) -> Result<Self, String> {
+        let exprs = columns.iter().map(|c| LogicalExpr::Column(c.clone())).collect();
+        Self::build(predicate, exprs, input_schema, |schema| {
+            ProjectOperator::new(columns, schema)
+        })
+    }
+
+    /// Keep rows matching `predicate`, then compute `exprs` (as
+    /// `ProjectOperator::with_exprs`)
+    pub fn with_exprs(
+        predicate: LogicalExpr,
+        exprs: Vec<LogicalExpr>,
+        input_schema: SchemaRef,
+    ) -> Result<Self, String> {
+        Self::build(predicate, exprs.clone(), input_schema, |schema| {
+            ProjectOperator::with_exprs(exprs, schema)
+        })
+    }
+
+    fn build(
+        predicate: LogicalExpr,
+        exprs: Vec<LogicalExpr>,
+        input_schema: SchemaRef,
+        project: impl FnOnce(SchemaRef) -> Result<ProjectOperator, String>,
+    ) -> Result<Self, String> {
+        let mut input_columns = Vec::new();
+        for name in exprs.iter().flat_map(|e| e.column_names()) {
+            let idx = input_schema
+                .index_of(&name)
+                .map_err(|_| format!("Column '{}' not found in schema", name))?;
+            if !input_columns.contains(&idx) {
+                input_columns.push(idx);
+            }
+        }
+        let narrowed = input_schema.project(&input_columns).map_err(|e| e.to_string())?;
+        let narrowed_schema = Arc::new(narrowed);
+        Ok(Self {
+            predicate,
+            input_columns,
+            project: project(narrowed_schema.clone())?,
+            narrowed_schema,
+            checked_arithmetic: false,
+        })
+    }
+
+    /// Report integer overflow in the predicate's and projection's arithmetic as an error
+    /// instead of wrapping
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Self {
+        self.project = self.project.with_checked_arithmetic(checked);
+        self.checked_arithmetic = checked;
+        self
+    }
+
+    /// The predicate applied before projecting
+    pub fn predicate(&self) -> &LogicalExpr {
+        &self.predicate
+    }
+
+    /// Names of the output columns
+    pub fn column_names(&self) -> &[String] {
+        self.project.column_names()
+    }
+}
+
+impl Operator for FilterProjectOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        let mask = evaluate_predicate_with(&self.predicate, input, self.checked_arithmetic)?;
+        // Keep the row count, as the projection may read no columns at all
+        let columns = self.input_columns.iter().map(|&i| input.columns()[i].clone()).collect();
+        let narrowed = RecordBatch::try_new_with_row_count(
+            self.narrowed_schema.clone(),
+            columns,
+            input.num_rows(),
+        )?;
+        self.project.execute(&narrowed.filter(&mask)?)
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.project.schema()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Input rows scaled by the optimizer's assumed selectivity for the predicate
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some((input_rows as f64 * selectivity(&self.predicate)).round() as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{col, lit_int32, ExprBuilder};
+    use crate::execution::operators::FilterOperator;
+    use arrow::array::{ArrayRef, Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    #[test]
+    fn test_matches_filter_then_project() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5])),
+            Arc::new(Int32Array::from(vec![Some(10), None, Some(30), Some(40), None])),
+            Arc::new(StringArray::from(vec!["v", "w", "x", "y", "z"])),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+        let predicate = col("a").gt(lit_int32(1));
+        let filter = FilterOperator::new(predicate.clone(), schema.clone()).unwrap();
+        let filtered = filter.execute(&batch).unwrap();
+
+        // Plain columns: the filter column is not in the output
+        let names = vec!["name".to_string(), "b".to_string()];
+        let fused = FilterProjectOperator::new(predicate.clone(), names.clone(), schema.clone())
+            .unwrap();
+        let project = ProjectOperator::new(names, filtered.schema().clone()).unwrap();
+        let expected = project.execute(&filtered).unwrap();
+        assert_eq!(fused.schema(), project.schema());
+        let actual = fused.execute(&batch).unwrap();
+        assert_eq!(actual.to_arrow().unwrap(), expected.to_arrow().unwrap());
+        assert_eq!(actual.num_rows(), 4);
+
+        // Expressions, including one with no column references
+        let exprs = vec![col("b").plus(col("a")).alias("total"), lit_int32(7).alias("seven")];
+        let fused = FilterProjectOperator::with_exprs(predicate, exprs.clone(), schema).unwrap();
+        let project = ProjectOperator::with_exprs(exprs, filtered.schema().clone()).unwrap();
+        let expected = project.execute(&filtered).unwrap();
+        let actual = fused.execute(&batch).unwrap();
+        assert_eq!(actual.to_arrow().unwrap(), expected.to_arrow().unwrap());
+    }
+}
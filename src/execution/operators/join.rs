@@ -1,24 +1,39 @@
-// Hash joins (inner and left)
+// Hash joins (inner, left, semi and anti)
 
-use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::batch::{concat_batches, timestamp_at, RecordBatch, SchemaRef};
 use crate::planner::logical_plan::JoinType;
+use crate::types::{numeric_coercion, string_coercion, temporal_coercion};
 use arrow::array::ArrayRef;
 use arrow::datatypes::DataType;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Hash join: build a hash table from the right (build) side, probe with the left.
-/// Supports Inner and Left join.
+/// Supports Inner, Left, Semi and Anti join.
 pub struct HashJoinOperator {
     left_key: String,
     right_key: String,
     join_type: JoinType,
-    /// Output schema: left fields + right fields
+    /// Type both keys are cast to before hashing, so e.g. an Int32 key matches an Int64
+    key_type: DataType,
+    /// Probe each left batch separately rather than concatenating the left side first
+    streaming_probe: bool,
+    /// Let null keys match each other (SQL equality never matches nulls)
+    null_equals_null: bool,
+    /// Input schemas, for concatenating either side
+    left_schema: SchemaRef,
+    right_schema: SchemaRef,
+    /// Output schema: left fields + right fields (left only for Semi and Anti)
     schema: SchemaRef,
 }
 
 impl HashJoinOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "HashJoin";
+
     /// Create a new HashJoin operator. left_schema and right_schema are used to build output schema.
+    /// Keys of different numeric, string or date/time types are compared as their common
+    /// type (see `join_key_type`); other differing key types are an error.
     pub fn new(
         left_key: String,
         right_key: String,
@@ -26,64 +41,128 @@ impl HashJoinOperator {
         left_schema: SchemaRef,
         right_schema: SchemaRef,
     ) -> Result<Self, String> {
-        let mut fields = left_schema.fields().iter().map(|f| f.as_ref().clone()).collect::<Vec<_>>();
-        fields.extend(right_schema.fields().iter().map(|f| f.as_ref().clone()));
-        let schema = Arc::new(arrow::datatypes::Schema::new(fields));
+        let key_type = join_key_type(&left_key, &left_schema, &right_key, &right_schema)?;
+        let schema = join_output_schema(&left_schema, &right_schema, join_type);
         Ok(Self {
             left_key,
             right_key,
             join_type,
+            key_type,
+            streaming_probe: false,
+            null_equals_null: false,
+            left_schema,
+            right_schema,
             schema,
         })
     }
 
-    /// Execute the join. Both sides are concat'd to single batches, then hash join.
+    /// Probe left batches one at a time against the right side's hash table, emitting
+    /// one output batch per left batch. Peak memory is then bounded by the right side
+    /// plus one left batch, which suits a small (broadcast) right side.
+    pub fn with_streaming_probe(mut self, streaming_probe: bool) -> Self {
+        self.streaming_probe = streaming_probe;
+        self
+    }
+
+    /// Match null keys to each other. By default, as in SQL, a null key matches nothing
+    /// (a Left join still keeps the left row, null-padded).
+    pub fn with_null_equals_null(mut self, null_equals_null: bool) -> Self {
+        self.null_equals_null = null_equals_null;
+        self
+    }
+
+    /// Get the output schema of this join (left fields followed by right fields, or just
+    /// the left fields for Semi and Anti joins)
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Name of this operator, as `Operator::name`
+    pub fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Execute the join. The right side is concat'd to a single batch and hashed; the left
+    /// side is concat'd too, unless streaming probe is enabled.
     pub fn execute_join(
         &self,
         left_batches: &[RecordBatch],
         right_batches: &[RecordBatch],
     ) -> Result<Vec<RecordBatch>, String> {
-        let left = if left_batches.is_empty() {
+        if left_batches.is_empty() {
             return Ok(Vec::new());
-        } else if left_batches.len() == 1 {
-            left_batches[0].clone()
-        } else {
-            RecordBatch::concat(left_batches)?
-        };
+        }
+        let concat_left = || concat_batches(left_batches, &self.left_schema);
 
-        let right = if right_batches.is_empty() {
-            if matches!(self.join_type, JoinType::Left) {
-                // Left join with empty right: return left with nulls for right cols
-                return self.left_only_result(&left);
+        if right_batches.is_empty() {
+            if !matches!(self.join_type, JoinType::Left | JoinType::Anti) {
+                return Ok(Vec::new());
             }
-            return Ok(Vec::new());
-        } else if right_batches.len() == 1 {
-            right_batches[0].clone()
-        } else {
-            RecordBatch::concat(right_batches)?
-        };
+            // Left join with empty right: return left with nulls for right cols (an anti
+            // join has none, so it returns left as is)
+            if self.streaming_probe {
+                let mut out = Vec::new();
+                for left in left_batches {
+                    out.extend(left_only_result(&self.schema, left)?);
+                }
+                return Ok(out);
+            }
+            return left_only_result(&self.schema, &concat_left()?);
+        }
+        let right = concat_batches(right_batches, &self.right_schema)?;
 
         // Build: hash map from right key -> right row indices
         let right_col = right
             .column_by_name(&self.right_key)
             .ok_or_else(|| format!("Right key '{}' not found", self.right_key))?;
+        let right_col = &cast_join_key(right_col, &self.key_type)?;
         let mut map: HashMap<String, Vec<usize>> = HashMap::new();
         for row in 0..right.num_rows() {
+            if right_col.is_null(row) && !self.null_equals_null {
+                continue;
+            }
             let k = key_string(right_col, row)?;
             map.entry(k).or_default().push(row);
         }
 
+        if self.streaming_probe {
+            let mut out = Vec::new();
+            for left in left_batches {
+                out.extend(self.probe(&map, left, &right)?);
+            }
+            return Ok(out);
+        }
+        Ok(self.probe(&map, &concat_left()?, &right)?.into_iter().collect())
+    }
+
+    /// Probe one left batch against the right side's hash table
+    fn probe(
+        &self,
+        map: &HashMap<String, Vec<usize>>,
+        left: &RecordBatch,
+        right: &RecordBatch,
+    ) -> Result<Option<RecordBatch>, String> {
         // Probe: for each left row, find matches
         let left_col = left
             .column_by_name(&self.left_key)
             .ok_or_else(|| format!("Left key '{}' not found", self.left_key))?;
+        let left_col = &cast_join_key(left_col, &self.key_type)?;
 
         let mut left_indices = Vec::new();
         let mut right_indices: Vec<Option<usize>> = Vec::new();
 
         for lr in 0..left.num_rows() {
-            let k = key_string(left_col, lr)?;
-            if let Some(rows) = map.get(&k) {
+            let matches = if left_col.is_null(lr) && !self.null_equals_null {
+                None
+            } else {
+                map.get(&key_string(left_col, lr)?)
+            };
+            if matches!(self.join_type, JoinType::Semi | JoinType::Anti) {
+                if matches.is_some() == (self.join_type == JoinType::Semi) {
+                    left_indices.push(lr as u32);
+                    right_indices.push(None);
+                }
+            } else if let Some(rows) = matches {
                 for &rr in rows {
                     left_indices.push(lr as u32);
                     right_indices.push(Some(rr));
@@ -95,44 +174,118 @@ impl HashJoinOperator {
         }
 
         if left_indices.is_empty() {
-            return Ok(vec![]);
+            return Ok(None);
         }
 
-        // Build output: take left columns by left_indices; for right, take or null
-        let u32_indices = arrow::array::UInt32Array::from(left_indices.clone());
-        let left_cols: Vec<ArrayRef> = left
-            .columns()
-            .iter()
-            .map(|c| arrow_select::take::take(c.as_ref(), &u32_indices, None).map_err(|e| e.to_string()))
-            .collect::<Result<Vec<_>, _>>()?;
+        let out = build_join_output(&self.schema, left, right, &left_indices, &right_indices)?;
+        Ok(Some(out))
+    }
+}
 
-        let num_left = left.schema().fields().len();
+/// Type both join keys are compared as: their common type if they are equal, or both
+/// numeric (the wider one), both strings, or both dates/timestamps (as for comparisons).
+/// Other differing key types are an error.
+pub(crate) fn join_key_type(
+    left_key: &str,
+    left_schema: &SchemaRef,
+    right_key: &str,
+    right_schema: &SchemaRef,
+) -> Result<DataType, String> {
+    let left_type = left_schema
+        .field_with_name(left_key)
+        .map_err(|_| format!("Left key '{}' not found", left_key))?
+        .data_type();
+    let right_type = right_schema
+        .field_with_name(right_key)
+        .map_err(|_| format!("Right key '{}' not found", right_key))?
+        .data_type();
+    if left_type == right_type {
+        return Ok(left_type.clone());
+    }
+    numeric_coercion(left_type, right_type)
+        .or_else(|| string_coercion(left_type, right_type))
+        .or_else(|| temporal_coercion(left_type, right_type))
+        .ok_or_else(|| {
+            format!(
+                "Join keys '{}' ({:?}) and '{}' ({:?}) have incompatible types",
+                left_key, left_type, right_key, right_type
+            )
+        })
+}
+
+/// A key column as the common key type from `join_key_type`
+pub(crate) fn cast_join_key(col: &ArrayRef, key_type: &DataType) -> Result<ArrayRef, String> {
+    if col.data_type() == key_type {
+        return Ok(col.clone());
+    }
+    arrow::compute::cast(col, key_type)
+        .map_err(|e| format!("Failed to cast join key to {:?}: {}", key_type, e))
+}
+
+/// Build the joined batch: take left columns by `left_indices`; for right, take or null.
+/// Right columns are only built if the schema has them (not for Semi and Anti joins).
+pub(crate) fn build_join_output(
+    schema: &SchemaRef,
+    left: &RecordBatch,
+    right: &RecordBatch,
+    left_indices: &[u32],
+    right_indices: &[Option<usize>],
+) -> Result<RecordBatch, String> {
+    let u32_indices = arrow::array::UInt32Array::from(left_indices.to_vec());
+    let left_cols: Vec<ArrayRef> = left
+        .columns()
+        .iter()
+        .map(|c| arrow_select::take::take(c.as_ref(), &u32_indices, None).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut all_cols = left_cols;
+    if schema.fields().len() > left.num_columns() {
         let right_cols: Vec<ArrayRef> = right
             .columns()
             .iter()
-            .enumerate()
-            .map(|(i, c)| {
-                build_with_nulls(c.as_ref(), &right_indices).map_err(|e| e.to_string())
-            })
+            .map(|c| build_with_nulls(c.as_ref(), right_indices).map_err(|e| e.to_string()))
             .collect::<Result<Vec<_>, _>>()?;
-
-        let mut all_cols = left_cols;
         all_cols.extend(right_cols);
-        let out = RecordBatch::try_new(self.schema.clone(), all_cols)?;
-        Ok(vec![out])
     }
+    RecordBatch::try_new_with_row_count(schema.clone(), all_cols, left_indices.len())
+}
 
-    /// Left join with empty right: left with nulls for right columns (from output schema)
-    fn left_only_result(&self, left: &RecordBatch) -> Result<Vec<RecordBatch>, String> {
-        let num_left = left.schema().fields().len();
-        let mut cols = left.columns().to_vec();
-        for i in num_left..self.schema.fields().len() {
-            let f = self.schema.fields()[i].as_ref();
-            cols.push(arrow::array::new_null_array(f.data_type(), left.num_rows()));
+/// Output schema of a join: left fields followed by right fields (just the left schema
+/// for Semi and Anti joins). Right fields become nullable for Left joins, since unmatched
+/// rows are null-padded.
+pub(crate) fn join_output_schema(
+    left_schema: &SchemaRef,
+    right_schema: &SchemaRef,
+    join_type: JoinType,
+) -> SchemaRef {
+    if matches!(join_type, JoinType::Semi | JoinType::Anti) {
+        return left_schema.clone();
+    }
+    let mut fields = left_schema.fields().iter().map(|f| f.as_ref().clone()).collect::<Vec<_>>();
+    fields.extend(right_schema.fields().iter().map(|f| {
+        let f = f.as_ref().clone();
+        if matches!(join_type, JoinType::Left) {
+            f.with_nullable(true)
+        } else {
+            f
         }
-        let batch = RecordBatch::try_new(self.schema.clone(), cols)?;
-        Ok(vec![batch])
+    }));
+    Arc::new(arrow::datatypes::Schema::new(fields))
+}
+
+/// Left join with empty right: left with nulls for right columns (from output schema)
+pub(crate) fn left_only_result(
+    schema: &SchemaRef,
+    left: &RecordBatch,
+) -> Result<Vec<RecordBatch>, String> {
+    let num_left = left.schema().fields().len();
+    let mut cols = left.columns().to_vec();
+    for i in num_left..schema.fields().len() {
+        let f = schema.fields()[i].as_ref();
+        cols.push(arrow::array::new_null_array(f.data_type(), left.num_rows()));
     }
+    let batch = RecordBatch::try_new(schema.clone(), cols)?;
+    Ok(vec![batch])
 }
 
 fn key_string(col: &ArrayRef, row: usize) -> Result<String, String> {
@@ -153,47 +306,247 @@ fn key_string(col: &ArrayRef, row: usize) -> Result<String, String> {
             let a = col.as_any().downcast_ref::<Float64Array>().ok_or("Float64")?;
             Ok(format!("f64:{}", a.value(row)))
         }
-        DataType::Utf8 | DataType::LargeUtf8 => {
+        // Utf8 and LargeUtf8 share a key prefix, so keys of either width can match
+        DataType::Utf8 => {
             let a = col.as_any().downcast_ref::<StringArray>().ok_or("Utf8")?;
             Ok(format!("str:{}", a.value(row)))
         }
+        DataType::LargeUtf8 => {
+            let a = col.as_any().downcast_ref::<LargeStringArray>().ok_or("LargeUtf8")?;
+            Ok(format!("str:{}", a.value(row)))
+        }
         DataType::Boolean => {
             let a = col.as_any().downcast_ref::<BooleanArray>().ok_or("Bool")?;
             Ok(format!("bool:{}", a.value(row)))
         }
+        DataType::Date32 => {
+            let a = col.as_any().downcast_ref::<Date32Array>().ok_or("Date32")?;
+            Ok(format!("date:{}", a.value(row)))
+        }
+        // Both keys have the same unit after `cast_join_key`, so raw values compare
+        DataType::Timestamp(_, _) => {
+            Ok(format!("ts:{}", timestamp_at(col, row).ok_or("Timestamp")?))
+        }
         _ => Err(format!("Unsupported join key type: {:?}", col.data_type())),
     }
 }
 
 /// Build array from `base` by indexing with `indices`; None means null in output.
-fn build_with_nulls(base: &dyn arrow::array::Array, indices: &[Option<usize>]) -> Result<ArrayRef, String> {
-    use arrow::array::*;
-    match base.data_type() {
-        DataType::Int32 => {
-            let a = base.as_any().downcast_ref::<Int32Array>().ok_or("Int32")?;
-            let out: Vec<Option<i32>> = indices.iter().map(|o| o.and_then(|i| if a.is_null(i) { None } else { Some(a.value(i)) })).collect();
-            Ok(Arc::new(Int32Array::from(out)) as ArrayRef)
-        }
-        DataType::Int64 => {
-            let a = base.as_any().downcast_ref::<Int64Array>().ok_or("Int64")?;
-            let out: Vec<Option<i64>> = indices.iter().map(|o| o.and_then(|i| if a.is_null(i) { None } else { Some(a.value(i)) })).collect();
-            Ok(Arc::new(Int64Array::from(out)) as ArrayRef)
-        }
-        DataType::Float64 => {
-            let a = base.as_any().downcast_ref::<Float64Array>().ok_or("Float64")?;
-            let out: Vec<Option<f64>> = indices.iter().map(|o| o.and_then(|i| if a.is_null(i) { None } else { Some(a.value(i)) })).collect();
-            Ok(Arc::new(Float64Array::from(out)) as ArrayRef)
-        }
-        DataType::Utf8 | DataType::LargeUtf8 => {
-            let a = base.as_any().downcast_ref::<StringArray>().ok_or("Utf8")?;
-            let out: Vec<Option<&str>> = indices.iter().map(|o| o.and_then(|i| if a.is_null(i) { None } else { Some(a.value(i)) })).collect();
-            Ok(Arc::new(StringArray::from(out)) as ArrayRef)
-        }
-        DataType::Boolean => {
-            let a = base.as_any().downcast_ref::<BooleanArray>().ok_or("Bool")?;
-            let out: Vec<Option<bool>> = indices.iter().map(|o| o.and_then(|i| if a.is_null(i) { None } else { Some(a.value(i)) })).collect();
-            Ok(Arc::new(BooleanArray::from(out)) as ArrayRef)
+/// Works for any column type: `take` with a null index gives a null row.
+pub(crate) fn build_with_nulls(
+    base: &dyn arrow::array::Array,
+    indices: &[Option<usize>],
+) -> Result<ArrayRef, String> {
+    let indices: arrow::array::UInt32Array =
+        indices.iter().map(|i| i.map(|i| i as u32)).collect();
+    arrow_select::take::take(base, &indices, None)
+        .map_err(|e| format!("Failed to build {:?} join column: {}", base.data_type(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{
+        Array, Date32Array, Int32Array, StringArray, TimestampMicrosecondArray,
+    };
+    use arrow::datatypes::{Field, Schema, TimeUnit};
+
+    fn left_batch(ids: Vec<i32>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int32Array::from(ids))]).unwrap()
+    }
+
+    fn right_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Int32, false),
+            Field::new("label", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(vec![2, 4, 4])),
+                Arc::new(StringArray::from(vec!["two", "four", "FOUR"])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_streaming_probe_matches_concat_join() {
+        let left = vec![left_batch(vec![1, 2, 3]), left_batch(vec![4, 5]), left_batch(vec![2])];
+        let right = vec![right_batch()];
+        for join_type in [JoinType::Inner, JoinType::Left] {
+            let op = || {
+                HashJoinOperator::new(
+                    "id".to_string(),
+                    "key".to_string(),
+                    join_type,
+                    left[0].schema().clone(),
+                    right[0].schema().clone(),
+                )
+                .unwrap()
+            };
+            let concat = op().execute_join(&left, &right).unwrap();
+            let streaming = op().with_streaming_probe(true).execute_join(&left, &right).unwrap();
+
+            // One output batch per left batch with matches, same rows overall
+            assert_eq!(concat.len(), 1);
+            assert!(streaming.len() > 1);
+            let streamed = RecordBatch::concat(&streaming).unwrap();
+            assert_eq!(concat[0].to_arrow().unwrap(), streamed.to_arrow().unwrap());
+
+            let empty = op().with_streaming_probe(true).execute_join(&left, &[]).unwrap();
+            let expected_rows = if join_type == JoinType::Left { 6 } else { 0 };
+            assert_eq!(empty.iter().map(|b| b.num_rows()).sum::<usize>(), expected_rows);
         }
-        _ => Err(format!("Unsupported type in build_with_nulls: {:?}", base.data_type())),
+    }
+
+    #[test]
+    fn test_null_keys_match_only_with_null_equals_null() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, true)]));
+        let left = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![Some(2), None]))],
+        )
+        .unwrap();
+        let right = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![None, Some(2)]))],
+        )
+        .unwrap();
+        let join = |join_type, null_equals_null| {
+            HashJoinOperator::new(
+                "id".to_string(),
+                "id".to_string(),
+                join_type,
+                schema.clone(),
+                schema.clone(),
+            )
+            .unwrap()
+            .with_null_equals_null(null_equals_null)
+            .execute_join(std::slice::from_ref(&left), std::slice::from_ref(&right))
+            .unwrap()
+        };
+
+        // By default only 2 = 2 matches
+        let inner = join(JoinType::Inner, false);
+        assert_eq!(inner[0].num_rows(), 1);
+        // Left join keeps the null-key row, with no right match
+        let left_join = join(JoinType::Left, false);
+        assert_eq!(left_join[0].num_rows(), 2);
+        assert_eq!(left_join[0].column(1).unwrap().null_count(), 1);
+
+        // With the flag the two null keys match as well
+        let inner = join(JoinType::Inner, true);
+        assert_eq!(inner[0].num_rows(), 2);
+        assert_eq!(inner[0].column(1).unwrap().null_count(), 1);
+    }
+
+    #[test]
+    fn test_left_join_nulls_unmatched_timestamp_and_date_columns() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Int32, false),
+            Field::new("at", DataType::Timestamp(TimeUnit::Microsecond, None), false),
+            Field::new("day", DataType::Date32, false),
+        ]));
+        let right = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![2, 4])),
+                Arc::new(TimestampMicrosecondArray::from(vec![1_000, 2_000])),
+                Arc::new(Date32Array::from(vec![10, 20])),
+            ],
+        )
+        .unwrap();
+        let left = left_batch(vec![1, 2, 3, 4]);
+        let op = HashJoinOperator::new(
+            "id".to_string(),
+            "key".to_string(),
+            JoinType::Left,
+            left.schema().clone(),
+            schema,
+        )
+        .unwrap();
+        let out = op.execute_join(&[left], &[right]).unwrap();
+        let at = out[0].column(2).unwrap();
+        let at = at.as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
+        let at: Vec<Option<i64>> = at.iter().collect();
+        assert_eq!(at, vec![None, Some(1_000), None, Some(2_000)]);
+        let day = out[0].column(3).unwrap();
+        assert_eq!(day.data_type(), &DataType::Date32);
+        assert_eq!(day.null_count(), 2);
+    }
+
+    #[test]
+    fn test_keys_of_different_numeric_types_match() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Int64, false),
+            Field::new("label", DataType::Utf8, false),
+        ]));
+        let right = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(arrow::array::Int64Array::from(vec![2, 4, 1 << 40])),
+                Arc::new(StringArray::from(vec!["two", "four", "big"])),
+            ],
+        )
+        .unwrap();
+        let left = left_batch(vec![1, 2, 3, 4]);
+        let op = HashJoinOperator::new(
+            "id".to_string(),
+            "key".to_string(),
+            JoinType::Inner,
+            left.schema().clone(),
+            schema,
+        )
+        .unwrap();
+        let out = op.execute_join(std::slice::from_ref(&left), &[right]).unwrap();
+        let ids = out[0].i32_column("id").unwrap().values().to_vec();
+        assert_eq!(ids, vec![2, 4]);
+        // The output keeps each side's own key type
+        assert_eq!(out[0].schema().field(1).data_type(), &DataType::Int64);
+
+        let strings = Arc::new(Schema::new(vec![Field::new("key", DataType::Utf8, false)]));
+        let err = HashJoinOperator::new(
+            "id".to_string(),
+            "key".to_string(),
+            JoinType::Inner,
+            left.schema().clone(),
+            strings,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, "Join keys 'id' (Int32) and 'key' (Utf8) have incompatible types");
+    }
+
+    #[test]
+    fn test_semi_and_anti_join_keep_left_rows_once() {
+        // right keys: 2, 4, 4
+        let left = vec![left_batch(vec![1, 2, 3]), left_batch(vec![4, 5])];
+        let right = vec![right_batch()];
+        let join = |join_type, right: &[RecordBatch]| {
+            let op = HashJoinOperator::new(
+                "id".to_string(),
+                "key".to_string(),
+                join_type,
+                left[0].schema().clone(),
+                right_batch().schema().clone(),
+            )
+            .unwrap();
+            assert_eq!(op.schema(), *left[0].schema());
+            let out = op.execute_join(&left, right).unwrap();
+            let ids: Vec<i32> = out
+                .iter()
+                .flat_map(|b| {
+                    let ids = b.column(0).unwrap().as_any().downcast_ref::<Int32Array>().unwrap();
+                    ids.values().to_vec()
+                })
+                .collect();
+            ids
+        };
+        assert_eq!(join(JoinType::Semi, &right), vec![2, 4]);
+        assert_eq!(join(JoinType::Anti, &right), vec![1, 3, 5]);
+        assert!(join(JoinType::Semi, &[]).is_empty());
+        assert_eq!(join(JoinType::Anti, &[]), vec![1, 2, 3, 4, 5]);
     }
 }
@@ -0,0 +1,107 @@
+// LIMIT
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::Operator;
+
+/// Limit operator: keeps the first `limit` rows of its input, in input order
+pub struct LimitOperator {
+    limit: usize,
+    schema: SchemaRef,
+}
+
+impl LimitOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Limit";
+
+    /// Create a new Limit operator
+    pub fn new(limit: usize, input_schema: SchemaRef) -> Self {
+        Self {
+            limit,
+            schema: input_schema,
+        }
+    }
+
+    /// Maximum number of rows produced
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl Operator for LimitOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        input.slice(0, input.num_rows().min(self.limit))
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn execute_many(&self, inputs: &[RecordBatch]) -> Result<Vec<RecordBatch>, String> {
+        first_rows(inputs.to_vec(), self.limit)
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows.min(self.limit))
+    }
+}
+
+/// Keep the first `limit` rows across `batches`, slicing the last batch kept and
+/// dropping empty batches
+pub(crate) fn first_rows(
+    batches: Vec<RecordBatch>,
+    limit: usize,
+) -> Result<Vec<RecordBatch>, String> {
+    let mut out = Vec::new();
+    let mut remaining = limit;
+    for batch in batches {
+        if remaining == 0 {
+            break;
+        }
+        if batch.is_empty() {
+            continue;
+        }
+        if batch.num_rows() <= remaining {
+            remaining -= batch.num_rows();
+            out.push(batch);
+        } else {
+            out.push(batch.slice(0, remaining)?);
+            remaining = 0;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn batch(values: Vec<i32>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int32, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int32Array::from(values))]).unwrap()
+    }
+
+    #[test]
+    fn test_limit_spans_batches() {
+        let inputs = vec![batch(vec![1, 2]), batch(vec![]), batch(vec![3, 4, 5]), batch(vec![6])];
+        let op = LimitOperator::new(4, inputs[0].schema().clone());
+        let out = op.execute_many(&inputs).unwrap();
+        let values: Vec<i32> = out
+            .iter()
+            .flat_map(|b| {
+                let v = b.column(0).unwrap();
+                v.as_any().downcast_ref::<Int32Array>().unwrap().values().to_vec()
+            })
+            .collect();
+        assert_eq!(values, vec![1, 2, 3, 4]);
+
+        let none = LimitOperator::new(0, inputs[0].schema().clone());
+        assert!(none.execute_many(&inputs).unwrap().is_empty());
+    }
+}
@@ -1,20 +1,40 @@
-pub mod aggregate;
-pub mod filter;
-pub mod join;
-pub mod project;
-pub mod scan;
-pub mod sort;
-
-// Export operators for use by executor
-pub use aggregate::AggregateOperator;
-pub use filter::FilterOperator;
-pub use join::HashJoinOperator;
-pub use project::ProjectOperator;
-pub use scan::ScanOperator;
-pub use sort::SortOperator;
+/// Declare each operator's module and re-export its operator (for use by the executor),
+/// listing the operator's `NAME` in `OPERATOR_NAMES`: an operator is registered here once
+macro_rules! operators {
+    ($($module:ident::$operator:ident),* $(,)?) => {
+        $(
+            pub mod $module;
+            pub use $module::$operator;
+        )*
+
+        /// Names returned by `Operator::name` (and the join and set operators' `name`),
+        /// for tools that label or filter plan nodes by operator
+        pub const OPERATOR_NAMES: &[&str] = &[$($operator::NAME),*];
+    };
+}
+
+operators! {
+    aggregate::AggregateOperator,
+    coalesce_batches::CoalesceBatchesOperator,
+    fill_null::FillNullOperator,
+    filter::FilterOperator,
+    filter_fn::FilterFnOperator,
+    filter_project::FilterProjectOperator,
+    join::HashJoinOperator,
+    limit::LimitOperator,
+    project::ProjectOperator,
+    rename::RenameOperator,
+    row_index::RowIndexOperator,
+    scan::ScanOperator,
+    set_operation::SetOperator,
+    sort::SortOperator,
+    sort_merge_join::SortMergeJoinOperator,
+    unpivot::UnpivotOperator,
+    values::ValuesOperator,
+    window::WindowOperator,
+}
 
 use crate::execution::batch::{RecordBatch, SchemaRef};
-use std::sync::Arc;
 
 /// Trait for all execution operators in the query engine
 /// Operators process RecordBatches in a vectorized manner
@@ -34,6 +54,10 @@ pub trait Operator: Send + Sync {
     /// The schema that this operator will produce
     fn schema(&self) -> SchemaRef;
 
+    /// Short name of the operator (e.g. `"Filter"`), labelling its node in metrics and
+    /// explain output
+    fn name(&self) -> &'static str;
+
     /// Execute the operator on multiple batches (for operators that can process multiple inputs)
     /// Default implementation processes each batch individually
     /// 
@@ -45,4 +69,103 @@ pub trait Operator: Send + Sync {
     fn execute_many(&self, inputs: &[RecordBatch]) -> Result<Vec<RecordBatch>, String> {
         inputs.iter().map(|batch| self.execute(batch)).collect()
     }
+
+    /// Estimated number of rows produced from `input_rows` input rows, for planning and
+    /// progress reporting. Default: None (no estimate)
+    fn estimated_output_rows(&self, _input_rows: usize) -> Option<usize> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{col, lit_int32, ExprBuilder};
+    use crate::planner::logical_plan::{
+        Aggregation, JoinType, OrderByExpr, RowPredicate, SetOperation, WindowExpr,
+        WindowFunction,
+    };
+    use crate::types::ScalarValue;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_operator_names() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let path = std::env::temp_dir().join(format!("{}_operator_names", std::process::id()));
+        let batch = arrow::record_batch::RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1]))],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema.clone(), None)
+            .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let key = || "a".to_string();
+        let a = || vec![key()];
+        let order_by = || vec![OrderByExpr { column: "a".to_string(), ascending: true }];
+        let predicate = col("a").gt(lit_int32(0));
+        let renames = HashMap::from([("a".to_string(), "b".to_string())]);
+        let window = WindowExpr {
+            function: WindowFunction::RowNumber,
+            column: None,
+            alias: "n".to_string(),
+        };
+        let count = vec![Aggregation::count_star("n")];
+        let zero = ScalarValue::Int32(0);
+        let keep_all = RowPredicate(Arc::new(|_: &RecordBatch, _: usize| true));
+        let unary: Vec<(Box<dyn Operator>, &str)> = vec![
+            (Box::new(AggregateOperator::new(vec![], count, schema.clone()).unwrap()), "Aggregate"),
+            (Box::new(CoalesceBatchesOperator::new(2, schema.clone())), "CoalesceBatches"),
+            (Box::new(FillNullOperator::new(None, zero, schema.clone()).unwrap()), "FillNull"),
+            (Box::new(FilterOperator::new(predicate.clone(), schema.clone()).unwrap()), "Filter"),
+            (Box::new(FilterFnOperator::new(keep_all, schema.clone())), "FilterFn"),
+            (
+                Box::new(FilterProjectOperator::new(predicate, a(), schema.clone()).unwrap()),
+                "FilterProject",
+            ),
+            (Box::new(LimitOperator::new(1, schema.clone())), "Limit"),
+            (Box::new(ProjectOperator::new(a(), schema.clone()).unwrap()), "Project"),
+            (Box::new(RenameOperator::new(&renames, schema.clone()).unwrap()), "Rename"),
+            (Box::new(RowIndexOperator::new("i", schema.clone()).unwrap()), "RowIndex"),
+            (Box::new(ScanOperator::new(&path, None).unwrap()), "Scan"),
+            (Box::new(SortOperator::new(order_by(), schema.clone()).unwrap()), "Sort"),
+            (Box::new(UnpivotOperator::new(&[], &a(), schema.clone()).unwrap()), "Unpivot"),
+            (Box::new(ValuesOperator::new(schema.clone(), &[]).unwrap()), "Values"),
+            (
+                Box::new(WindowOperator::new(vec![], order_by(), vec![window], schema.clone())
+                    .unwrap()),
+                "Window",
+            ),
+        ];
+        // The scan has read the footer it needs, so the file can go before any assertion
+        std::fs::remove_file(&path).unwrap();
+        let mut names: Vec<&str> = unary
+            .iter()
+            .map(|(op, expected)| {
+                assert_eq!(op.name(), *expected);
+                op.name()
+            })
+            .collect();
+
+        let (left, right) = (schema.clone(), schema.clone());
+        let hash = HashJoinOperator::new(key(), key(), JoinType::Inner, left, right).unwrap();
+        let (left, right) = (schema.clone(), schema.clone());
+        let merge = SortMergeJoinOperator::new(key(), key(), JoinType::Inner, left, right).unwrap();
+        let set = SetOperator::new(SetOperation::Except, schema.clone(), schema).unwrap();
+        assert_eq!(hash.name(), "HashJoin");
+        assert_eq!(merge.name(), "SortMergeJoin");
+        assert_eq!(set.name(), "SetOperation");
+        names.extend([hash.name(), merge.name(), set.name()]);
+
+        // Every operator is listed in the registry, once
+        names.sort_unstable();
+        assert_eq!(names, OPERATOR_NAMES);
+    }
 }
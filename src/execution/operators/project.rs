@@ -1,19 +1,29 @@
 // Column selection/projection
 
 use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::expression::evaluate_with;
 use crate::execution::operators::Operator;
-use arrow::datatypes::{Field, Schema};
+use crate::planner::logical_plan::LogicalExpr;
+use arrow::array::ArrayRef;
+use arrow::datatypes::Schema;
 use std::sync::Arc;
 
-/// Project operator that selects a subset of columns
+/// Project operator that selects a subset of columns, or computes expressions
 /// Uses vectorized column selection for efficient projection
 pub struct ProjectOperator {
     column_names: Vec<String>,
-    column_indices: Vec<usize>,
+    /// Input column indices when every output is a plain column reference
+    column_indices: Option<Vec<usize>>,
+    /// Output expressions, evaluated per batch when column_indices is None
+    exprs: Vec<LogicalExpr>,
     schema: SchemaRef,
+    checked_arithmetic: bool,
 }
 
 impl ProjectOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Project";
+
     /// Create a new Project operator
     /// 
     /// # Arguments
@@ -40,24 +50,131 @@ impl ProjectOperator {
         }
 
         let schema = Arc::new(Schema::new(fields));
+        let exprs = column_names
+            .iter()
+            .map(|name| LogicalExpr::Column(name.clone()))
+            .collect();
 
         Ok(Self {
             column_names,
-            column_indices,
+            column_indices: Some(column_indices),
+            exprs,
             schema,
+            checked_arithmetic: false,
+        })
+    }
+
+    /// Create a Project operator that computes each expression as an output column,
+    /// named by its alias (or column name / rendered expression)
+    pub fn with_exprs(exprs: Vec<LogicalExpr>, input_schema: SchemaRef) -> Result<Self, String> {
+        let fields = exprs
+            .iter()
+            .map(|e| e.to_field(&input_schema))
+            .collect::<Result<Vec<_>, _>>()?;
+        let column_names = fields.iter().map(|f| f.name().clone()).collect();
+        Ok(Self {
+            column_names,
+            column_indices: None,
+            exprs,
+            schema: Arc::new(Schema::new(fields)),
+            checked_arithmetic: false,
         })
     }
+
+    /// Report integer overflow in the expressions' arithmetic as an error instead of wrapping
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Self {
+        self.checked_arithmetic = checked;
+        self
+    }
+
+    /// Names of the columns selected by this projection
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
 }
 
 impl Operator for ProjectOperator {
     /// Execute the project operator on a batch
     /// Uses vectorized column selection
     fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
-        // Use the batch's select_columns method which is already vectorized
-        input.select_columns(&self.column_indices)
+        if let Some(ref column_indices) = self.column_indices {
+            // Use the batch's select_columns method which is already vectorized
+            return input.select_columns(column_indices);
+        }
+        let columns: Vec<ArrayRef> = self
+            .exprs
+            .iter()
+            .map(|e| evaluate_with(e, input, self.checked_arithmetic))
+            .collect::<Result<_, _>>()?;
+        RecordBatch::try_new_with_row_count(self.schema.clone(), columns, input.num_rows())
     }
 
     fn schema(&self) -> SchemaRef {
         self.schema.clone()
     }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planner::logical_plan::BinaryOp;
+    use arrow::array::{Array, Int32Array};
+    use arrow::datatypes::{DataType, Field};
+
+    #[test]
+    fn test_project_aliased_expression() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(Int32Array::from(vec![10, 20, 30])),
+            ],
+        )
+        .unwrap();
+
+        // SELECT a, (a + b) AS total
+        let total = LogicalExpr::Alias {
+            expr: Box::new(LogicalExpr::BinaryExpr {
+                left: Box::new(LogicalExpr::Column("a".to_string())),
+                op: BinaryOp::Plus,
+                right: Box::new(LogicalExpr::Column("b".to_string())),
+            }),
+            name: "total".to_string(),
+        };
+        assert_eq!(total.to_string(), "a + b AS total");
+        let op = ProjectOperator::with_exprs(
+            vec![LogicalExpr::Column("a".to_string()), total],
+            schema,
+        )
+        .unwrap();
+        assert_eq!(op.column_names(), &["a".to_string(), "total".to_string()]);
+
+        let out = op.execute(&batch).unwrap();
+        assert_eq!(out.schema().field(1).name(), "total");
+        assert_eq!(out.schema().field(1).data_type(), &DataType::Int32);
+        let totals = out.column_by_name("total").unwrap();
+        let totals = totals.as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(totals.values().to_vec(), vec![11, 22, 33]);
+        assert_eq!(totals.null_count(), 0);
+    }
+
+    #[test]
+    fn test_estimated_output_rows() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let op = ProjectOperator::new(vec!["a".to_string()], schema).unwrap();
+        assert_eq!(op.estimated_output_rows(1000), Some(1000));
+        assert_eq!(op.estimated_output_rows(0), Some(0));
+    }
 }
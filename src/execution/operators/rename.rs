@@ -0,0 +1,118 @@
+// Column renaming
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::Operator;
+use arrow::datatypes::{Field, Schema};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Rename operator: renames columns by rebuilding the schema's fields.
+/// Column data is passed through untouched.
+pub struct RenameOperator {
+    schema: SchemaRef,
+}
+
+impl RenameOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Rename";
+
+    /// Create a new Rename operator from a map of old name -> new name.
+    /// Errors if a source column is missing or the result would have duplicate names
+    /// (a target colliding with a column that is not itself renamed, or two equal targets).
+    pub fn new(renames: &HashMap<String, String>, input_schema: SchemaRef) -> Result<Self, String> {
+        for old in renames.keys() {
+            input_schema
+                .field_with_name(old)
+                .map_err(|_| format!("Column '{}' not found", old))?;
+        }
+
+        let fields: Vec<Field> = input_schema
+            .fields()
+            .iter()
+            .map(|f| match renames.get(f.name()) {
+                Some(new) => f.as_ref().clone().with_name(new),
+                None => f.as_ref().clone(),
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        for field in &fields {
+            if !seen.insert(field.name()) {
+                return Err(format!(
+                    "Rename would produce duplicate column name '{}'",
+                    field.name()
+                ));
+            }
+        }
+
+        Ok(Self {
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+}
+
+impl Operator for RenameOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        RecordBatch::try_new(self.schema.clone(), input.columns().to_vec())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::DataType;
+
+    fn input_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+            Field::new("c", DataType::Int64, false),
+        ]))
+    }
+
+    fn renames(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_rename() {
+        let op = RenameOperator::new(&renames(&[("a", "x"), ("c", "z")]), input_schema()).unwrap();
+        let names: Vec<&str> = op.schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, vec!["x", "b", "z"]);
+        // Types and nullability are kept
+        assert_eq!(op.schema.field(2).data_type(), &DataType::Int64);
+        assert!(op.schema.field(1).is_nullable());
+    }
+
+    #[test]
+    fn test_swap_is_allowed() {
+        let op = RenameOperator::new(&renames(&[("a", "b"), ("b", "a")]), input_schema()).unwrap();
+        let names: Vec<&str> = op.schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_collisions_are_rejected() {
+        // b already exists and is not renamed
+        assert!(RenameOperator::new(&renames(&[("a", "b")]), input_schema()).is_err());
+        // Two columns renamed to the same name
+        assert!(RenameOperator::new(&renames(&[("a", "y"), ("c", "y")]), input_schema()).is_err());
+        // Unknown source column
+        assert!(RenameOperator::new(&renames(&[("missing", "y")]), input_schema()).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+// Row index column
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::Operator;
+use arrow::array::Int64Array;
+use arrow::datatypes::{DataType, Field, Schema};
+use std::sync::Arc;
+
+/// Row index operator: appends an Int64 column numbering the rows 0..n in input order.
+/// The numbering runs across all input batches, so batches must be processed together.
+pub struct RowIndexOperator {
+    schema: SchemaRef,
+}
+
+impl RowIndexOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "RowIndex";
+
+    /// Create a new RowIndex operator appending a column called `name`
+    pub fn new(name: &str, input_schema: SchemaRef) -> Result<Self, String> {
+        if input_schema.field_with_name(name).is_ok() {
+            return Err(format!("Column '{}' already exists", name));
+        }
+        let mut fields: Vec<Field> =
+            input_schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+        fields.push(Field::new(name, DataType::Int64, false));
+        Ok(Self {
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    /// Append indices `start..start + rows` to `batch`
+    fn with_indices(&self, batch: &RecordBatch, start: i64) -> Result<RecordBatch, String> {
+        let end = start + batch.num_rows() as i64;
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(Int64Array::from_iter_values(start..end)));
+        RecordBatch::try_new_with_row_count(self.schema.clone(), columns, batch.num_rows())
+    }
+}
+
+impl Operator for RowIndexOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        self.with_indices(input, 0)
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows)
+    }
+
+    fn execute_many(&self, inputs: &[RecordBatch]) -> Result<Vec<RecordBatch>, String> {
+        let mut next = 0;
+        inputs
+            .iter()
+            .map(|batch| {
+                let out = self.with_indices(batch, next)?;
+                next += batch.num_rows() as i64;
+                Ok(out)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Int32Array};
+
+    #[test]
+    fn test_indices_continue_across_batches() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = |values: Vec<i32>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))]).unwrap()
+        };
+        let op = RowIndexOperator::new("idx", schema.clone()).unwrap();
+        let out = op
+            .execute_many(&[batch(vec![5, 6, 7]), batch(vec![]), batch(vec![8, 9])])
+            .unwrap();
+        let indices: Vec<i64> = out
+            .iter()
+            .flat_map(|b| {
+                let idx = b.column_by_name("idx").unwrap();
+                idx.as_any().downcast_ref::<Int64Array>().unwrap().values().to_vec()
+            })
+            .collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+        assert!(!op.schema().field(1).is_nullable());
+
+        assert!(RowIndexOperator::new("a", schema).is_err());
+    }
+}
@@ -1,100 +1,568 @@
 // Scan Parquet files
 
 use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::expression::evaluate_predicate_with;
+use crate::execution::operators::limit::first_rows;
 use crate::execution::operators::Operator;
-use crate::storage::parquet_reader::{ParquetReader, ParquetReaderConfig};
+use crate::planner::logical_plan::{BinaryOp, LogicalExpr};
+use crate::storage::parquet_reader::{
+    flatten_schema, gunzipped_path, is_supported_type, list_parquet_files, GunzippedPath,
+    ParquetReader, ParquetReaderConfig,
+};
+use crate::storage::predicate_pushdown::{
+    metadata_may_match_statistics, prune_row_groups_with_metadata,
+};
+use crate::storage::schema_cache::SchemaCache;
+use arrow::compute::can_cast_types;
 use arrow::datatypes::Schema;
+use arrow::record_batch::RecordBatch as ArrowRecordBatch;
+use parquet::arrow::arrow_reader::ArrowReaderMetadata;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// Scan operator that reads data from Parquet files
-/// Supports column projection and can read row groups in parallel
+/// Supports column projection and can read row groups in parallel.
+/// The path may be a single file or a directory of `*.parquet` (or `*.parquet.gz`) files
+/// sharing one schema.
 pub struct ScanOperator {
-    path: PathBuf,
+    /// Files to read, in order
+    files: Vec<PathBuf>,
     projection: Option<Vec<String>>,
+    /// Pushed-down predicates: used to skip row groups, then applied to the rows read
+    /// (while decoding, when simple enough)
+    filters: Vec<LogicalExpr>,
+    /// Schema of the file as stored, whose top-level columns the reader selects
+    root_schema: SchemaRef,
+    /// Full schema of the file as read, before projection (struct columns flattened
+    /// when enabled)
+    file_schema: SchemaRef,
     schema: SchemaRef,
     config: ParquetReaderConfig,
+    /// Stop reading once this many rows (after filters) have been produced
+    limit: Option<usize>,
+    /// With an explicit schema (see `new_with_schema`), each file's own schema: columns
+    /// are selected by their position in it, then cast to the explicit schema
+    file_schemas: Option<HashMap<PathBuf, SchemaRef>>,
+    /// Each file's footer metadata and decompressed copy, by path
+    sources: HashMap<PathBuf, FileSource>,
+}
+
+/// How one file of a scan is read
+struct FileSource {
+    /// Footer metadata, parsed (or taken from the cache) when the scan was created, so
+    /// reading never parses a footer again
+    metadata: ArrowReaderMetadata,
+    /// For a gzip-wrapped file, its decompressed copy: made on first read and deleted
+    /// when the scan is dropped
+    gunzipped: Mutex<Option<GunzippedPath>>,
 }
 
 impl ScanOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Scan";
+
     /// Create a new Scan operator
     /// 
     /// # Arguments
-    /// * `path` - Path to the Parquet file to scan
+    /// * `path` - Path to the Parquet file (or directory of Parquet files) to scan
     /// * `projection` - Optional list of column names to read (for column pruning)
     /// 
     /// # Returns
     /// Result containing the ScanOperator, or an error string
     pub fn new<P: AsRef<Path>>(path: P, projection: Option<Vec<String>>) -> Result<Self, String> {
-        // Read schema first to validate the file
-        let reader = ParquetReader::from_path(&path)
-            .map_err(|e| format!("Failed to open Parquet file: {}", e))?;
-
-        let arrow_schema = reader.schema()
-            .map_err(|e| format!("Failed to read Parquet schema: {}", e))?;
-
-        // Determine column indices for projection (before we might move arrow_schema)
-        let column_indices = projection.as_ref().map(|columns| {
-            columns
-                .iter()
-                .filter_map(|name| {
-                    arrow_schema
-                        .fields()
-                        .iter()
-                        .position(|f| f.name() == name)
-                })
-                .collect::<Vec<_>>()
-        });
+        Self::new_with_cache(path, projection, &SchemaCache::new())
+    }
 
-        // If projection is specified, create a projected schema (prune the columns)
-        let schema = if let Some(ref columns) = projection {
-            let fields: Vec<_> = columns
-                .iter()
-                .map(|name| {
-                    arrow_schema
-                        .fields()
-                        .iter()
-                        .find(|f| f.name() == name)
-                        .ok_or_else(|| format!("Column '{}' not found in schema", name))
-                        .map(|f| f.as_ref().clone())
-                })
-                .collect::<Result<_, _>>()?;
-            Arc::new(Schema::new(fields))
-        } else {
-            Arc::new(arrow_schema)
-        };
+    /// Create a new Scan operator, taking file schemas from `cache` when the files are
+    /// unchanged since they were cached
+    pub fn new_with_cache<P: AsRef<Path>>(
+        path: P,
+        projection: Option<Vec<String>>,
+        cache: &SchemaCache,
+    ) -> Result<Self, String> {
+        let files = list_parquet_files(&path)
+            .map_err(|e| format!("Failed to list Parquet files: {}", e))?;
+
+        // Read schema first to validate the files
+        let arrow_schema = read_schema(&files[0], cache)?;
+        let sources = read_sources(&files, cache)?;
+        for file in &files[1..] {
+            if read_schema(file, cache)? != arrow_schema {
+                return Err(format!(
+                    "Parquet file '{}' has a different schema than '{}'",
+                    file.display(),
+                    files[0].display()
+                ));
+            }
+        }
 
-        let config = ParquetReaderConfig {
-            parallel: true,
-            column_indices,
-            batch_size: 8192,
+        let scan = Self {
+            files,
+            projection: None,
+            filters: Vec::new(),
+            root_schema: arrow_schema.clone(),
+            file_schema: arrow_schema.clone(),
+            schema: arrow_schema,
+            config: ParquetReaderConfig::default(),
+            limit: None,
+            file_schemas: None,
+            sources,
         };
+        scan.with_projection(projection)
+    }
+
+    /// Create a Scan operator that reads its files as `schema` rather than their own
+    /// schema. Every file must have each of its columns, in a type that casts to the
+    /// given one; columns are cast as they are read. The files may otherwise differ.
+    pub fn new_with_schema<P: AsRef<Path>>(
+        path: P,
+        schema: SchemaRef,
+        cache: &SchemaCache,
+    ) -> Result<Self, String> {
+        let files = list_parquet_files(&path)
+            .map_err(|e| format!("Failed to list Parquet files: {}", e))?;
+        let mut file_schemas = HashMap::new();
+        for file in &files {
+            let file_schema = read_schema(file, cache)?;
+            for field in schema.fields() {
+                let found = file_schema.field_with_name(field.name()).map_err(|_| {
+                    format!("Parquet file '{}' has no column '{}'", file.display(), field.name())
+                })?;
+                if !can_cast_types(found.data_type(), field.data_type()) {
+                    return Err(format!(
+                        "Parquet file '{}' has column '{}' as {:?}, which cannot be cast to {:?}",
+                        file.display(),
+                        field.name(),
+                        found.data_type(),
+                        field.data_type()
+                    ));
+                }
+            }
+            file_schemas.insert(file.clone(), file_schema);
+        }
+        let sources = read_sources(&files, cache)?;
 
         Ok(Self {
-            path: path.as_ref().to_path_buf(),
-            projection,
+            files,
+            projection: None,
+            filters: Vec::new(),
+            root_schema: schema.clone(),
+            file_schema: schema.clone(),
             schema,
-            config,
+            config: ParquetReaderConfig::default(),
+            limit: None,
+            file_schemas: Some(file_schemas),
+            sources,
         })
     }
 
-    /// Read all data from the Parquet file
-    /// This is the main execution method for Scan
+    /// Read only these columns (all if None), in this order
+    pub fn with_projection(mut self, projection: Option<Vec<String>>) -> Result<Self, String> {
+        // If projection is specified, create a projected schema (prune the columns)
+        self.schema = match projection {
+            Some(ref columns) => projected_schema(&self.file_schema, columns)?,
+            None => self.file_schema.clone(),
+        };
+        self.config.column_indices = projection
+            .as_ref()
+            .map(|columns| column_indices(&self.root_schema, columns));
+        self.projection = projection;
+        Ok(self)
+    }
+
+    /// Read each top-level struct column as one column per field, named `parent.field`
+    /// (e.g. `address.city`), so struct fields can be projected and filtered like
+    /// columns. Structs nested inside structs are not supported.
+    pub fn with_flatten_structs(mut self, flatten: bool) -> Result<Self, String> {
+        if !flatten {
+            return Ok(self);
+        }
+        if self.file_schemas.is_some() {
+            return Err("Struct columns cannot be flattened in a scan with an explicit schema"
+                .to_string());
+        }
+        self.file_schema = Arc::new(flatten_schema(&self.root_schema).map_err(|e| e.to_string())?);
+        self.config.flatten_structs = true;
+        let projection = self.projection.take();
+        self.with_projection(projection)
+    }
+
+    /// Attach pushed-down filter predicates to this scan.
+    /// Equality predicates are checked against column bloom filters to skip row groups;
+    /// all predicates are then applied to the rows that are read.
+    pub fn with_filters(mut self, filters: Vec<LogicalExpr>) -> Result<Self, String> {
+        for name in filters.iter().flat_map(|f| f.column_names()) {
+            if self.file_schema.field_with_name(&name).is_err() {
+                return Err(format!("Filter column '{}' not found in schema", name));
+            }
+        }
+        self.filters = filters;
+        Ok(self)
+    }
+
+    /// Set the number of rows per batch read from the files
+    pub fn with_batch_size(mut self, batch_size: usize) -> Result<Self, String> {
+        if batch_size == 0 {
+            return Err("Scan batch size must be greater than zero".to_string());
+        }
+        self.config = self.config.with_batch_size(batch_size);
+        Ok(self)
+    }
+
+    /// Drop columns of unsupported types (with a warning on stderr) when no projection is
+    /// given, instead of failing the read. Projected columns are always read as asked.
+    pub fn with_skip_unsupported_columns(self, skip: bool) -> Result<Self, String> {
+        if !skip || self.projection.is_some() {
+            return Ok(self);
+        }
+        let (supported, unsupported): (Vec<_>, Vec<_>) = self
+            .file_schema
+            .fields()
+            .iter()
+            .partition(|f| is_supported_type(f.data_type()));
+        if unsupported.is_empty() {
+            return Ok(self);
+        }
+        for field in unsupported {
+            eprintln!(
+                "warning: skipping column '{}' of unsupported type {:?}",
+                field.name(),
+                field.data_type()
+            );
+        }
+        let columns: Vec<String> = supported.iter().map(|f| f.name().clone()).collect();
+        self.with_projection(Some(columns))
+    }
+
+    /// Leave these files unread. The schema is still taken from every listed file, so a
+    /// scan whose files are all skipped returns no batches rather than failing.
+    pub fn with_skip_files(mut self, skip_files: &HashSet<PathBuf>) -> Self {
+        self.files.retain(|file| !skip_files.contains(file));
+        self
+    }
+
+    /// Files this scan will read, in order
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Predicates applied while reading
+    pub fn filters(&self) -> &[LogicalExpr] {
+        &self.filters
+    }
+
+    /// Produce only the first `limit` rows in file order. Row groups are then read one
+    /// at a time, in order, and reading stops as soon as enough rows have been produced.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Report integer overflow in the filters' arithmetic as an error instead of wrapping
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Self {
+        self.config.checked_arithmetic = checked;
+        self
+    }
+
+    /// Read all data from the Parquet file(s)
+    /// This is the main execution method for Scan.
+    /// With filters, files whose min/max statistics rule out every row are skipped unread.
     pub fn read_all(&self) -> Result<Vec<RecordBatch>, String> {
-        let reader = ParquetReader::from_path_with_config(&self.path, self.config.clone())
-            .map_err(|e| format!("Failed to create Parquet reader: {}", e))?;
-        
+        let mut batches = Vec::new();
+        let mut rows = 0;
+        for path in &self.files {
+            if self.limit.is_some_and(|limit| rows >= limit) {
+                break;
+            }
+            if !self.file_may_match(path)? {
+                continue;
+            }
+            let Some(limit) = self.limit else {
+                batches.extend(self.read_file(path, self.row_groups(path)?)?);
+                continue;
+            };
+            for row_group in self.row_groups(path)?.unwrap_or_default() {
+                let read = self.read_file(path, Some(vec![row_group]))?;
+                rows += read.iter().map(|b| b.num_rows()).sum::<usize>();
+                batches.extend(read);
+                if rows >= limit {
+                    break;
+                }
+            }
+        }
+        match self.limit {
+            Some(limit) => first_rows(batches, limit),
+            None => Ok(batches),
+        }
+    }
+
+    /// Read the Parquet file(s) one batch at a time, passing each batch to `f` as soon as
+    /// it is decoded, so only one batch is held in memory. With a limit the rows are
+    /// read as by `read_all` first.
+    pub fn read_each(
+        &self,
+        f: &mut dyn FnMut(RecordBatch) -> Result<(), String>,
+    ) -> Result<(), String> {
+        if self.limit.is_some() {
+            return self.read_all()?.into_iter().try_for_each(f);
+        }
+        for path in &self.files {
+            if self.file_may_match(path)? {
+                self.stream_file(path, f)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// False if the filters' min/max statistics rule out every row of `path`
+    fn file_may_match(&self, path: &Path) -> Result<bool, String> {
+        if self.filters.is_empty() || !self.can_prune(path) {
+            return Ok(true);
+        }
+        Ok(metadata_may_match_statistics(
+            self.sources[path].metadata.metadata(),
+            &self.filters,
+        ))
+    }
+
+    /// Row groups of `path` to read: those that survive bloom filter pruning when there
+    /// are filters, otherwise all (listed explicitly only when a limit is set)
+    fn row_groups(&self, path: &Path) -> Result<Option<Vec<usize>>, String> {
+        let metadata = self.sources[path].metadata.metadata();
+        if !self.filters.is_empty() && self.can_prune(path) {
+            let file = self.gunzipped(path)?;
+            let row_groups = prune_row_groups_with_metadata(file.path(), metadata, &self.filters)
+                .map_err(|e| format!("Failed to read Parquet bloom filters: {}", e))?;
+            return Ok(Some(row_groups));
+        }
+        if self.limit.is_none() {
+            return Ok(None);
+        }
+        Ok(Some((0..metadata.num_row_groups()).collect()))
+    }
+
+    /// Whether the filters can be checked against the statistics and bloom filters of
+    /// `path`: unless an explicit schema casts one of the filter columns in that file
+    fn can_prune(&self, path: &Path) -> bool {
+        let Some(file_schema) = self.file_schemas.as_ref().map(|schemas| &schemas[path]) else {
+            return true;
+        };
+        let data_type = |schema: &Schema, name: &str| {
+            schema.field_with_name(name).ok().map(|f| f.data_type().clone())
+        };
+        self.filters
+            .iter()
+            .flat_map(|f| f.column_names())
+            .all(|name| data_type(file_schema, &name) == data_type(&self.file_schema, &name))
+    }
+
+    /// Read row groups of one Parquet file (all if None), applying filters and projection
+    fn read_file(
+        &self,
+        path: &Path,
+        row_groups: Option<Vec<usize>>,
+    ) -> Result<Vec<RecordBatch>, String> {
+        let reader = self.file_reader(path, row_groups)?;
         let arrow_batches = reader.read_all()
             .map_err(|e| format!("Failed to read Parquet data: {}", e))?;
+        let mut batches = Vec::new();
+        for batch in arrow_batches {
+            batches.extend(self.finish_batch(batch)?);
+        }
+        Ok(batches)
+    }
 
-        // Convert Arrow RecordBatches to our RecordBatch type
-        let batches: Vec<RecordBatch> = arrow_batches
-            .into_iter()
-            .map(RecordBatch::from_arrow)
-            .collect();
+    /// As `read_file` for all row groups to read, but passing each batch to `f` as soon
+    /// as it is decoded
+    fn stream_file(
+        &self,
+        path: &Path,
+        f: &mut dyn FnMut(RecordBatch) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let reader = self.file_reader(path, self.row_groups(path)?)?;
+        let batches = reader
+            .batch_iter()
+            .map_err(|e| format!("Failed to read Parquet data: {}", e))?;
+        for batch in batches {
+            let batch = batch.map_err(|e| format!("Failed to read Parquet data: {}", e))?;
+            if let Some(batch) = self.finish_batch(batch)? {
+                f(batch)?;
+            }
+        }
+        Ok(())
+    }
 
-        Ok(batches)
+    /// Reader over row groups of one Parquet file (all if None), selecting the columns
+    /// to read and pushing the filters down where the file's types allow
+    fn file_reader(
+        &self,
+        path: &Path,
+        row_groups: Option<Vec<usize>>,
+    ) -> Result<ParquetReader, String> {
+        let mut config = self.config.clone();
+        config.row_groups = row_groups;
+        let cast_from = self.file_schemas.as_ref().map(|schemas| &schemas[path]);
+        if let Some(file_schema) = cast_from {
+            // Select columns by their position in this file, which can differ per file.
+            // Filters run after the cast, on the explicit schema's types.
+            let mut columns = self.output_columns();
+            for name in self.filters.iter().flat_map(|f| f.column_names()) {
+                if !columns.contains(&name) {
+                    columns.push(name);
+                }
+            }
+            config.column_indices = Some(column_indices(file_schema, &columns));
+        } else if !self.filters.is_empty() {
+            // Filter columns must be read even when they are not projected
+            if let Some(ref projection) = self.projection {
+                let mut columns = projection.clone();
+                for name in self.filters.iter().flat_map(|f| f.column_names()) {
+                    if !columns.contains(&name) {
+                        columns.push(name);
+                    }
+                }
+                config.column_indices = Some(column_indices(&self.root_schema, &columns));
+            }
+            config.predicate = self.predicate();
+        }
+
+        let reader = ParquetReader::from_gunzipped(self.gunzipped(path)?, config);
+        Ok(reader.with_metadata(self.sources[path].metadata.clone()))
     }
+
+    /// The file to open for `path`, decompressing it on first use if gzip-wrapped
+    fn gunzipped(&self, path: &Path) -> Result<GunzippedPath, String> {
+        let mut gunzipped = self.sources[path].gunzipped.lock().unwrap();
+        if let Some(ref file) = *gunzipped {
+            return Ok(file.clone());
+        }
+        let file =
+            gunzipped_path(path).map_err(|e| format!("Failed to create Parquet reader: {}", e))?;
+        *gunzipped = Some(file.clone());
+        Ok(file)
+    }
+
+    /// The filters combined with AND
+    fn predicate(&self) -> Option<LogicalExpr> {
+        self.filters.iter().cloned().reduce(|left, right| {
+            LogicalExpr::BinaryExpr {
+                left: Box::new(left),
+                op: BinaryOp::And,
+                right: Box::new(right),
+            }
+        })
+    }
+
+    /// Convert a batch read from a file to the scan's output: cast to the explicit schema
+    /// and filtered (with an explicit schema), then in the requested column order.
+    /// None if filtering leaves no rows.
+    fn finish_batch(&self, batch: ArrowRecordBatch) -> Result<Option<RecordBatch>, String> {
+        let mut batch = RecordBatch::from_arrow(batch);
+        if self.file_schemas.is_some() {
+            batch = self.cast_batch(&batch)?;
+            if let Some(predicate) = self.predicate() {
+                let checked = self.config.checked_arithmetic;
+                batch = batch.filter(&evaluate_predicate_with(&predicate, &batch, checked)?)?;
+                if batch.num_rows() == 0 {
+                    return Ok(None);
+                }
+            }
+        }
+
+        // Restore the requested column order (the reader returns file order)
+        if self.projection.is_some() || self.file_schemas.is_some() {
+            let columns = self.output_columns();
+            let names: Vec<&str> = columns.iter().map(|s| s.as_str()).collect();
+            batch = batch.select_columns_by_name(&names)?;
+        }
+        Ok(Some(batch))
+    }
+
+    /// Names of the columns this scan produces, in order
+    fn output_columns(&self) -> Vec<String> {
+        match self.projection {
+            Some(ref projection) => projection.clone(),
+            None => self.file_schema.fields().iter().map(|f| f.name().clone()).collect(),
+        }
+    }
+
+    /// Cast the columns of a batch read from a file to the explicit schema's types
+    fn cast_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, String> {
+        let mut fields = Vec::with_capacity(batch.num_columns());
+        let mut columns = Vec::with_capacity(batch.num_columns());
+        for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+            let target = self
+                .file_schema
+                .field_with_name(field.name())
+                .map_err(|_| format!("Column '{}' not found in schema", field.name()))?;
+            let cast = arrow::compute::cast(column, target.data_type())
+                .map_err(|e| format!("Failed to cast column '{}': {}", field.name(), e))?;
+            fields.push(target.clone());
+            columns.push(cast);
+        }
+        RecordBatch::try_new_with_row_count(
+            Arc::new(Schema::new(fields)),
+            columns,
+            batch.num_rows(),
+        )
+    }
+}
+
+/// Read the Arrow schema of one Parquet file
+fn read_schema(path: &Path, cache: &SchemaCache) -> Result<SchemaRef, String> {
+    cache
+        .get(path)
+        .map_err(|e| format!("Failed to read Parquet schema: {}", e))
+}
+
+/// Each file's source, with its footer metadata from `cache`
+fn read_sources(
+    files: &[PathBuf],
+    cache: &SchemaCache,
+) -> Result<HashMap<PathBuf, FileSource>, String> {
+    files
+        .iter()
+        .map(|file| {
+            let metadata = cache
+                .metadata(file)
+                .map_err(|e| format!("Failed to read Parquet metadata: {}", e))?;
+            let source = FileSource {
+                metadata,
+                gunzipped: Mutex::default(),
+            };
+            Ok((file.clone(), source))
+        })
+        .collect()
+}
+
+/// Schema of `columns`, in the given order, erroring on names not in `schema`
+fn projected_schema(schema: &Schema, columns: &[String]) -> Result<SchemaRef, String> {
+    let fields: Vec<_> = columns
+        .iter()
+        .map(|name| {
+            schema
+                .field_with_name(name)
+                .cloned()
+                .map_err(|_| format!("Column '{}' not found in schema", name))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Arc::new(Schema::new(fields)))
+}
+
+/// Map column names to the indices of the top-level file columns holding them, skipping
+/// unknown names. A flattened struct field `parent.field` maps to its struct column.
+fn column_indices(root_schema: &Schema, columns: &[String]) -> Vec<usize> {
+    let position = |name: &str| root_schema.fields().iter().position(|f| f.name() == name);
+    let mut indices: Vec<usize> = columns
+        .iter()
+        .filter_map(|name| {
+            position(name).or_else(|| name.split_once('.').and_then(|(parent, _)| position(parent)))
+        })
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
 }
 
 impl Operator for ScanOperator {
@@ -115,4 +583,8 @@ impl Operator for ScanOperator {
     fn schema(&self) -> SchemaRef {
         self.schema.clone()
     }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
 }
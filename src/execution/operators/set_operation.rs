@@ -0,0 +1,221 @@
+// Set operations (INTERSECT, EXCEPT)
+
+use crate::execution::batch::{RecordBatch, Schema, SchemaMismatch, SchemaRef};
+use crate::planner::logical_plan::SetOperation;
+use arrow::array::{ArrayRef, UInt32Array};
+use arrow::row::{RowConverter, Rows, SortField};
+use arrow_select::take::take;
+use std::collections::HashSet;
+
+/// Set operator: INTERSECT (left rows also in right) or EXCEPT (left rows not in right).
+/// Uses set semantics like SQL's INTERSECT / EXCEPT without ALL: output rows are distinct,
+/// kept in order of first appearance on the left. Rows are compared on all columns, with
+/// nulls equal to each other. Output column names come from the left input.
+pub struct SetOperator {
+    op: SetOperation,
+    schema: SchemaRef,
+}
+
+impl SetOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "SetOperation";
+
+    /// Create a new Set operator. Both inputs must have the same number of columns
+    /// with the same data types, in order.
+    pub fn new(
+        op: SetOperation,
+        left_schema: SchemaRef,
+        right_schema: SchemaRef,
+    ) -> Result<Self, String> {
+        if left_schema.fields().len() != right_schema.fields().len() {
+            return Err(format!(
+                "{:?} inputs have {} and {} columns",
+                op,
+                left_schema.fields().len(),
+                right_schema.fields().len()
+            ));
+        }
+        // Columns match by position, so compare the right side under the left's names;
+        // nullability may differ
+        let right_as_left = Schema::new(
+            left_schema
+                .fields()
+                .iter()
+                .zip(right_schema.fields().iter())
+                .map(|(l, r)| r.as_ref().clone().with_name(l.name()))
+                .collect::<Vec<_>>(),
+        );
+        let mismatch = SchemaMismatch::between(&left_schema, &right_as_left);
+        if !mismatch.types_match() {
+            return Err(format!("{:?} inputs are incompatible: {}", op, mismatch));
+        }
+        Ok(Self {
+            op,
+            schema: left_schema,
+        })
+    }
+
+    /// Get the output schema (the left input's schema)
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Name of this operator, as `Operator::name`
+    pub fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Execute the set operation over all batches of both inputs
+    pub fn execute_set(
+        &self,
+        left_batches: &[RecordBatch],
+        right_batches: &[RecordBatch],
+    ) -> Result<Vec<RecordBatch>, String> {
+        if left_batches.is_empty() {
+            return Ok(Vec::new());
+        }
+        let left = RecordBatch::concat(left_batches)?;
+
+        let converter = RowConverter::new(
+            self.schema
+                .fields()
+                .iter()
+                .map(|f| SortField::new(f.data_type().clone()))
+                .collect(),
+        )
+        .map_err(|e| format!("Failed to create row converter: {}", e))?;
+        let left_rows = converter
+            .convert_columns(left.columns())
+            .map_err(|e| format!("Failed to convert left rows: {}", e))?;
+
+        let right_set: HashSet<Vec<u8>> = if right_batches.is_empty() {
+            HashSet::new()
+        } else {
+            let right = RecordBatch::concat(right_batches)?;
+            let right_rows = converter
+                .convert_columns(right.columns())
+                .map_err(|e| format!("Failed to convert right rows: {}", e))?;
+            row_bytes(&right_rows).collect()
+        };
+
+        let keep_matches = matches!(self.op, SetOperation::Intersect);
+        let mut seen = HashSet::new();
+        let indices: Vec<u32> = row_bytes(&left_rows)
+            .enumerate()
+            .filter(|(_, row)| right_set.contains(row) == keep_matches)
+            .filter(|(_, row)| seen.insert(row.clone()))
+            .map(|(i, _)| i as u32)
+            .collect();
+        if indices.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let indices = UInt32Array::from(indices);
+        let columns: Vec<ArrayRef> = left
+            .columns()
+            .iter()
+            .map(|col| take(col.as_ref(), &indices, None).map_err(|e| format!("Take failed: {}", e)))
+            .collect::<Result<_, _>>()?;
+        Ok(vec![RecordBatch::try_new(self.schema.clone(), columns)?])
+    }
+}
+
+/// Owned byte encoding of each row, usable as a hash key
+fn row_bytes(rows: &Rows) -> impl Iterator<Item = Vec<u8>> + '_ {
+    rows.iter().map(|row| row.as_ref().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn batch(ids: Vec<Option<i32>>, names: Vec<&str>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn rows(batches: &[RecordBatch]) -> Vec<(Option<i32>, String)> {
+        batches
+            .iter()
+            .flat_map(|b| {
+                let ids = b.column(0).unwrap();
+                let ids = ids.as_any().downcast_ref::<Int32Array>().unwrap();
+                let names = b.column(1).unwrap();
+                let names = names.as_any().downcast_ref::<StringArray>().unwrap();
+                (0..b.num_rows())
+                    .map(|i| (ids.is_valid(i).then(|| ids.value(i)), names.value(i).to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn run(op: SetOperation) -> Vec<(Option<i32>, String)> {
+        // Left has duplicates of (1, a) and (None, n); right has (1, a) twice too
+        let left = vec![
+            batch(vec![Some(1), Some(2), Some(1)], vec!["a", "b", "a"]),
+            batch(vec![None, Some(3), None], vec!["n", "c", "n"]),
+        ];
+        let right = vec![batch(
+            vec![Some(1), None, Some(3), Some(1)],
+            vec!["a", "n", "x", "a"],
+        )];
+        let set_op = SetOperator::new(op, left[0].schema().clone(), right[0].schema().clone())
+            .unwrap();
+        rows(&set_op.execute_set(&left, &right).unwrap())
+    }
+
+    #[test]
+    fn test_intersect_is_distinct() {
+        assert_eq!(
+            run(SetOperation::Intersect),
+            vec![(Some(1), "a".to_string()), (None, "n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_except_is_distinct() {
+        assert_eq!(
+            run(SetOperation::Except),
+            vec![(Some(2), "b".to_string()), (Some(3), "c".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_incompatible_schemas() {
+        let left = batch(vec![Some(1)], vec!["a"]);
+        let other = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        let err = SetOperator::new(SetOperation::Except, left.schema().clone(), other)
+            .err()
+            .unwrap();
+        assert!(err.contains("'id' is Int32 vs Int64"), "{}", err);
+        let narrow = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, true)]));
+        assert!(SetOperator::new(SetOperation::Intersect, left.schema().clone(), narrow).is_err());
+    }
+
+    #[test]
+    fn test_names_and_nullability_may_differ() {
+        let left = batch(vec![Some(1)], vec!["a"]);
+        let right = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Int32, false),
+            Field::new("label", DataType::Utf8, true),
+        ]));
+        let op = SetOperator::new(SetOperation::Intersect, left.schema().clone(), right).unwrap();
+        assert_eq!(op.schema(), left.schema().clone());
+    }
+}
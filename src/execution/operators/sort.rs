@@ -1,6 +1,6 @@
 // ORDER BY sorting
 
-use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::batch::{concat_batches, RecordBatch, SchemaRef};
 use crate::execution::operators::Operator;
 use crate::planner::logical_plan::OrderByExpr;
 use arrow::array::ArrayRef;
@@ -8,13 +8,19 @@ use arrow_ord::sort::{lexsort_to_indices, SortColumn, SortOptions};
 use arrow_select::take::take;
 
 /// Sort operator for ORDER BY
-/// Uses arrow_ord::lexsort for lexicographic multi-column sort
+/// Uses arrow_ord::lexsort for lexicographic multi-column sort.
+/// Nulls come first; Float64 NaN sorts after +inf (last ascending, first descending).
 pub struct SortOperator {
     order_by: Vec<OrderByExpr>,
     schema: SchemaRef,
+    /// Keep only this many leading rows of the sorted output
+    limit: Option<usize>,
 }
 
 impl SortOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Sort";
+
     /// Create a new Sort operator
     pub fn new(order_by: Vec<OrderByExpr>, input_schema: SchemaRef) -> Result<Self, String> {
         // Validate that all order_by columns exist
@@ -28,16 +34,27 @@ impl SortOperator {
         Ok(Self {
             order_by,
             schema: input_schema,
+            limit: None,
         })
     }
 
+    /// Output only the first `limit` sorted rows. Only that many sort indices are
+    /// computed (a partial sort) and only those rows are copied.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
     /// Sort a single batch
     fn sort_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, String> {
         if batch.num_rows() == 0 {
             return Ok(batch.clone());
         }
         if self.order_by.is_empty() {
-            return Ok(batch.clone());
+            return match self.limit {
+                Some(limit) => batch.slice(0, limit.min(batch.num_rows())),
+                None => Ok(batch.clone()),
+            };
         }
 
         let sort_columns: Vec<SortColumn> = self
@@ -47,7 +64,7 @@ impl SortOperator {
                 let col = batch
                     .column_by_name(&e.column)
                     .ok_or_else(|| format!("Column '{}' not found", e.column))
-                    .map(|c| c.clone())?;
+                    .cloned()?;
                 Ok(SortColumn {
                     values: col,
                     options: Some(SortOptions {
@@ -58,7 +75,7 @@ impl SortOperator {
             })
             .collect::<Result<Vec<_>, String>>()?;
 
-        let indices = lexsort_to_indices(&sort_columns, None)
+        let indices = lexsort_to_indices(&sort_columns, self.limit)
             .map_err(|e| format!("Sort failed: {}", e))?;
 
         // Apply take to each column in the batch
@@ -81,13 +98,140 @@ impl Operator for SortOperator {
         self.schema.clone()
     }
 
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(self.limit.map_or(input_rows, |limit| limit.min(input_rows)))
+    }
+
     fn execute_many(&self, inputs: &[RecordBatch]) -> Result<Vec<RecordBatch>, String> {
-        if inputs.is_empty() {
-            return Ok(Vec::new());
-        }
         // Concat all batches then sort (for correct global ORDER BY)
-        let combined = RecordBatch::concat(inputs)?;
+        let combined = concat_batches(inputs, &self.schema)?;
         let sorted = self.sort_batch(&combined)?;
         Ok(if sorted.is_empty() { vec![] } else { vec![sorted] })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Float64Array, Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn order(column: &str, ascending: bool) -> OrderByExpr {
+        OrderByExpr {
+            column: column.to_string(),
+            ascending,
+        }
+    }
+
+    #[test]
+    fn test_mixed_direction_sort() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Utf8, false),
+            Field::new("b", DataType::Int32, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["y", "x", "y", "x", "x", "y"])),
+                Arc::new(Int32Array::from(vec![Some(1), Some(2), Some(3), Some(9), None, Some(2)])),
+            ],
+        )
+        .unwrap();
+
+        // ORDER BY a ASC, b DESC: ties on a are broken by b, largest first (nulls first)
+        let op = SortOperator::new(vec![order("a", true), order("b", false)], schema).unwrap();
+        let out = op
+            .execute_many(&[batch.slice(0, 3).unwrap(), batch.slice(3, 3).unwrap()])
+            .unwrap();
+        assert_eq!(out.len(), 1);
+
+        let a = out[0].column_by_name("a").unwrap();
+        let a = a.as_any().downcast_ref::<StringArray>().unwrap();
+        let b = out[0].column_by_name("b").unwrap();
+        let b = b.as_any().downcast_ref::<Int32Array>().unwrap();
+        let rows: Vec<(&str, Option<i32>)> = (0..out[0].num_rows())
+            .map(|i| (a.value(i), b.is_valid(i).then(|| b.value(i))))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("x", None),
+                ("x", Some(9)),
+                ("x", Some(2)),
+                ("y", Some(3)),
+                ("y", Some(2)),
+                ("y", Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nan_and_infinity_order() {
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Float64, true)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Float64Array::from(vec![
+                Some(f64::NAN),
+                Some(1.0),
+                None,
+                Some(f64::INFINITY),
+                Some(f64::NEG_INFINITY),
+            ]))],
+        )
+        .unwrap();
+        let sorted = |ascending| {
+            let op = SortOperator::new(vec![order("x", ascending)], schema.clone()).unwrap();
+            let out = op.execute(&batch).unwrap();
+            let x = out.column(0).unwrap().as_any().downcast_ref::<Float64Array>().unwrap();
+            (0..x.len())
+                .map(|i| x.is_valid(i).then(|| x.value(i)).map(|v| format!("{}", v)))
+                .collect::<Vec<_>>()
+        };
+        let text = |values: &[&str]| -> Vec<Option<String>> {
+            values
+                .iter()
+                .map(|v| (*v != "null").then(|| v.to_string()))
+                .collect()
+        };
+        assert_eq!(sorted(true), text(&["null", "-inf", "1", "inf", "NaN"]));
+        assert_eq!(sorted(false), text(&["null", "NaN", "inf", "1", "-inf"]));
+    }
+
+    #[test]
+    fn test_with_limit_keeps_smallest_rows() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("x", DataType::Int32, false),
+            Field::new("label", DataType::Utf8, false),
+        ]));
+        let values = vec![42, 7, 19, 3, 88, 3, 61, 25, 14, 50, 9, 33];
+        let labels: Vec<String> = values.iter().map(|v| format!("v{}", v)).collect();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(values.clone())),
+                Arc::new(StringArray::from(labels)),
+            ],
+        )
+        .unwrap();
+        let op = SortOperator::new(vec![order("x", true)], schema.clone()).unwrap().with_limit(5);
+        let out = op
+            .execute_many(&[batch.slice(0, 7).unwrap(), batch.slice(7, 5).unwrap()])
+            .unwrap();
+        assert_eq!(out.len(), 1);
+        let x = out[0].column(0).unwrap().as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(x.values().to_vec(), vec![3, 3, 7, 9, 14]);
+        let label = out[0].column(1).unwrap();
+        let label = label.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(label.value(4), "v14");
+        assert_eq!(op.estimated_output_rows(100), Some(5));
+
+        // A limit past the input keeps every row
+        let op = SortOperator::new(vec![order("x", true)], schema).unwrap().with_limit(50);
+        assert_eq!(op.execute(&batch).unwrap().num_rows(), values.len());
+    }
+}
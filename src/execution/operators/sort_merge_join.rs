@@ -0,0 +1,357 @@
+// Sort-merge joins (inner, left, semi and anti)
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::join::{
+    build_join_output, cast_join_key, join_key_type, join_output_schema, left_only_result,
+};
+use crate::planner::logical_plan::JoinType;
+use arrow::datatypes::DataType;
+use arrow::row::{RowConverter, Rows, SortField};
+use std::cmp::Ordering;
+
+/// Sort-merge join: sort both sides by the join key, then merge them in a single pass.
+/// Supports Inner, Left, Semi and Anti join and produces the same rows, in the same order, as
+/// `HashJoinOperator` (left row order, then right row order for duplicate keys).
+/// Useful when inputs are already sorted on the key or the build side is too large
+/// to hash comfortably.
+pub struct SortMergeJoinOperator {
+    left_key: String,
+    right_key: String,
+    join_type: JoinType,
+    /// Type both keys are cast to before sorting, as for `HashJoinOperator`
+    key_type: DataType,
+    /// Let null keys match each other (SQL equality never matches nulls)
+    null_equals_null: bool,
+    /// Output schema: left fields + right fields (left only for Semi and Anti)
+    schema: SchemaRef,
+}
+
+impl SortMergeJoinOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "SortMergeJoin";
+
+    /// Create a new SortMergeJoin operator. left_schema and right_schema are used to build output schema.
+    /// Keys of different numeric, string or date/time types are compared as their common
+    /// type (see `join_key_type`); other differing key types are an error.
+    pub fn new(
+        left_key: String,
+        right_key: String,
+        join_type: JoinType,
+        left_schema: SchemaRef,
+        right_schema: SchemaRef,
+    ) -> Result<Self, String> {
+        let key_type = join_key_type(&left_key, &left_schema, &right_key, &right_schema)?;
+        let schema = join_output_schema(&left_schema, &right_schema, join_type);
+        Ok(Self {
+            left_key,
+            right_key,
+            join_type,
+            key_type,
+            null_equals_null: false,
+            schema,
+        })
+    }
+
+    /// Match null keys to each other. By default, as in SQL, a null key matches nothing
+    /// (a Left join still keeps the left row, null-padded).
+    pub fn with_null_equals_null(mut self, null_equals_null: bool) -> Self {
+        self.null_equals_null = null_equals_null;
+        self
+    }
+
+    /// Get the output schema of this join (left fields followed by right fields, or just
+    /// the left fields for Semi and Anti joins)
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Name of this operator, as `Operator::name`
+    pub fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Execute the join. Both sides are concat'd to single batches, sorted by key, then merged.
+    pub fn execute_join(
+        &self,
+        left_batches: &[RecordBatch],
+        right_batches: &[RecordBatch],
+    ) -> Result<Vec<RecordBatch>, String> {
+        let left = if left_batches.is_empty() {
+            return Ok(Vec::new());
+        } else if left_batches.len() == 1 {
+            left_batches[0].clone()
+        } else {
+            RecordBatch::concat(left_batches)?
+        };
+
+        let right = if right_batches.is_empty() {
+            if matches!(self.join_type, JoinType::Left | JoinType::Anti) {
+                return left_only_result(&self.schema, &left);
+            }
+            return Ok(Vec::new());
+        } else if right_batches.len() == 1 {
+            right_batches[0].clone()
+        } else {
+            RecordBatch::concat(right_batches)?
+        };
+
+        let left_col = left
+            .column_by_name(&self.left_key)
+            .ok_or_else(|| format!("Left key '{}' not found", self.left_key))?;
+        let left_col = &cast_join_key(left_col, &self.key_type)?;
+        let right_col = right
+            .column_by_name(&self.right_key)
+            .ok_or_else(|| format!("Right key '{}' not found", self.right_key))?;
+        let right_col = &cast_join_key(right_col, &self.key_type)?;
+
+        // Row format gives byte-comparable keys that order consistently across both sides
+        let converter = RowConverter::new(vec![SortField::new(self.key_type.clone())])
+            .map_err(|e| format!("Failed to create row converter: {}", e))?;
+        let left_rows = converter
+            .convert_columns(std::slice::from_ref(left_col))
+            .map_err(|e| format!("Failed to convert left keys: {}", e))?;
+        let right_rows = converter
+            .convert_columns(std::slice::from_ref(right_col))
+            .map_err(|e| format!("Failed to convert right keys: {}", e))?;
+
+        let left_order = sorted_indices(&left_rows);
+        let right_order = sorted_indices(&right_rows);
+
+        // Merge: pairs of (left row, right row or None for unmatched left rows)
+        let mut pairs: Vec<(usize, Option<usize>)> = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < left_order.len() {
+            let left_key = left_rows.row(left_order[i]);
+            let left_end = run_end(&left_rows, &left_order, i);
+
+            while j < right_order.len() && right_rows.row(right_order[j]) < left_key {
+                j += 1;
+            }
+
+            // Null keys sort together, so a null run only matches when nulls are equal
+            let can_match = self.null_equals_null || left_col.is_valid(left_order[i]);
+            if can_match && j < right_order.len() && right_rows.row(right_order[j]) == left_key {
+                let right_end = run_end(&right_rows, &right_order, j);
+                match self.join_type {
+                    JoinType::Inner | JoinType::Left => {
+                        for &l in &left_order[i..left_end] {
+                            for &r in &right_order[j..right_end] {
+                                pairs.push((l, Some(r)));
+                            }
+                        }
+                    }
+                    JoinType::Semi => {
+                        pairs.extend(left_order[i..left_end].iter().map(|&l| (l, None)))
+                    }
+                    JoinType::Anti => {}
+                }
+                j = right_end;
+            } else if matches!(self.join_type, JoinType::Left | JoinType::Anti) {
+                pairs.extend(left_order[i..left_end].iter().map(|&l| (l, None)));
+            }
+            i = left_end;
+        }
+
+        if pairs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Restore left row order; the stable sort keeps right matches in right row order
+        pairs.sort_by_key(|&(l, _)| l);
+        let left_indices: Vec<u32> = pairs.iter().map(|&(l, _)| l as u32).collect();
+        let right_indices: Vec<Option<usize>> = pairs.iter().map(|&(_, r)| r).collect();
+
+        let out = build_join_output(&self.schema, &left, &right, &left_indices, &right_indices)?;
+        Ok(vec![out])
+    }
+}
+
+/// Row indices sorted by key; the stable sort keeps equal keys in row order
+fn sorted_indices(rows: &Rows) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..rows.num_rows()).collect();
+    indices.sort_by(|&a, &b| rows.row(a).cmp(&rows.row(b)));
+    indices
+}
+
+/// End (exclusive) of the run of equal keys starting at `start` in `order`
+fn run_end(rows: &Rows, order: &[usize], start: usize) -> usize {
+    let key = rows.row(order[start]);
+    let mut end = start + 1;
+    while end < order.len() && rows.row(order[end]).cmp(&key) == Ordering::Equal {
+        end += 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::operators::HashJoinOperator;
+    use arrow::array::{ArrayRef, Int32Array, Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn left_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int32Array::from(vec![Some(3), Some(1), Some(2), Some(1), None, Some(5)])),
+            Arc::new(StringArray::from(vec!["c", "a1", "b", "a2", "null", "e"])),
+        ];
+        RecordBatch::try_new(schema, columns).unwrap()
+    }
+
+    fn right_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("user_id", DataType::Int32, true),
+            Field::new("amount", DataType::Int32, false),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int32Array::from(vec![Some(1), Some(2), Some(1), Some(4), None])),
+            Arc::new(Int32Array::from(vec![10, 20, 30, 40, 50])),
+        ];
+        RecordBatch::try_new(schema, columns).unwrap()
+    }
+
+    fn compare_with_hash_join(join_type: JoinType, null_equals_null: bool) {
+        let left = left_batch();
+        let right = right_batch();
+        let args = || {
+            (
+                "id".to_string(),
+                "user_id".to_string(),
+                join_type,
+                left.schema().clone(),
+                right.schema().clone(),
+            )
+        };
+        let (lk, rk, jt, ls, rs) = args();
+        let hash = HashJoinOperator::new(lk, rk, jt, ls, rs)
+            .unwrap()
+            .with_null_equals_null(null_equals_null);
+        let (lk, rk, jt, ls, rs) = args();
+        let merge = SortMergeJoinOperator::new(lk, rk, jt, ls, rs)
+            .unwrap()
+            .with_null_equals_null(null_equals_null);
+
+        // Split the left side to exercise the concat path as well
+        let left_parts = vec![left.slice(0, 3).unwrap(), left.slice(3, 3).unwrap()];
+        let expected = hash.execute_join(&left_parts, std::slice::from_ref(&right)).unwrap();
+        let actual = merge.execute_join(&left_parts, &[right]).unwrap();
+
+        assert_eq!(expected.len(), actual.len());
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert_eq!(e.to_arrow().unwrap(), a.to_arrow().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_inner_join_matches_hash_join() {
+        compare_with_hash_join(JoinType::Inner, false);
+        compare_with_hash_join(JoinType::Inner, true);
+    }
+
+    #[test]
+    fn test_left_join_matches_hash_join() {
+        compare_with_hash_join(JoinType::Left, false);
+        compare_with_hash_join(JoinType::Left, true);
+    }
+
+    #[test]
+    fn test_semi_and_anti_join_match_hash_join() {
+        for join_type in [JoinType::Semi, JoinType::Anti] {
+            compare_with_hash_join(join_type, false);
+            compare_with_hash_join(join_type, true);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_keys_produce_cross_product() {
+        let left = left_batch();
+        let right = right_batch();
+        let merge = SortMergeJoinOperator::new(
+            "id".to_string(),
+            "user_id".to_string(),
+            JoinType::Inner,
+            left.schema().clone(),
+            right.schema().clone(),
+        )
+        .unwrap();
+        let out = merge.execute_join(&[left], &[right]).unwrap();
+        let names = out[0]
+            .column_by_name("name")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .clone();
+        let amounts = out[0]
+            .column_by_name("amount")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap()
+            .clone();
+        // Two left rows with id 1, two right rows with user_id 1 -> four rows
+        let id_one: Vec<(&str, i32)> = (0..out[0].num_rows())
+            .filter(|&i| names.value(i).starts_with('a'))
+            .map(|i| (names.value(i), amounts.value(i)))
+            .collect();
+        assert_eq!(id_one, vec![("a1", 10), ("a1", 30), ("a2", 10), ("a2", 30)]);
+    }
+
+    #[test]
+    fn test_keys_of_different_numeric_types_match() {
+        let left = left_batch();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("user_id", DataType::Int64, true),
+            Field::new("amount", DataType::Int32, false),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![2, 1 << 40, 1])),
+            Arc::new(Int32Array::from(vec![20, 99, 10])),
+        ];
+        let right = RecordBatch::try_new(schema.clone(), columns).unwrap();
+        let args = || {
+            (
+                "id".to_string(),
+                "user_id".to_string(),
+                JoinType::Inner,
+                left.schema().clone(),
+                schema.clone(),
+            )
+        };
+        let (lk, rk, jt, ls, rs) = args();
+        let hash = HashJoinOperator::new(lk, rk, jt, ls, rs).unwrap();
+        let (lk, rk, jt, ls, rs) = args();
+        let merge = SortMergeJoinOperator::new(lk, rk, jt, ls, rs).unwrap();
+
+        let (left, right) = ([left.clone()], [right]);
+        let expected = hash.execute_join(&left, &right).unwrap();
+        let actual = merge.execute_join(&left, &right).unwrap();
+        assert_eq!(actual[0].num_rows(), 3);
+        assert_eq!(
+            actual[0].to_arrow().unwrap(),
+            expected[0].to_arrow().unwrap()
+        );
+        // The output keeps each side's own key type
+        assert_eq!(actual[0].schema().field(2).data_type(), &DataType::Int64);
+
+        let strings = Arc::new(Schema::new(vec![Field::new("key", DataType::Utf8, false)]));
+        let err = SortMergeJoinOperator::new(
+            "id".to_string(),
+            "key".to_string(),
+            JoinType::Inner,
+            left[0].schema().clone(),
+            strings,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err,
+            "Join keys 'id' (Int32) and 'key' (Utf8) have incompatible types"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+// Unpivot (melt): wide columns to rows
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::Operator;
+use crate::types::{numeric_coercion, string_coercion};
+use arrow::array::{ArrayRef, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use std::sync::Arc;
+
+/// Unpivot operator: each input row becomes one row per value column, holding the id
+/// columns, a `variable` column with the value column's name and a `value` column.
+/// Rows of a batch are grouped by value column (all rows for the first, then the next).
+pub struct UnpivotOperator {
+    id_indices: Vec<usize>,
+    /// Value column indices and names
+    values: Vec<(usize, String)>,
+    value_type: DataType,
+    schema: SchemaRef,
+}
+
+impl UnpivotOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Unpivot";
+
+    /// Create a new Unpivot operator. Value columns must share a type, or widen to a
+    /// common numeric or string type; the `value` column has that type.
+    pub fn new(
+        id_columns: &[String],
+        value_columns: &[String],
+        input_schema: SchemaRef,
+    ) -> Result<Self, String> {
+        if value_columns.is_empty() {
+            return Err("Unpivot needs at least one value column".to_string());
+        }
+        let index = |name: &String| {
+            input_schema
+                .index_of(name)
+                .map_err(|_| format!("Column '{}' not found", name))
+        };
+        let id_indices = id_columns.iter().map(index).collect::<Result<Vec<_>, _>>()?;
+        let mut fields: Vec<Field> = id_indices
+            .iter()
+            .map(|&i| input_schema.field(i).clone())
+            .collect();
+        for name in ["variable", "value"] {
+            if fields.iter().any(|f| f.name() == name) {
+                return Err(format!("Unpivot id column '{}' clashes with its output", name));
+            }
+        }
+
+        let mut values = Vec::with_capacity(value_columns.len());
+        let mut value_type: Option<DataType> = None;
+        let mut nullable = false;
+        for name in value_columns {
+            let i = index(name)?;
+            let field = input_schema.field(i);
+            nullable |= field.is_nullable();
+            value_type = Some(match value_type {
+                None => field.data_type().clone(),
+                Some(common) if &common == field.data_type() => common,
+                Some(common) => numeric_coercion(&common, field.data_type())
+                    .or_else(|| string_coercion(&common, field.data_type()))
+                    .ok_or_else(|| {
+                        format!(
+                            "Unpivot value columns must share a type: '{}' is {:?}, not {:?}",
+                            name,
+                            field.data_type(),
+                            common
+                        )
+                    })?,
+            });
+            values.push((i, name.clone()));
+        }
+        let value_type = value_type.unwrap_or(DataType::Null);
+        fields.push(Field::new("variable", DataType::Utf8, false));
+        fields.push(Field::new("value", value_type.clone(), nullable));
+
+        Ok(Self {
+            id_indices,
+            values,
+            value_type,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+}
+
+impl Operator for UnpivotOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        let rows = input.num_rows();
+        let parts = self
+            .values
+            .iter()
+            .map(|(i, name)| {
+                let mut columns: Vec<ArrayRef> = self
+                    .id_indices
+                    .iter()
+                    .map(|&id| input.columns()[id].clone())
+                    .collect();
+                columns.push(Arc::new(StringArray::from(vec![name.as_str(); rows])));
+                let value = arrow::compute::cast(&input.columns()[*i], &self.value_type)
+                    .map_err(|e| format!("Failed to cast '{}': {}", name, e))?;
+                columns.push(value);
+                RecordBatch::try_new(self.schema.clone(), columns)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        RecordBatch::concat(&parts)
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows * self.values.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int32Array, Int64Array};
+
+    #[test]
+    fn test_unpivot_widens_value_columns() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("q1", DataType::Int32, false),
+            Field::new("q2", DataType::Int64, true),
+            Field::new("q3", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(Int32Array::from(vec![10, 20])),
+                Arc::new(Int64Array::from(vec![Some(11), None])),
+                Arc::new(Float64Array::from(vec![1.5, 2.5])),
+            ],
+        )
+        .unwrap();
+        let names = |cols: &[&str]| cols.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let op = UnpivotOperator::new(&names(&["id"]), &names(&["q1", "q2", "q3"]), schema)
+            .unwrap();
+        assert_eq!(op.schema().field(2).data_type(), &DataType::Float64);
+        assert!(op.schema().field(2).is_nullable());
+
+        let out = op.execute(&batch).unwrap();
+        assert_eq!(out.num_rows(), 6);
+        let rows: Vec<String> = out
+            .rows()
+            .map(|row| {
+                let row: Vec<String> = row.unwrap().iter().map(|v| v.to_string()).collect();
+                row.join(" ")
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "1 'q1' 10.0",
+                "2 'q1' 20.0",
+                "1 'q2' 11.0",
+                "2 'q2' NULL",
+                "1 'q3' 1.5",
+                "2 'q3' 2.5",
+            ]
+        );
+        assert_eq!(op.estimated_output_rows(10), Some(30));
+    }
+
+    #[test]
+    fn test_unpivot_rejects_mixed_types() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, false),
+        ]));
+        let names = |cols: &[&str]| cols.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let err = UnpivotOperator::new(&[], &names(&["a", "b"]), schema.clone()).err().unwrap();
+        assert!(err.contains("must share a type"), "{}", err);
+        assert!(UnpivotOperator::new(&[], &[], schema.clone()).is_err());
+        assert!(UnpivotOperator::new(&names(&["a"]), &names(&["c"]), schema).is_err());
+    }
+}
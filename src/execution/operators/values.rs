@@ -0,0 +1,133 @@
+// Constant rows given inline
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::expression::literal_to_array;
+use crate::execution::operators::Operator;
+use crate::planner::logical_plan::LogicalValue;
+use arrow::array::{new_empty_array, new_null_array, Array, ArrayRef};
+
+/// Values operator: a source producing one batch built from literal rows
+pub struct ValuesOperator {
+    batch: RecordBatch,
+}
+
+impl ValuesOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Values";
+
+    /// Build the batch for `rows`. Each row must have one value per field of `schema`,
+    /// each of the field's type; `LogicalValue::Null` is allowed in nullable fields.
+    pub fn new(schema: SchemaRef, rows: &[Vec<LogicalValue>]) -> Result<Self, String> {
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != schema.fields().len() {
+                return Err(format!(
+                    "Row {} has {} values, but the schema has {} columns",
+                    i,
+                    row.len(),
+                    schema.fields().len()
+                ));
+            }
+        }
+        let columns = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(c, field)| {
+                let cells = rows
+                    .iter()
+                    .enumerate()
+                    .map(|(i, row)| match &row[c] {
+                        LogicalValue::Null(_) if field.is_nullable() => {
+                            Ok(new_null_array(field.data_type(), 1))
+                        }
+                        LogicalValue::Null(_) => Err(format!(
+                            "Row {} has NULL for non-nullable column '{}'",
+                            i,
+                            field.name()
+                        )),
+                        value if &value.data_type() == field.data_type() => {
+                            Ok(literal_to_array(value, 1))
+                        }
+                        value => Err(format!(
+                            "Row {} has {} ({:?}) for column '{}' of type {:?}",
+                            i,
+                            value,
+                            value.data_type(),
+                            field.name(),
+                            field.data_type()
+                        )),
+                    })
+                    .collect::<Result<Vec<ArrayRef>, String>>()?;
+                if cells.is_empty() {
+                    return Ok(new_empty_array(field.data_type()));
+                }
+                let cells: Vec<&dyn Array> = cells.iter().map(|a| a.as_ref()).collect();
+                arrow::compute::concat(&cells).map_err(|e| format!("Concat failed: {}", e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let batch = RecordBatch::try_new_with_row_count(schema, columns, rows.len())?;
+        Ok(Self { batch })
+    }
+
+    /// The rows as batches: none if there are no rows, else one
+    pub fn batches(&self) -> Vec<RecordBatch> {
+        if self.batch.is_empty() {
+            Vec::new()
+        } else {
+            vec![self.batch.clone()]
+        }
+    }
+}
+
+impl Operator for ValuesOperator {
+    /// Values is a source and ignores its input, returning its rows
+    fn execute(&self, _input: &RecordBatch) -> Result<RecordBatch, String> {
+        Ok(self.batch.clone())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.batch.schema().clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, _input_rows: usize) -> Option<usize> {
+        Some(self.batch.num_rows())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_values_validates_rows() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("label", DataType::Utf8, true),
+        ]));
+        let label = |s: &str| LogicalValue::String(s.to_string());
+        let rows = vec![
+            vec![LogicalValue::Int32(1), label("one")],
+            vec![LogicalValue::Int32(2), LogicalValue::Null(DataType::Utf8)],
+        ];
+        let op = ValuesOperator::new(schema.clone(), &rows).unwrap();
+        let batch = &op.batches()[0];
+        assert_eq!(batch.i32_column("id").unwrap().values().to_vec(), vec![1, 2]);
+        assert_eq!(batch.str_column("label").unwrap().null_count(), 1);
+        assert!(ValuesOperator::new(schema.clone(), &[]).unwrap().batches().is_empty());
+
+        let short = vec![vec![LogicalValue::Int32(1)]];
+        let err = ValuesOperator::new(schema.clone(), &short).err().unwrap();
+        assert_eq!(err, "Row 0 has 1 values, but the schema has 2 columns");
+        let wrong_type = vec![vec![LogicalValue::Int64(1), label("x")]];
+        let err = ValuesOperator::new(schema.clone(), &wrong_type).err().unwrap();
+        assert_eq!(err, "Row 0 has 1 (Int64) for column 'id' of type Int32");
+        let null_id = vec![vec![LogicalValue::Null(DataType::Int32), label("x")]];
+        assert!(ValuesOperator::new(schema, &null_id).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+// Window functions (ROW_NUMBER, running SUM)
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::aggregate::extract_numeric;
+use crate::execution::operators::Operator;
+use crate::planner::logical_plan::{OrderByExpr, WindowExpr, WindowFunction};
+use arrow::array::{ArrayRef, Float64Array, Int64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::row::{RowConverter, SortField};
+use arrow_ord::sort::{lexsort_to_indices, SortColumn, SortOptions};
+use arrow_select::take::take;
+use std::sync::Arc;
+
+/// Window operator: sorts rows by (partition_by, order_by), then computes each window
+/// function row-by-row within partitions and appends the results as new columns.
+/// Running aggregates use a ROWS frame (start of partition to the current row), so
+/// rows that tie on the order keys still get distinct running values.
+pub struct WindowOperator {
+    partition_by: Vec<String>,
+    order_by: Vec<OrderByExpr>,
+    functions: Vec<WindowExpr>,
+    schema: SchemaRef,
+}
+
+impl WindowOperator {
+    /// Name of this operator, as returned by `name`
+    pub const NAME: &str = "Window";
+
+    /// Create a new Window operator
+    pub fn new(
+        partition_by: Vec<String>,
+        order_by: Vec<OrderByExpr>,
+        functions: Vec<WindowExpr>,
+        input_schema: SchemaRef,
+    ) -> Result<Self, String> {
+        for name in &partition_by {
+            input_schema
+                .field_with_name(name)
+                .map_err(|_| format!("Partition column '{}' not found", name))?;
+        }
+        for e in &order_by {
+            input_schema
+                .field_with_name(&e.column)
+                .map_err(|_| format!("Order column '{}' not found", e.column))?;
+        }
+
+        // Output schema: input columns + one column per window function
+        let mut fields: Vec<Field> = input_schema
+            .fields()
+            .iter()
+            .map(|f| f.as_ref().clone())
+            .collect();
+        for w in &functions {
+            let data_type = match w.function {
+                WindowFunction::RowNumber => {
+                    if w.column.is_some() {
+                        return Err(format!("ROW_NUMBER '{}' does not take a column", w.alias));
+                    }
+                    DataType::Int64
+                }
+                WindowFunction::RunningSum => {
+                    let column = w
+                        .column
+                        .as_ref()
+                        .ok_or_else(|| format!("Running SUM '{}' requires a column", w.alias))?;
+                    input_schema
+                        .field_with_name(column)
+                        .map_err(|_| format!("Window column '{}' not found", column))?;
+                    DataType::Float64
+                }
+            };
+            fields.push(Field::new(w.alias.as_str(), data_type, true));
+        }
+
+        Ok(Self {
+            partition_by,
+            order_by,
+            functions,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    /// Sort the batch by partition keys, then order keys
+    fn sort_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, String> {
+        let mut sort_columns: Vec<SortColumn> = Vec::new();
+        for name in &self.partition_by {
+            sort_columns.push(SortColumn {
+                values: batch
+                    .column_by_name(name)
+                    .ok_or_else(|| format!("Column '{}' not found", name))?
+                    .clone(),
+                options: Some(SortOptions {
+                    descending: false,
+                    nulls_first: true,
+                }),
+            });
+        }
+        for e in &self.order_by {
+            sort_columns.push(SortColumn {
+                values: batch
+                    .column_by_name(&e.column)
+                    .ok_or_else(|| format!("Column '{}' not found", e.column))?
+                    .clone(),
+                options: Some(SortOptions {
+                    descending: !e.ascending,
+                    nulls_first: true,
+                }),
+            });
+        }
+        if sort_columns.is_empty() {
+            return Ok(batch.clone());
+        }
+
+        let indices = lexsort_to_indices(&sort_columns, None)
+            .map_err(|e| format!("Sort failed: {}", e))?;
+        let sorted_columns: Vec<ArrayRef> = batch
+            .columns()
+            .iter()
+            .map(|col| take(col.as_ref(), &indices, None).map_err(|e| format!("Take failed: {}", e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        RecordBatch::try_new(batch.schema().clone(), sorted_columns)
+    }
+
+    /// For each row of a sorted batch, whether it starts a new partition
+    fn partition_starts(&self, batch: &RecordBatch) -> Result<Vec<bool>, String> {
+        let n = batch.num_rows();
+        if self.partition_by.is_empty() {
+            return Ok((0..n).map(|row| row == 0).collect());
+        }
+        let columns: Vec<ArrayRef> = self
+            .partition_by
+            .iter()
+            .map(|name| {
+                batch
+                    .column_by_name(name)
+                    .cloned()
+                    .ok_or_else(|| format!("Column '{}' not found", name))
+            })
+            .collect::<Result<_, _>>()?;
+        let fields = columns
+            .iter()
+            .map(|c| SortField::new(c.data_type().clone()))
+            .collect();
+        let converter = RowConverter::new(fields)
+            .map_err(|e| format!("Failed to create row converter: {}", e))?;
+        let rows = converter
+            .convert_columns(&columns)
+            .map_err(|e| format!("Failed to convert partition keys: {}", e))?;
+        Ok((0..n)
+            .map(|row| row == 0 || rows.row(row) != rows.row(row - 1))
+            .collect())
+    }
+
+    /// Compute one window function over a sorted batch
+    fn compute(
+        &self,
+        function: &WindowExpr,
+        batch: &RecordBatch,
+        starts: &[bool],
+    ) -> Result<ArrayRef, String> {
+        match function.function {
+            WindowFunction::RowNumber => {
+                let mut row_number = 0i64;
+                let values: Vec<i64> = starts
+                    .iter()
+                    .map(|&start| {
+                        row_number = if start { 1 } else { row_number + 1 };
+                        row_number
+                    })
+                    .collect();
+                Ok(Arc::new(Int64Array::from(values)))
+            }
+            WindowFunction::RunningSum => {
+                let column = function.column.as_deref().unwrap_or_default();
+                let col = batch
+                    .column_by_name(column)
+                    .ok_or_else(|| format!("Column '{}' not found", column))?;
+                // Null until the partition has seen a non-null value, like SUM over nulls
+                let mut sum: Option<f64> = None;
+                let values: Vec<Option<f64>> = starts
+                    .iter()
+                    .enumerate()
+                    .map(|(row, &start)| {
+                        if start {
+                            sum = None;
+                        }
+                        if let Some(v) = extract_numeric(col, row) {
+                            sum = Some(sum.unwrap_or(0.0) + v);
+                        }
+                        sum
+                    })
+                    .collect();
+                Ok(Arc::new(Float64Array::from(values)))
+            }
+        }
+    }
+}
+
+impl Operator for WindowOperator {
+    fn execute(&self, input: &RecordBatch) -> Result<RecordBatch, String> {
+        let sorted = self.sort_batch(input)?;
+        let starts = self.partition_starts(&sorted)?;
+
+        let mut columns = sorted.columns().to_vec();
+        for function in &self.functions {
+            columns.push(self.compute(function, &sorted, &starts)?);
+        }
+        RecordBatch::try_new(self.schema.clone(), columns)
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn estimated_output_rows(&self, input_rows: usize) -> Option<usize> {
+        Some(input_rows)
+    }
+
+    fn execute_many(&self, inputs: &[RecordBatch]) -> Result<Vec<RecordBatch>, String> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Partitions can span batches, so window over the concatenated input
+        let combined = RecordBatch::concat(inputs)?;
+        let out = self.execute(&combined)?;
+        Ok(if out.is_empty() { vec![] } else { vec![out] })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Int32Array, StringArray};
+
+    fn sales_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, false),
+            Field::new("day", DataType::Int32, false),
+            Field::new("amount", DataType::Int32, true),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec!["west", "east", "west", "east", "west"])),
+            Arc::new(Int32Array::from(vec![3, 2, 1, 1, 2])),
+            Arc::new(Int32Array::from(vec![Some(30), Some(20), Some(10), None, Some(5)])),
+        ];
+        RecordBatch::try_new(schema, columns).unwrap()
+    }
+
+    #[test]
+    fn test_row_number_and_running_sum() {
+        let batch = sales_batch();
+        let op = WindowOperator::new(
+            vec!["region".to_string()],
+            vec![OrderByExpr {
+                column: "day".to_string(),
+                ascending: true,
+            }],
+            vec![
+                WindowExpr {
+                    function: WindowFunction::RowNumber,
+                    column: None,
+                    alias: "rn".to_string(),
+                },
+                WindowExpr {
+                    function: WindowFunction::RunningSum,
+                    column: Some("amount".to_string()),
+                    alias: "running".to_string(),
+                },
+            ],
+            batch.schema().clone(),
+        )
+        .unwrap();
+
+        // Split input so partitions span batches
+        let out = op
+            .execute_many(&[batch.slice(0, 2).unwrap(), batch.slice(2, 3).unwrap()])
+            .unwrap();
+        assert_eq!(out.len(), 1);
+        let out = &out[0];
+        assert_eq!(out.num_columns(), 5);
+
+        let region = out.column_by_name("region").unwrap();
+        let region = region.as_any().downcast_ref::<StringArray>().unwrap();
+        let day = out.column_by_name("day").unwrap();
+        let day = day.as_any().downcast_ref::<Int32Array>().unwrap();
+        let rn = out.column_by_name("rn").unwrap();
+        let rn = rn.as_any().downcast_ref::<Int64Array>().unwrap();
+        let running = out.column_by_name("running").unwrap();
+        let running = running.as_any().downcast_ref::<Float64Array>().unwrap();
+
+        let rows: Vec<(&str, i32, i64, Option<f64>)> = (0..out.num_rows())
+            .map(|i| {
+                let r = if running.is_null(i) { None } else { Some(running.value(i)) };
+                (region.value(i), day.value(i), rn.value(i), r)
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("east", 1, 1, None),
+                ("east", 2, 2, Some(20.0)),
+                ("west", 1, 1, Some(10.0)),
+                ("west", 2, 2, Some(15.0)),
+                ("west", 3, 3, Some(45.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_running_sum_requires_column() {
+        let batch = sales_batch();
+        let result = WindowOperator::new(
+            vec![],
+            vec![],
+            vec![WindowExpr {
+                function: WindowFunction::RunningSum,
+                column: None,
+                alias: "s".to_string(),
+            }],
+            batch.schema().clone(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,363 @@
+// Physical query plan
+
+use crate::execution::batch::{RecordBatch, SchemaRef};
+use crate::execution::operators::{
+    AggregateOperator, CoalesceBatchesOperator, FillNullOperator, FilterFnOperator,
+    FilterOperator, FilterProjectOperator, HashJoinOperator, LimitOperator, Operator,
+    ProjectOperator, RenameOperator, RowIndexOperator, ScanOperator, SetOperator,
+    SortMergeJoinOperator, SortOperator, UnpivotOperator, ValuesOperator, WindowOperator,
+};
+
+/// Physical plan: a tree of operators with their schemas already bound.
+/// Built from a LogicalPlan by `Executor::create_physical_plan`, which resolves
+/// each node's input schema and chooses the operator that will run it.
+pub enum PhysicalPlan {
+    /// Read a Parquet file
+    Scan(ScanOperator),
+    /// Constant rows
+    Values(ValuesOperator),
+    /// Select columns from the input
+    Project {
+        input: Box<PhysicalPlan>,
+        op: ProjectOperator,
+    },
+    /// Filter rows of the input
+    Filter {
+        input: Box<PhysicalPlan>,
+        op: FilterOperator,
+    },
+    /// Filter rows of the input with a row closure
+    FilterFn {
+        input: Box<PhysicalPlan>,
+        op: FilterFnOperator,
+    },
+    /// Filter rows of the input and project the survivors in one pass
+    FilterProject {
+        input: Box<PhysicalPlan>,
+        op: FilterProjectOperator,
+    },
+    /// Hash aggregation over all input batches
+    Aggregate {
+        input: Box<PhysicalPlan>,
+        op: AggregateOperator,
+    },
+    /// Global sort over all input batches
+    Sort {
+        input: Box<PhysicalPlan>,
+        op: SortOperator,
+    },
+    /// Replace nulls with a constant
+    FillNull {
+        input: Box<PhysicalPlan>,
+        op: FillNullOperator,
+    },
+    /// Rename columns of the input
+    Rename {
+        input: Box<PhysicalPlan>,
+        op: RenameOperator,
+    },
+    /// Window functions over all input batches
+    Window {
+        input: Box<PhysicalPlan>,
+        op: WindowOperator,
+    },
+    /// Hash join: right is the build side, left is the probe side
+    HashJoin {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        op: HashJoinOperator,
+    },
+    /// Sort-merge join: both sides are sorted by their key and merged
+    SortMergeJoin {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        op: SortMergeJoinOperator,
+    },
+    /// INTERSECT / EXCEPT over all batches of both inputs
+    SetOperation {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        op: SetOperator,
+    },
+    /// First `limit` rows of the input, in input order
+    Limit {
+        input: Box<PhysicalPlan>,
+        op: LimitOperator,
+    },
+    /// Row numbers appended across all input batches
+    RowIndex {
+        input: Box<PhysicalPlan>,
+        op: RowIndexOperator,
+    },
+    /// Wide value columns turned into variable/value rows, batch by batch
+    Unpivot {
+        input: Box<PhysicalPlan>,
+        op: UnpivotOperator,
+    },
+    /// Small adjacent input batches merged into larger ones
+    CoalesceBatches {
+        input: Box<PhysicalPlan>,
+        op: CoalesceBatchesOperator,
+    },
+}
+
+impl PhysicalPlan {
+    /// Output schema of this node
+    pub fn schema(&self) -> SchemaRef {
+        match self {
+            PhysicalPlan::Scan(op) => op.schema(),
+            PhysicalPlan::Values(op) => op.schema(),
+            PhysicalPlan::Project { op, .. } => op.schema(),
+            PhysicalPlan::Filter { op, .. } => op.schema(),
+            PhysicalPlan::FilterFn { op, .. } => op.schema(),
+            PhysicalPlan::FilterProject { op, .. } => op.schema(),
+            PhysicalPlan::Aggregate { op, .. } => op.schema(),
+            PhysicalPlan::Sort { op, .. } => op.schema(),
+            PhysicalPlan::Window { op, .. } => op.schema(),
+            PhysicalPlan::FillNull { op, .. } => op.schema(),
+            PhysicalPlan::Rename { op, .. } => op.schema(),
+            PhysicalPlan::HashJoin { op, .. } => op.schema(),
+            PhysicalPlan::SortMergeJoin { op, .. } => op.schema(),
+            PhysicalPlan::SetOperation { op, .. } => op.schema(),
+            PhysicalPlan::Limit { op, .. } => op.schema(),
+            PhysicalPlan::RowIndex { op, .. } => op.schema(),
+            PhysicalPlan::Unpivot { op, .. } => op.schema(),
+            PhysicalPlan::CoalesceBatches { op, .. } => op.schema(),
+        }
+    }
+
+    /// Execute this node (and its inputs) and return the resulting batches
+    pub fn execute(&self) -> Result<Vec<RecordBatch>, String> {
+        match self {
+            PhysicalPlan::Scan(op) => op.read_all(),
+            PhysicalPlan::Values(op) => Ok(op.batches()),
+            PhysicalPlan::Project { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::Filter { input, op } => {
+                let input_batches = input.execute()?;
+                let filtered_batches = op.execute_many(&input_batches)?;
+
+                // Filter out empty batches
+                Ok(filtered_batches
+                    .into_iter()
+                    .filter(|b| !b.is_empty())
+                    .collect())
+            }
+            PhysicalPlan::FilterFn { input, op } => {
+                let input_batches = input.execute()?;
+                let batches = op.execute_many(&input_batches)?;
+                Ok(batches.into_iter().filter(|b| !b.is_empty()).collect())
+            }
+            PhysicalPlan::FilterProject { input, op } => {
+                let input_batches = input.execute()?;
+                let batches = op.execute_many(&input_batches)?;
+                Ok(batches.into_iter().filter(|b| !b.is_empty()).collect())
+            }
+            PhysicalPlan::Aggregate { input, op } => {
+                let input_batches = input.execute()?;
+                if input_batches.is_empty() {
+                    // No input at all: no groups, or the single row of a global aggregate
+                    return Ok(vec![op.execute(&RecordBatch::new_empty(input.schema()))?]);
+                }
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::Sort { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::FillNull { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::Rename { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::Window { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::HashJoin { left, right, op } => {
+                let left_batches = left.execute()?;
+                let right_batches = right.execute()?;
+                op.execute_join(&left_batches, &right_batches)
+            }
+            PhysicalPlan::SortMergeJoin { left, right, op } => {
+                let left_batches = left.execute()?;
+                let right_batches = right.execute()?;
+                op.execute_join(&left_batches, &right_batches)
+            }
+            PhysicalPlan::SetOperation { left, right, op } => {
+                let left_batches = left.execute()?;
+                let right_batches = right.execute()?;
+                op.execute_set(&left_batches, &right_batches)
+            }
+            PhysicalPlan::Limit { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::RowIndex { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::Unpivot { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+            PhysicalPlan::CoalesceBatches { input, op } => {
+                let input_batches = input.execute()?;
+                op.execute_many(&input_batches)
+            }
+        }
+    }
+
+    /// Execute this node, passing each output batch to `f` as it is produced rather
+    /// than collecting them. Scans, projections, filters, renames, null fills, unpivots
+    /// and coalescing stream batch by batch; other nodes need their whole input and run
+    /// as in `execute`.
+    pub fn execute_each(
+        &self,
+        f: &mut dyn FnMut(RecordBatch) -> Result<(), String>,
+    ) -> Result<(), String> {
+        match self {
+            PhysicalPlan::Scan(op) => op.read_each(f),
+            PhysicalPlan::Project { input, op } => {
+                input.execute_each(&mut |batch| f(op.execute(&batch)?))
+            }
+            PhysicalPlan::Filter { input, op } => input.execute_each(&mut |batch| {
+                let filtered = op.execute(&batch)?;
+                if filtered.is_empty() {
+                    return Ok(());
+                }
+                f(filtered)
+            }),
+            PhysicalPlan::FilterFn { input, op } => input.execute_each(&mut |batch| {
+                let filtered = op.execute(&batch)?;
+                if filtered.is_empty() {
+                    return Ok(());
+                }
+                f(filtered)
+            }),
+            PhysicalPlan::FilterProject { input, op } => input.execute_each(&mut |batch| {
+                let projected = op.execute(&batch)?;
+                if projected.is_empty() {
+                    return Ok(());
+                }
+                f(projected)
+            }),
+            PhysicalPlan::FillNull { input, op } => {
+                input.execute_each(&mut |batch| f(op.execute(&batch)?))
+            }
+            PhysicalPlan::Rename { input, op } => {
+                input.execute_each(&mut |batch| f(op.execute(&batch)?))
+            }
+            PhysicalPlan::Unpivot { input, op } => {
+                input.execute_each(&mut |batch| f(op.execute(&batch)?))
+            }
+            PhysicalPlan::CoalesceBatches { input, op } => {
+                let mut pending = Vec::new();
+                input.execute_each(&mut |batch| {
+                    op.push(&mut pending, batch)?
+                        .into_iter()
+                        .try_for_each(&mut *f)
+                })?;
+                op.finish(&mut pending)?.into_iter().try_for_each(f)
+            }
+            _ => self.execute()?.into_iter().try_for_each(f),
+        }
+    }
+
+    /// Wrap every filter, fused filter-project and filtered scan in a CoalesceBatches
+    /// node merging its output batches of fewer than `min_rows` rows
+    pub fn coalesce_filtered_batches(self, min_rows: usize) -> PhysicalPlan {
+        let wrap = |input: Box<PhysicalPlan>| Box::new(input.coalesce_filtered_batches(min_rows));
+        let plan = match self {
+            PhysicalPlan::Scan(op) => PhysicalPlan::Scan(op),
+            PhysicalPlan::Values(op) => PhysicalPlan::Values(op),
+            PhysicalPlan::Project { input, op } => PhysicalPlan::Project {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::Filter { input, op } => PhysicalPlan::Filter {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::FilterFn { input, op } => PhysicalPlan::FilterFn {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::FilterProject { input, op } => PhysicalPlan::FilterProject {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::Aggregate { input, op } => PhysicalPlan::Aggregate {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::Sort { input, op } => PhysicalPlan::Sort {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::FillNull { input, op } => PhysicalPlan::FillNull {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::Rename { input, op } => PhysicalPlan::Rename {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::Window { input, op } => PhysicalPlan::Window {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::HashJoin { left, right, op } => PhysicalPlan::HashJoin {
+                left: wrap(left),
+                right: wrap(right),
+                op,
+            },
+            PhysicalPlan::SortMergeJoin { left, right, op } => PhysicalPlan::SortMergeJoin {
+                left: wrap(left),
+                right: wrap(right),
+                op,
+            },
+            PhysicalPlan::SetOperation { left, right, op } => PhysicalPlan::SetOperation {
+                left: wrap(left),
+                right: wrap(right),
+                op,
+            },
+            PhysicalPlan::Limit { input, op } => PhysicalPlan::Limit {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::RowIndex { input, op } => PhysicalPlan::RowIndex {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::Unpivot { input, op } => PhysicalPlan::Unpivot {
+                input: wrap(input),
+                op,
+            },
+            PhysicalPlan::CoalesceBatches { input, op } => PhysicalPlan::CoalesceBatches {
+                input: wrap(input),
+                op,
+            },
+        };
+        let filtered = match &plan {
+            PhysicalPlan::Scan(op) => !op.filters().is_empty(),
+            PhysicalPlan::Filter { .. }
+            | PhysicalPlan::FilterFn { .. }
+            | PhysicalPlan::FilterProject { .. } => true,
+            _ => false,
+        };
+        if !filtered {
+            return plan;
+        }
+        let op = CoalesceBatchesOperator::new(min_rows, plan.schema());
+        PhysicalPlan::CoalesceBatches {
+            input: Box::new(plan),
+            op,
+        }
+    }
+}
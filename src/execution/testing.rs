@@ -0,0 +1,177 @@
+// Result comparison for tests
+
+use crate::execution::batch::{RecordBatch, SchemaMismatch};
+use arrow::array::{Array, ArrayRef};
+use arrow::compute::{lexsort_to_indices, take, SortColumn};
+use arrow::datatypes::DataType;
+use arrow::util::display::array_value_to_string;
+
+/// Panic unless `actual` and `expected` hold the same rows in the same order, however
+/// they are split into batches. The message names the first row and column that differ.
+pub fn assert_batches_eq(actual: &[RecordBatch], expected: &[RecordBatch]) {
+    if let Err(diff) = compare_batches(actual, expected, false) {
+        panic!("{}", diff);
+    }
+}
+
+/// As `assert_batches_eq`, but ignoring row order: both sides are sorted by every column
+/// first, for queries whose output order is not defined
+pub fn assert_batches_eq_unordered(actual: &[RecordBatch], expected: &[RecordBatch]) {
+    if let Err(diff) = compare_batches(actual, expected, true) {
+        panic!("{}", diff);
+    }
+}
+
+/// Compare the rows of `actual` and `expected` cell by cell, after sorting both by every
+/// column if `sort`. Column names and types must match; nullability is ignored. On a
+/// difference, returns a description of the first one.
+pub fn compare_batches(
+    actual: &[RecordBatch],
+    expected: &[RecordBatch],
+    sort: bool,
+) -> Result<(), String> {
+    let rows = |batches: &[RecordBatch]| batches.iter().map(|b| b.num_rows()).sum::<usize>();
+    let (num_rows, expected_rows) = (rows(actual), rows(expected));
+    if num_rows != expected_rows {
+        return Err(format!(
+            "Row counts differ: actual {}, expected {}",
+            num_rows, expected_rows
+        ));
+    }
+    let (Some(actual_first), Some(expected_first)) = (actual.first(), expected.first()) else {
+        // No batches on one side, and so no rows on either
+        return Ok(());
+    };
+    let mismatch = SchemaMismatch::between(actual_first.schema(), expected_first.schema());
+    if !mismatch.types_match() {
+        return Err(format!("Schemas differ (left is actual): {}", mismatch));
+    }
+    let schema = expected_first.schema();
+    let names: Vec<&String> = schema.fields().iter().map(|f| f.name()).collect();
+    let actual = columns(actual, sort)?;
+    let expected = columns(expected, sort)?;
+
+    for row in 0..num_rows {
+        for (i, (a, e)) in actual.iter().zip(&expected).enumerate() {
+            let (a, e) = (cell(a, row)?, cell(e, row)?);
+            if a != e {
+                return Err(format!(
+                    "Row {} differs in column '{}': actual {}, expected {}\n  \
+                     actual:   [{}]\n  expected: [{}]",
+                    row,
+                    names[i],
+                    a,
+                    e,
+                    render_row(&actual, row)?,
+                    render_row(&expected, row)?
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Columns of the concatenated batches, with rows sorted by every column if `sort`
+fn columns(batches: &[RecordBatch], sort: bool) -> Result<Vec<ArrayRef>, String> {
+    let columns = RecordBatch::concat(batches)?.columns().to_vec();
+    if !sort || columns.is_empty() {
+        return Ok(columns);
+    }
+    let sort_columns: Vec<SortColumn> = columns
+        .iter()
+        .map(|c| SortColumn {
+            values: c.clone(),
+            options: None,
+        })
+        .collect();
+    let indices = lexsort_to_indices(&sort_columns, None)
+        .map_err(|e| format!("Failed to sort rows: {}", e))?;
+    columns
+        .iter()
+        .map(|c| take(c.as_ref(), &indices, None).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// One value as displayed in a diff: NULL, strings quoted
+fn cell(column: &ArrayRef, row: usize) -> Result<String, String> {
+    if column.is_null(row) {
+        return Ok("NULL".to_string());
+    }
+    let value = array_value_to_string(column, row).map_err(|e| e.to_string())?;
+    Ok(match column.data_type() {
+        DataType::Utf8 | DataType::LargeUtf8 => format!("'{}'", value),
+        _ => value,
+    })
+}
+
+/// A row as displayed in a diff, e.g. `2, NULL, 0.5`
+fn render_row(columns: &[ArrayRef], row: usize) -> Result<String, String> {
+    let cells = columns
+        .iter()
+        .map(|c| cell(c, row))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(cells.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int32Array, StringArray};
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    fn batch(ids: Vec<i32>, names: Vec<Option<&str>>, scores: Vec<f64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("score", DataType::Float64, false),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+            Arc::new(Float64Array::from(scores)),
+        ];
+        RecordBatch::try_new(schema, columns).unwrap()
+    }
+
+    #[test]
+    fn test_compare_batches() {
+        let expected = vec![batch(
+            vec![1, 2, 3],
+            vec![Some("a"), None, Some("c")],
+            vec![0.5; 3],
+        )];
+        // Same rows, split differently
+        let actual = vec![
+            batch(vec![1], vec![Some("a")], vec![0.5]),
+            batch(vec![2, 3], vec![None, Some("c")], vec![0.5, 0.5]),
+        ];
+        assert_batches_eq(&actual, &expected);
+        assert_batches_eq(&[], &[]);
+
+        let reordered = vec![batch(
+            vec![3, 1, 2],
+            vec![Some("c"), Some("a"), None],
+            vec![0.5; 3],
+        )];
+        assert!(compare_batches(&reordered, &expected, false).is_err());
+        assert_batches_eq_unordered(&reordered, &expected);
+
+        let changed = vec![batch(
+            vec![1, 2, 3],
+            vec![Some("a"), Some("b"), Some("c")],
+            vec![0.5; 3],
+        )];
+        assert_eq!(
+            compare_batches(&changed, &expected, false).unwrap_err(),
+            "Row 1 differs in column 'name': actual 'b', expected NULL\n  \
+             actual:   [2, 'b', 0.5]\n  expected: [2, NULL, 0.5]"
+        );
+        let short = vec![batch(vec![1], vec![Some("a")], vec![0.5])];
+        let err = compare_batches(&short, &expected, false).unwrap_err();
+        assert_eq!(err, "Row counts differ: actual 1, expected 3");
+        let retyped = expected[0].cast_column("id", DataType::Int64).unwrap();
+        let err = compare_batches(&[retyped], &expected, false).unwrap_err();
+        assert!(err.starts_with("Schemas differ"), "{}", err);
+    }
+}
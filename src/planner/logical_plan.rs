@@ -1,27 +1,297 @@
 // Logical query plan
 
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use arrow::datatypes::SchemaRef;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::temporal_conversions::{date32_to_datetime, timestamp_us_to_datetime};
 
-/// Logical expression for filtering
+use crate::execution::batch::RecordBatch;
+use crate::storage::DEFAULT_BATCH_SIZE;
+use crate::types::{numeric_coercion, string_coercion, ScalarValue};
+
+/// Logical expression for filtering and projection
 #[derive(Debug, Clone)]
 pub enum LogicalExpr {
     /// Column reference by name
     Column(String),
     /// Literal value
     Literal(LogicalValue),
-    /// Binary comparison: left op right
+    /// Binary comparison, logical or arithmetic operation: left op right
     BinaryExpr {
         left: Box<LogicalExpr>,
         op: BinaryOp,
         right: Box<LogicalExpr>,
     },
+    /// True where the inner expression is null
+    IsNull(Box<LogicalExpr>),
+    /// True where the inner expression is not null
+    IsNotNull(Box<LogicalExpr>),
+    /// Inner expression with an output name: expr AS name
+    Alias {
+        expr: Box<LogicalExpr>,
+        name: String,
+    },
+    /// Membership test: expr [NOT] IN (list), with SQL null semantics. When the value
+    /// is not found, the result is null (not false) if the value or any list entry is
+    /// null, so `x NOT IN (1, NULL)` is never true.
+    InList {
+        expr: Box<LogicalExpr>,
+        list: Vec<ScalarValue>,
+        negated: bool,
+    },
+    /// Placeholder for the value at this index of `DataFrame::bind`, shown as `$index`.
+    /// Plans with unbound parameters fail to execute.
+    Param(usize),
+}
+
+impl LogicalExpr {
+    /// Names of all columns referenced by this expression, in first-seen order
+    pub fn column_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_column_names(&mut names);
+        names
+    }
+
+    fn collect_column_names(&self, names: &mut Vec<String>) {
+        match self {
+            LogicalExpr::Column(name) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            LogicalExpr::Literal(_) | LogicalExpr::Param(_) => {}
+            LogicalExpr::BinaryExpr { left, right, .. } => {
+                left.collect_column_names(names);
+                right.collect_column_names(names);
+            }
+            LogicalExpr::IsNull(expr)
+            | LogicalExpr::IsNotNull(expr)
+            | LogicalExpr::Alias { expr, .. }
+            | LogicalExpr::InList { expr, .. } => {
+                expr.collect_column_names(names);
+            }
+        }
+    }
+
+    /// Copy of this expression with every column reference renamed by `f`
+    pub fn try_map_columns(
+        &self,
+        f: &mut impl FnMut(&str) -> Result<String, String>,
+    ) -> Result<LogicalExpr, String> {
+        Ok(match self {
+            LogicalExpr::Column(name) => LogicalExpr::Column(f(name)?),
+            LogicalExpr::Literal(_) | LogicalExpr::Param(_) => self.clone(),
+            LogicalExpr::BinaryExpr { left, op, right } => LogicalExpr::BinaryExpr {
+                left: Box::new(left.try_map_columns(f)?),
+                op: *op,
+                right: Box::new(right.try_map_columns(f)?),
+            },
+            LogicalExpr::IsNull(expr) => LogicalExpr::IsNull(Box::new(expr.try_map_columns(f)?)),
+            LogicalExpr::IsNotNull(expr) => {
+                LogicalExpr::IsNotNull(Box::new(expr.try_map_columns(f)?))
+            }
+            LogicalExpr::Alias { expr, name } => LogicalExpr::Alias {
+                expr: Box::new(expr.try_map_columns(f)?),
+                name: name.clone(),
+            },
+            LogicalExpr::InList {
+                expr,
+                list,
+                negated,
+            } => LogicalExpr::InList {
+                expr: Box::new(expr.try_map_columns(f)?),
+                list: list.clone(),
+                negated: *negated,
+            },
+        })
+    }
+
+    /// Copy of this expression with each `Param(i)` replaced by `params[i]`; parameters
+    /// beyond the end of `params` stay unbound
+    pub fn bind_params(&self, params: &[LogicalValue]) -> LogicalExpr {
+        match self {
+            LogicalExpr::Param(i) => match params.get(*i) {
+                Some(value) => LogicalExpr::Literal(value.clone()),
+                None => self.clone(),
+            },
+            LogicalExpr::Column(_) | LogicalExpr::Literal(_) => self.clone(),
+            LogicalExpr::BinaryExpr { left, op, right } => LogicalExpr::BinaryExpr {
+                left: Box::new(left.bind_params(params)),
+                op: *op,
+                right: Box::new(right.bind_params(params)),
+            },
+            LogicalExpr::IsNull(expr) => LogicalExpr::IsNull(Box::new(expr.bind_params(params))),
+            LogicalExpr::IsNotNull(expr) => {
+                LogicalExpr::IsNotNull(Box::new(expr.bind_params(params)))
+            }
+            LogicalExpr::Alias { expr, name } => LogicalExpr::Alias {
+                expr: Box::new(expr.bind_params(params)),
+                name: name.clone(),
+            },
+            LogicalExpr::InList {
+                expr,
+                list,
+                negated,
+            } => LogicalExpr::InList {
+                expr: Box::new(expr.bind_params(params)),
+                list: list.clone(),
+                negated: *negated,
+            },
+        }
+    }
+
+    /// Index of the first parameter in this expression, if it has any
+    pub fn first_param(&self) -> Option<usize> {
+        match self {
+            LogicalExpr::Param(i) => Some(*i),
+            LogicalExpr::Column(_) | LogicalExpr::Literal(_) => None,
+            LogicalExpr::BinaryExpr { left, right, .. } => {
+                left.first_param().or_else(|| right.first_param())
+            }
+            LogicalExpr::IsNull(expr)
+            | LogicalExpr::IsNotNull(expr)
+            | LogicalExpr::Alias { expr, .. }
+            | LogicalExpr::InList { expr, .. } => expr.first_param(),
+        }
+    }
+
+    /// Name of the column this expression produces in a projection:
+    /// the alias, the column name, or the rendered expression
+    pub fn output_name(&self) -> String {
+        match self {
+            LogicalExpr::Alias { name, .. } => name.clone(),
+            LogicalExpr::Column(name) => name.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Data type this expression evaluates to against `schema`
+    pub fn data_type(&self, schema: &Schema) -> Result<DataType, String> {
+        match self {
+            LogicalExpr::Column(name) => schema
+                .field_with_name(name)
+                .map(|f| f.data_type().clone())
+                .map_err(|_| format!("Column '{}' not found in schema", name)),
+            LogicalExpr::Literal(value) => Ok(value.data_type()),
+            LogicalExpr::BinaryExpr { left, op, right } => {
+                if !op.is_arithmetic() && *op != BinaryOp::Concat {
+                    return Ok(DataType::Boolean);
+                }
+                let left_type = left.data_type(schema)?;
+                let right_type = right.data_type(schema)?;
+                let common = if *op == BinaryOp::Concat {
+                    string_coercion(&left_type, &right_type)
+                } else {
+                    numeric_coercion(&left_type, &right_type)
+                };
+                common.ok_or_else(|| {
+                    format!(
+                        "Cannot apply '{}' to {:?} and {:?} in '{}'",
+                        op, left_type, right_type, self
+                    )
+                })
+            }
+            LogicalExpr::IsNull(_) | LogicalExpr::IsNotNull(_) => Ok(DataType::Boolean),
+            LogicalExpr::InList { expr, .. } => {
+                // Still resolve the tested expression so unknown columns are reported
+                expr.data_type(schema)?;
+                Ok(DataType::Boolean)
+            }
+            LogicalExpr::Alias { expr, .. } => expr.data_type(schema),
+            LogicalExpr::Param(i) => Err(unbound_param(*i)),
+        }
+    }
+
+    /// Output field for this expression in a projection over `schema`
+    pub fn to_field(&self, schema: &Schema) -> Result<Field, String> {
+        let nullable = match self.unalias() {
+            LogicalExpr::Column(name) => schema
+                .field_with_name(name)
+                .map(|f| f.is_nullable())
+                .unwrap_or(true),
+            LogicalExpr::Literal(value) => matches!(value, LogicalValue::Null(_)),
+            LogicalExpr::IsNull(_) | LogicalExpr::IsNotNull(_) => false,
+            _ => true,
+        };
+        Ok(Field::new(self.output_name(), self.data_type(schema)?, nullable))
+    }
+
+    /// The expression under any aliases
+    pub fn unalias(&self) -> &LogicalExpr {
+        match self {
+            LogicalExpr::Alias { expr, .. } => expr.unalias(),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for LogicalExpr {
+    /// Render in SQL-like infix form, e.g. `age > 18 AND name = 'Bob'`.
+    /// Nested binary expressions are parenthesized when they bind less tightly than
+    /// their parent (or equally, on the right), so the output re-parses to the same tree.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogicalExpr::Column(name) => write!(f, "{}", name),
+            LogicalExpr::Literal(value) => write!(f, "{}", value),
+            LogicalExpr::BinaryExpr { left, op, right } => {
+                let precedence = op.precedence();
+                write_operand(f, left, |p| p < precedence)?;
+                write!(f, " {} ", op)?;
+                write_operand(f, right, |p| p <= precedence)
+            }
+            LogicalExpr::IsNull(expr) => {
+                write_operand(f, expr, |_| true)?;
+                write!(f, " IS NULL")
+            }
+            LogicalExpr::IsNotNull(expr) => {
+                write_operand(f, expr, |_| true)?;
+                write!(f, " IS NOT NULL")
+            }
+            LogicalExpr::Alias { expr, name } => write!(f, "{} AS {}", expr, name),
+            LogicalExpr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                write_operand(f, expr, |p| p <= BinaryOp::Eq.precedence())?;
+                let list: Vec<String> = list.iter().map(|v| v.to_string()).collect();
+                let not = if *negated { "NOT " } else { "" };
+                write!(f, " {}IN ({})", not, list.join(", "))
+            }
+            LogicalExpr::Param(i) => write!(f, "${}", i),
+        }
+    }
+}
+
+/// Error for a parameter that was never given a value
+pub(crate) fn unbound_param(index: usize) -> String {
+    format!("Parameter ${} is not bound; pass a value for it to DataFrame::bind", index)
+}
+
+/// Write an operand, parenthesizing binary expressions whose operator precedence
+/// satisfies `needs_parens`
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    expr: &LogicalExpr,
+    needs_parens: impl Fn(u8) -> bool,
+) -> fmt::Result {
+    match expr {
+        LogicalExpr::BinaryExpr { op, .. } if needs_parens(op.precedence()) => {
+            write!(f, "({})", expr)
+        }
+        LogicalExpr::InList { .. } if needs_parens(BinaryOp::Eq.precedence()) => {
+            write!(f, "({})", expr)
+        }
+        LogicalExpr::Alias { .. } => write!(f, "({})", expr),
+        _ => write!(f, "{}", expr),
+    }
 }
 
 /// Binary operators for expressions
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BinaryOp {
     Eq,   // ==
     Neq,  // !=
@@ -31,26 +301,130 @@ pub enum BinaryOp {
     Ge,   // >=
     And,  // &&
     Or,   // ||
+    Plus,     // +
+    Minus,    // -
+    Multiply, // *
+    Divide,   // /
+    Modulo,   // %
+    Concat,   // || (string concatenation)
+}
+
+impl BinaryOp {
+    /// Binding strength: OR < AND < comparisons < +, -, || < *, /, %
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::Neq | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt
+            | BinaryOp::Ge => 3,
+            BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Concat => 4,
+            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo => 5,
+        }
+    }
+
+    /// Whether this operator produces a number rather than a boolean
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Plus
+                | BinaryOp::Minus
+                | BinaryOp::Multiply
+                | BinaryOp::Divide
+                | BinaryOp::Modulo
+        )
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            BinaryOp::Eq => "=",
+            BinaryOp::Neq => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "AND",
+            BinaryOp::Or => "OR",
+            BinaryOp::Plus => "+",
+            BinaryOp::Minus => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Modulo => "%",
+            BinaryOp::Concat => "||",
+        };
+        write!(f, "{}", s)
+    }
 }
 
 /// Literal values in expressions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LogicalValue {
     Int32(i32),
     Int64(i64),
     Float64(f64),
     String(String),
     Boolean(bool),
+    /// Days since 1970-01-01
+    Date32(i32),
+    /// Microseconds since 1970-01-01 00:00:00 UTC
+    TimestampMicros(i64),
+    /// NULL of the given type; comparing with it gives null, so filters drop every row
+    Null(DataType),
+}
+
+impl LogicalValue {
+    /// Arrow data type of this literal
+    pub fn data_type(&self) -> DataType {
+        match self {
+            LogicalValue::Int32(_) => DataType::Int32,
+            LogicalValue::Int64(_) => DataType::Int64,
+            LogicalValue::Float64(_) => DataType::Float64,
+            LogicalValue::String(_) => DataType::Utf8,
+            LogicalValue::Boolean(_) => DataType::Boolean,
+            LogicalValue::Date32(_) => DataType::Date32,
+            LogicalValue::TimestampMicros(_) => DataType::Timestamp(TimeUnit::Microsecond, None),
+            LogicalValue::Null(data_type) => data_type.clone(),
+        }
+    }
+}
+
+impl fmt::Display for LogicalValue {
+    /// Strings are single-quoted with embedded quotes doubled, as in SQL
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogicalValue::Int32(v) => write!(f, "{}", v),
+            LogicalValue::Int64(v) => write!(f, "{}", v),
+            LogicalValue::Float64(v) => write!(f, "{:?}", v),
+            LogicalValue::String(v) => write!(f, "'{}'", v.replace('\'', "''")),
+            LogicalValue::Boolean(v) => write!(f, "{}", v),
+            LogicalValue::Date32(v) => match date32_to_datetime(*v) {
+                Some(date) => write!(f, "DATE '{}'", date.date()),
+                None => write!(f, "DATE {}", v),
+            },
+            LogicalValue::TimestampMicros(v) => match timestamp_us_to_datetime(*v) {
+                Some(timestamp) => write!(f, "TIMESTAMP '{}'", timestamp),
+                None => write!(f, "TIMESTAMP {}", v),
+            },
+            LogicalValue::Null(_) => write!(f, "NULL"),
+        }
+    }
 }
 
 /// Aggregate function for GROUP BY aggregations
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AggregateFunction {
     Count,
     Sum,
     Avg,
     Min,
     Max,
+    /// Exact median. Buffers every non-null value of each group, so memory grows with
+    /// the input rather than the number of groups.
+    Median,
+    /// Exact percentile in [0, 1], interpolating between the closest ranks.
+    /// Buffers values like Median.
+    Percentile(f64),
 }
 
 /// An aggregation expression: function, optional column (None for Count(*)), and output alias
@@ -59,6 +433,112 @@ pub struct Aggregation {
     pub function: AggregateFunction,
     pub column: Option<String>,
     pub alias: String,
+    /// Only rows where this predicate is true feed the aggregation, as in SQL
+    /// `SUM(x) FILTER (WHERE y > 0)`; rows where it is false or null are skipped
+    pub filter: Option<LogicalExpr>,
+}
+
+impl Aggregation {
+    /// COUNT(*) - count all rows
+    pub fn count_star(alias: &str) -> Self {
+        Self::new(AggregateFunction::Count, None, alias)
+    }
+
+    /// COUNT(column) - count non-null values
+    pub fn count(column: &str, alias: &str) -> Self {
+        Self::new(AggregateFunction::Count, Some(column), alias)
+    }
+
+    /// SUM(column), null when the column has no non-null values
+    pub fn sum(column: &str, alias: &str) -> Self {
+        Self::new(AggregateFunction::Sum, Some(column), alias)
+    }
+
+    /// AVG(column)
+    pub fn avg(column: &str, alias: &str) -> Self {
+        Self::new(AggregateFunction::Avg, Some(column), alias)
+    }
+
+    /// MIN(column)
+    pub fn min(column: &str, alias: &str) -> Self {
+        Self::new(AggregateFunction::Min, Some(column), alias)
+    }
+
+    /// MAX(column)
+    pub fn max(column: &str, alias: &str) -> Self {
+        Self::new(AggregateFunction::Max, Some(column), alias)
+    }
+
+    /// MEDIAN(column)
+    pub fn median(column: &str, alias: &str) -> Self {
+        Self::new(AggregateFunction::Median, Some(column), alias)
+    }
+
+    /// PERCENTILE(column, p) with p in [0, 1], e.g. 0.95 for p95
+    pub fn percentile(column: &str, p: f64, alias: &str) -> Self {
+        Self::new(AggregateFunction::Percentile(p), Some(column), alias)
+    }
+
+    fn new(function: AggregateFunction, column: Option<&str>, alias: &str) -> Self {
+        Self {
+            function,
+            column: column.map(str::to_string),
+            alias: alias.to_string(),
+            filter: None,
+        }
+    }
+
+    /// Aggregate only the rows where `predicate` is true (a FILTER clause)
+    pub fn with_filter(mut self, predicate: LogicalExpr) -> Self {
+        self.filter = Some(predicate);
+        self
+    }
+
+    /// Check that the function and column fit together: functions other than COUNT need a
+    /// column, COUNT works with (COUNT(col)) or without (COUNT(*)) one, the alias is
+    /// non-empty and a percentile lies in [0, 1]
+    pub fn validate(&self) -> Result<(), String> {
+        if self.alias.is_empty() {
+            return Err(format!("{:?} aggregation requires an alias", self.function));
+        }
+        if let AggregateFunction::Percentile(p) = self.function {
+            if !(0.0..=1.0).contains(&p) {
+                return Err(format!(
+                    "Percentile aggregation '{}' needs a fraction in [0, 1], got {}",
+                    self.alias, p
+                ));
+            }
+        }
+        match (self.function, &self.column) {
+            (AggregateFunction::Count, _) => Ok(()),
+            (function, None) => Err(format!(
+                "{:?} aggregation '{}' requires a column",
+                function, self.alias
+            )),
+            (_, Some(column)) if column.is_empty() => Err(format!(
+                "{:?} aggregation '{}' has an empty column name",
+                self.function, self.alias
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Window function evaluated over the ordered rows of each partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFunction {
+    /// ROW_NUMBER(): 1-based position of the row within its partition
+    RowNumber,
+    /// Running SUM(column) from the start of the partition up to the current row
+    RunningSum,
+}
+
+/// A window expression: function, optional column (None for RowNumber), and output alias
+#[derive(Debug, Clone)]
+pub struct WindowExpr {
+    pub function: WindowFunction,
+    pub column: Option<String>,
+    pub alias: String,
 }
 
 /// Logical query plan representing a query as a tree of operations
@@ -69,22 +549,42 @@ pub enum LogicalPlan {
         path: PathBuf,
         projection: Option<Vec<String>>, // Column names to read
         filters: Vec<LogicalExpr>,       // Predicate pushdown filters
+        options: ScanOptions,
+    },
+    /// Constant rows given inline, one value per schema field in each row
+    Values {
+        schema: SchemaRef,
+        rows: Vec<Vec<LogicalValue>>,
     },
     /// Select/project specific columns
     Project {
         input: Box<LogicalPlan>,
         columns: Vec<String>, // Column names to select
     },
+    /// Compute expressions as output columns, named by their aliases
+    ProjectExprs {
+        input: Box<LogicalPlan>,
+        exprs: Vec<LogicalExpr>,
+    },
     /// Filter rows based on a predicate
     Filter {
         input: Box<LogicalPlan>,
         predicate: LogicalExpr,
     },
+    /// Filter rows with a closure called once per row (see `DataFrame::filter_fn`)
+    FilterFn {
+        input: Box<LogicalPlan>,
+        predicate: RowPredicate,
+    },
     /// Aggregate with GROUP BY
     Aggregate {
         input: Box<LogicalPlan>,
-        group_by: Vec<String>,
+        group_by: Vec<LogicalExpr>,
         aggs: Vec<Aggregation>,
+        /// Emit groups sorted by these group columns (empty: in first-seen order)
+        order_by: Vec<OrderByExpr>,
+        /// Expected number of groups, to pre-size the group table (never changes the result)
+        estimated_groups: Option<usize>,
     },
     /// ORDER BY
     Sort {
@@ -97,14 +597,164 @@ pub enum LogicalPlan {
         right: Box<LogicalPlan>,
         join_type: JoinType,
         on: (String, String), // (left_key, right_key)
+        /// Hint that the right side is small: build its hash table once and probe left
+        /// batches one at a time instead of materializing the whole left side
+        broadcast_right: bool,
+    },
+    /// Window functions: FUNC() OVER (PARTITION BY ... ORDER BY ...), appended as new columns
+    Window {
+        input: Box<LogicalPlan>,
+        partition_by: Vec<String>,
+        order_by: Vec<OrderByExpr>,
+        functions: Vec<WindowExpr>,
+    },
+    /// Replace nulls with a constant, in one column or (None) every column of the value's type
+    FillNull {
+        input: Box<LogicalPlan>,
+        column: Option<String>,
+        value: ScalarValue,
+    },
+    /// Set operation over two plans with compatible schemas
+    SetOperation {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        op: SetOperation,
+    },
+    /// Rename columns (old name -> new name), applied in one pass
+    Rename {
+        input: Box<LogicalPlan>,
+        renames: HashMap<String, String>,
+    },
+    /// Qualify every column with a table alias, `name` becoming `alias.name`, so the
+    /// two sides of a self-join can be told apart
+    Alias {
+        input: Box<LogicalPlan>,
+        alias: String,
+    },
+    /// Keep at most `limit` rows of the input
+    Limit {
+        input: Box<LogicalPlan>,
+        limit: usize,
+    },
+    /// Append an Int64 column numbering the rows 0..n in input order
+    RowIndex {
+        input: Box<LogicalPlan>,
+        name: String,
+    },
+    /// One row per input row and value column: the id columns, `variable` (the value
+    /// column's name) and `value`
+    Unpivot {
+        input: Box<LogicalPlan>,
+        id_columns: Vec<String>,
+        value_columns: Vec<String>,
     },
 }
 
-/// Join type: Inner or Left (outer)
+/// Function deciding whether the given row of a batch is kept
+pub type RowPredicateFn = dyn Fn(&RecordBatch, usize) -> bool + Send + Sync;
+
+/// Closure deciding whether the given row of a batch is kept
+#[derive(Clone)]
+pub struct RowPredicate(pub Arc<RowPredicateFn>);
+
+impl fmt::Debug for RowPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RowPredicate(<closure>)")
+    }
+}
+
+/// Join type: Inner, Left (outer), Semi or Anti
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JoinType {
     Inner,
     Left,
+    /// Left rows with at least one match (EXISTS), each once; only left columns
+    Semi,
+    /// Left rows with no match (NOT EXISTS); only left columns. A null key never
+    /// matches, so its row is kept.
+    Anti,
+}
+
+/// Options controlling how a Scan reads its files
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Number of rows per batch read from the files (default: DEFAULT_BATCH_SIZE)
+    pub batch_size: usize,
+    /// Under a limit, read row groups in file order and stop early so the first N rows
+    /// are returned (default: true). When false, a limit returns some N rows.
+    pub preserve_order: bool,
+    /// Files to leave unread, e.g. those handled by an earlier run over a directory that
+    /// has since grown. Paths are matched as listed from the scan path (default: none).
+    pub skip_files: HashSet<PathBuf>,
+    /// Without a projection, leave out columns of unsupported types (with a warning)
+    /// rather than failing the read (default: false)
+    pub skip_unsupported_columns: bool,
+    /// Read top-level struct columns as one `parent.field` column per field
+    /// (default: false)
+    pub flatten_structs: bool,
+    /// Read the files as this schema instead of their own: every file must have its
+    /// columns, in types that cast to it (default: None, the files' common schema)
+    pub schema: Option<SchemaRef>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+            preserve_order: true,
+            skip_files: HashSet::new(),
+            skip_unsupported_columns: false,
+            flatten_structs: false,
+            schema: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Set the number of rows per batch
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set whether a limit over this scan keeps file order
+    pub fn with_preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+
+    /// Set the already-processed files to skip, so only new files are read
+    pub fn with_skip_files(mut self, skip_files: HashSet<PathBuf>) -> Self {
+        self.skip_files = skip_files;
+        self
+    }
+
+    /// Set whether unsupported columns are dropped instead of failing the read
+    pub fn with_skip_unsupported_columns(mut self, skip: bool) -> Self {
+        self.skip_unsupported_columns = skip;
+        self
+    }
+
+    /// Set whether struct columns are flattened into `parent.field` columns
+    pub fn with_flatten_structs(mut self, flatten_structs: bool) -> Self {
+        self.flatten_structs = flatten_structs;
+        self
+    }
+
+    /// Set an explicit schema to read the files as, skipping their own
+    pub fn with_schema(mut self, schema: SchemaRef) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+}
+
+/// Set operation, with set (distinct) semantics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperation {
+    /// Rows present in both inputs
+    Intersect,
+    /// Rows of the left input not present in the right
+    Except,
 }
 
 /// Expression for ORDER BY: column name and direction
@@ -123,6 +773,7 @@ impl LogicalPlan {
                 // This will be handled during execution
                 Err("Schema not available for Scan without execution".to_string())
             }
+            LogicalPlan::Values { schema, .. } => Ok(schema.clone()),
             LogicalPlan::Project { input, columns } => {
                 let input_schema = input.schema()?;
                 let fields: Vec<_> = columns
@@ -133,12 +784,20 @@ impl LogicalPlan {
                             .iter()
                             .find(|f| f.name() == name)
                             .ok_or_else(|| format!("Column '{}' not found in schema", name))
-                            .map(|f| f.clone())
+                            .cloned()
                     })
                     .collect::<Result<_, _>>()?;
                 Ok(Arc::new(arrow::datatypes::Schema::new(fields)))
             }
-            LogicalPlan::Filter { input, .. } => {
+            LogicalPlan::ProjectExprs { input, exprs } => {
+                let input_schema = input.schema()?;
+                let fields = exprs
+                    .iter()
+                    .map(|e| e.to_field(&input_schema))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Arc::new(Schema::new(fields)))
+            }
+            LogicalPlan::Filter { input, .. } | LogicalPlan::FilterFn { input, .. } => {
                 // Filter doesn't change schema
                 input.schema()
             }
@@ -153,6 +812,337 @@ impl LogicalPlan {
             LogicalPlan::Join { .. } => {
                 Err("Schema not available for Join without execution".to_string())
             }
+            LogicalPlan::Window { .. } => {
+                // Schema is computed during execution based on input + window functions
+                Err("Schema not available for Window without execution".to_string())
+            }
+            LogicalPlan::FillNull { .. } => {
+                // Filled columns become non-nullable, which is resolved during planning
+                Err("Schema not available for FillNull without execution".to_string())
+            }
+            LogicalPlan::SetOperation { left, .. } => {
+                // Output uses the left input's schema
+                left.schema()
+            }
+            LogicalPlan::Rename { .. } => {
+                // Renames are validated against the input schema during planning
+                Err("Schema not available for Rename without execution".to_string())
+            }
+            LogicalPlan::Alias { input, alias } => {
+                let fields: Vec<_> = input
+                    .schema()?
+                    .fields()
+                    .iter()
+                    .map(|f| f.as_ref().clone().with_name(format!("{}.{}", alias, f.name())))
+                    .collect();
+                Ok(Arc::new(Schema::new(fields)))
+            }
+            LogicalPlan::Limit { input, .. } => {
+                // Limit doesn't change schema
+                input.schema()
+            }
+            LogicalPlan::RowIndex { input, name } => {
+                let input_schema = input.schema()?;
+                let mut fields: Vec<Field> =
+                    input_schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+                fields.push(Field::new(name, DataType::Int64, false));
+                Ok(Arc::new(Schema::new(fields)))
+            }
+            LogicalPlan::Unpivot { .. } => {
+                // The value column's type is resolved from the input during planning
+                Err("Schema not available for Unpivot without execution".to_string())
+            }
         }
     }
 }
+
+impl fmt::Display for LogicalPlan {
+    /// Render as an indented tree, one node per line, inputs below their parent
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl LogicalPlan {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        fn list<T: fmt::Display>(items: &[T]) -> String {
+            items.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ")
+        }
+        fn order(order_by: &[OrderByExpr]) -> String {
+            let keys: Vec<String> = order_by
+                .iter()
+                .map(|o| format!("{} {}", o.column, if o.ascending { "ASC" } else { "DESC" }))
+                .collect();
+            keys.join(", ")
+        }
+
+        write!(f, "{:indent$}", "", indent = depth * 2)?;
+        let inputs: Vec<&LogicalPlan> = match self {
+            LogicalPlan::Scan {
+                path,
+                projection,
+                filters,
+                ..
+            } => {
+                write!(f, "Scan: {}", path.display())?;
+                if let Some(columns) = projection {
+                    write!(f, " projection=[{}]", list(columns))?;
+                }
+                if !filters.is_empty() {
+                    write!(f, " filters=[{}]", list(filters))?;
+                }
+                vec![]
+            }
+            LogicalPlan::Values { schema, rows } => {
+                let names: Vec<&String> = schema.fields().iter().map(|f| f.name()).collect();
+                write!(f, "Values: {} rows of [{}]", rows.len(), list(&names))?;
+                vec![]
+            }
+            LogicalPlan::Project { input, columns } => {
+                write!(f, "Project: {}", list(columns))?;
+                vec![input]
+            }
+            LogicalPlan::ProjectExprs { input, exprs } => {
+                write!(f, "Project: {}", list(exprs))?;
+                vec![input]
+            }
+            LogicalPlan::Filter { input, predicate } => {
+                write!(f, "Filter: {}", predicate)?;
+                vec![input]
+            }
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggs,
+                order_by,
+                ..
+            } => {
+                let aggs: Vec<String> = aggs
+                    .iter()
+                    .map(|a| {
+                        let function = match a.function {
+                            AggregateFunction::Percentile(p) => format!("PERCENTILE_{}", p),
+                            ref other => format!("{:?}", other).to_uppercase(),
+                        };
+                        let column = a.column.as_deref().unwrap_or("*");
+                        match &a.filter {
+                            Some(filter) => format!(
+                                "{}({}) FILTER (WHERE {}) AS {}",
+                                function, column, filter, a.alias
+                            ),
+                            None => format!("{}({}) AS {}", function, column, a.alias),
+                        }
+                    })
+                    .collect();
+                write!(f, "Aggregate: group_by=[{}] aggs=[{}]", list(group_by), list(&aggs))?;
+                if !order_by.is_empty() {
+                    write!(f, " order_by=[{}]", order(order_by))?;
+                }
+                vec![input]
+            }
+            LogicalPlan::Sort { input, order_by } => {
+                write!(f, "Sort: {}", order(order_by))?;
+                vec![input]
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type,
+                on: (left_key, right_key),
+                broadcast_right,
+            } => {
+                write!(f, "Join: {:?} on {} = {}", join_type, left_key, right_key)?;
+                if *broadcast_right {
+                    write!(f, " (broadcast right)")?;
+                }
+                vec![left, right]
+            }
+            LogicalPlan::Window {
+                input,
+                partition_by,
+                order_by,
+                functions,
+            } => {
+                let functions: Vec<String> = functions
+                    .iter()
+                    .map(|w| {
+                        let column = w.column.as_deref().unwrap_or("");
+                        format!("{:?}({}) AS {}", w.function, column, w.alias)
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "Window: {} partition_by=[{}] order_by=[{}]",
+                    list(&functions),
+                    list(partition_by),
+                    order(order_by)
+                )?;
+                vec![input]
+            }
+            LogicalPlan::FillNull {
+                input,
+                column,
+                value,
+            } => {
+                let column = column.as_deref().unwrap_or("*");
+                write!(f, "FillNull: {} = {}", column, value)?;
+                vec![input]
+            }
+            LogicalPlan::SetOperation { left, right, op } => {
+                write!(f, "{:?}", op)?;
+                vec![left, right]
+            }
+            LogicalPlan::Rename { input, renames } => {
+                let mut renames: Vec<String> = renames
+                    .iter()
+                    .map(|(old, new)| format!("{} -> {}", old, new))
+                    .collect();
+                renames.sort();
+                write!(f, "Rename: {}", renames.join(", "))?;
+                vec![input]
+            }
+            LogicalPlan::Alias { input, alias } => {
+                write!(f, "Alias: {}", alias)?;
+                vec![input]
+            }
+            LogicalPlan::Limit { input, limit } => {
+                write!(f, "Limit: {}", limit)?;
+                vec![input]
+            }
+            LogicalPlan::FilterFn { input, .. } => {
+                write!(f, "FilterFn: <closure>")?;
+                vec![input]
+            }
+            LogicalPlan::RowIndex { input, name } => {
+                write!(f, "RowIndex: {}", name)?;
+                vec![input]
+            }
+            LogicalPlan::Unpivot {
+                input,
+                id_columns,
+                value_columns,
+            } => {
+                write!(f, "Unpivot: ids=[{}] values=[{}]", list(id_columns), list(value_columns))?;
+                vec![input]
+            }
+        };
+        for input in inputs {
+            writeln!(f)?;
+            input.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(name: &str) -> LogicalExpr {
+        LogicalExpr::Column(name.to_string())
+    }
+
+    fn binary(left: LogicalExpr, op: BinaryOp, right: LogicalExpr) -> LogicalExpr {
+        LogicalExpr::BinaryExpr {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    fn int(v: i32) -> LogicalExpr {
+        LogicalExpr::Literal(LogicalValue::Int32(v))
+    }
+
+    #[test]
+    fn test_display_comparisons_and_literals() {
+        assert_eq!(binary(col("age"), BinaryOp::Gt, int(18)).to_string(), "age > 18");
+        let name = binary(
+            col("name"),
+            BinaryOp::Eq,
+            LogicalExpr::Literal(LogicalValue::String("O'Brien".to_string())),
+        );
+        assert_eq!(name.to_string(), "name = 'O''Brien'");
+        let price = binary(
+            col("price"),
+            BinaryOp::Le,
+            LogicalExpr::Literal(LogicalValue::Float64(10.0)),
+        );
+        assert_eq!(price.to_string(), "price <= 10.0");
+        assert_eq!(
+            LogicalExpr::IsNotNull(Box::new(col("email"))).to_string(),
+            "email IS NOT NULL"
+        );
+    }
+
+    #[test]
+    fn test_display_precedence() {
+        let a = binary(col("a"), BinaryOp::Eq, int(1));
+        let b = binary(col("b"), BinaryOp::Eq, int(2));
+        let c = binary(col("c"), BinaryOp::Eq, int(3));
+
+        // AND binds tighter than OR, so no parentheses are needed here
+        let and_b = binary(a.clone(), BinaryOp::And, b.clone());
+        let and_in_or = binary(and_b, BinaryOp::Or, c.clone());
+        assert_eq!(and_in_or.to_string(), "a = 1 AND b = 2 OR c = 3");
+
+        // OR under AND must be parenthesized
+        let or_b = binary(a.clone(), BinaryOp::Or, b.clone());
+        let or_in_and = binary(or_b, BinaryOp::And, c.clone());
+        assert_eq!(or_in_and.to_string(), "(a = 1 OR b = 2) AND c = 3");
+
+        // Right-nested operators of equal precedence keep their grouping
+        let right_nested = binary(a, BinaryOp::And, binary(b, BinaryOp::And, c));
+        assert_eq!(right_nested.to_string(), "a = 1 AND (b = 2 AND c = 3)");
+
+        let is_null = LogicalExpr::IsNull(Box::new(binary(col("x"), BinaryOp::Gt, int(0))));
+        assert_eq!(is_null.to_string(), "(x > 0) IS NULL");
+    }
+
+    #[test]
+    fn test_display_in_list() {
+        let not_in = LogicalExpr::InList {
+            expr: Box::new(binary(col("a"), BinaryOp::Plus, int(1))),
+            list: vec![ScalarValue::Utf8("x".to_string()), ScalarValue::Null],
+            negated: true,
+        };
+        assert_eq!(not_in.to_string(), "a + 1 NOT IN ('x', NULL)");
+        let and = binary(not_in, BinaryOp::And, col("b"));
+        assert_eq!(and.to_string(), "a + 1 NOT IN ('x', NULL) AND b");
+    }
+
+    #[test]
+    fn test_display_plan_tree() {
+        let scan = |path: &str| LogicalPlan::Scan {
+            path: PathBuf::from(path),
+            projection: Some(vec!["id".to_string()]),
+            filters: vec![],
+            options: ScanOptions::default(),
+        };
+        let join = LogicalPlan::Join {
+            left: Box::new(scan("a.parquet")),
+            right: Box::new(scan("b.parquet")),
+            join_type: JoinType::Left,
+            on: ("id".to_string(), "id".to_string()),
+            broadcast_right: true,
+        };
+        let plan = LogicalPlan::Limit {
+            input: Box::new(LogicalPlan::Aggregate {
+                input: Box::new(join),
+                group_by: vec![col("id")],
+                aggs: vec![Aggregation::count_star("n")],
+                order_by: vec![],
+                estimated_groups: None,
+            }),
+            limit: 5,
+        };
+        assert_eq!(
+            plan.to_string(),
+            "Limit: 5\n\
+             \x20 Aggregate: group_by=[id] aggs=[COUNT(*) AS n]\n\
+             \x20   Join: Left on id = id (broadcast right)\n\
+             \x20     Scan: a.parquet projection=[id]\n\
+             \x20     Scan: b.parquet projection=[id]"
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub mod logical_plan;
 pub mod optimizer;
+pub mod sql;
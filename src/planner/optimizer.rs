@@ -1 +1,458 @@
 // Query optimization (predicate pushdown, etc.)
+
+use crate::execution::Executor;
+use crate::planner::logical_plan::{BinaryOp, JoinType, LogicalExpr, LogicalPlan};
+use crate::storage::parquet_reader::list_parquet_files;
+use crate::storage::schema_cache::SchemaCache;
+use std::collections::HashSet;
+
+/// Rewrite a plan into an equivalent one that should run faster.
+/// Moves filters below projections and into the scans below them, lets aggregates emit
+/// groups already sorted in place of a sort on their group columns, reorders chains of
+/// inner joins so the smallest estimated inputs are joined first, and removes redundant
+/// projections; the result has the same rows and columns (in the same order) as the
+/// input plan. A filter on a column that a projection dropped, but that exists below it,
+/// is applied before the projection, so such a plan runs once optimized.
+pub fn optimize(plan: &LogicalPlan) -> Result<LogicalPlan, String> {
+    optimize_with(plan, &Executor::new())
+}
+
+/// `optimize` for a plan that `executor` will run: input schemas and row estimates come
+/// from its Parquet schema cache, so each file's footer is read at most once, and
+/// column names are resolved as it resolves them (see `ExecutorConfig::case_insensitive`)
+pub fn optimize_with(plan: &LogicalPlan, executor: &Executor) -> Result<LogicalPlan, String> {
+    let plan = map_children(plan, |input| optimize_with(input, executor))?;
+    let plan = filter_below_project(plan, executor)?;
+    let plan = push_down_filter(plan);
+    let plan = sort_in_aggregate(plan);
+    let plan = reorder_joins(&plan, executor)?;
+    eliminate_projections(&plan, executor)
+}
+
+/// Estimated number of output rows of a plan: Parquet row counts scaled by assumed
+/// filter selectivities. Inner joins are assumed to be key/foreign-key joins, so they
+/// produce at most as many rows as their smaller input. File footers are read through
+/// `cache`. Returns None if a file's metadata cannot be read.
+pub fn estimate_rows(plan: &LogicalPlan, cache: &SchemaCache) -> Option<f64> {
+    match plan {
+        LogicalPlan::Scan {
+            path,
+            filters,
+            options,
+            ..
+        } => {
+            let mut rows = 0;
+            let files = list_parquet_files(path).ok()?;
+            for file in files.iter().filter(|f| !options.skip_files.contains(*f)) {
+                let metadata = cache.metadata(file).ok()?;
+                rows += metadata.metadata().file_metadata().num_rows();
+            }
+            Some(rows as f64 * filters.iter().map(selectivity).product::<f64>())
+        }
+        LogicalPlan::Values { rows, .. } => Some(rows.len() as f64),
+        LogicalPlan::Filter { input, predicate } => {
+            Some(estimate_rows(input, cache)? * selectivity(predicate))
+        }
+        // Nothing is known about the closure, so as for an unknown predicate
+        LogicalPlan::FilterFn { input, .. } => Some(estimate_rows(input, cache)? * 0.5),
+        LogicalPlan::Project { input, .. }
+        | LogicalPlan::ProjectExprs { input, .. }
+        | LogicalPlan::Aggregate { input, .. }
+        | LogicalPlan::Sort { input, .. }
+        | LogicalPlan::Window { input, .. }
+        | LogicalPlan::FillNull { input, .. }
+        | LogicalPlan::Rename { input, .. }
+        | LogicalPlan::Alias { input, .. }
+        | LogicalPlan::RowIndex { input, .. } => estimate_rows(input, cache),
+        LogicalPlan::Limit { input, limit } => {
+            Some(estimate_rows(input, cache)?.min(*limit as f64))
+        }
+        LogicalPlan::Unpivot {
+            input,
+            value_columns,
+            ..
+        } => Some(estimate_rows(input, cache)? * value_columns.len() as f64),
+        LogicalPlan::Join {
+            left,
+            right,
+            join_type,
+            ..
+        } => {
+            let (left, right) = (estimate_rows(left, cache)?, estimate_rows(right, cache)?);
+            Some(match join_type {
+                JoinType::Inner => left.min(right),
+                JoinType::Left | JoinType::Anti => left,
+                JoinType::Semi => left.min(right),
+            })
+        }
+        LogicalPlan::SetOperation { left, .. } => estimate_rows(left, cache),
+    }
+}
+
+/// Assumed fraction of rows a predicate keeps
+pub(crate) fn selectivity(predicate: &LogicalExpr) -> f64 {
+    match predicate {
+        LogicalExpr::BinaryExpr { left, op, right } => match op {
+            BinaryOp::Eq => 0.1,
+            BinaryOp::Neq => 0.9,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => 1.0 / 3.0,
+            BinaryOp::And => selectivity(left) * selectivity(right),
+            BinaryOp::Or => {
+                let (l, r) = (selectivity(left), selectivity(right));
+                l + r - l * r
+            }
+            _ => 0.5,
+        },
+        LogicalExpr::IsNull(_) => 0.1,
+        LogicalExpr::IsNotNull(_) => 0.9,
+        LogicalExpr::InList { list, negated, .. } => {
+            let s = (0.1 * list.len() as f64).min(1.0);
+            if *negated {
+                1.0 - s
+            } else {
+                s
+            }
+        }
+        LogicalExpr::Alias { expr, .. } => selectivity(expr),
+        _ => 0.5,
+    }
+}
+
+/// Swap a filter directly over a column projection when every column the predicate reads
+/// exists below the projection, including columns the projection drops. Selecting
+/// columns does not change rows, so filtering first gives the same result.
+fn filter_below_project(plan: LogicalPlan, executor: &Executor) -> Result<LogicalPlan, String> {
+    let LogicalPlan::Filter { input, predicate } = plan else {
+        return Ok(plan);
+    };
+    let LogicalPlan::Project { input, columns } = *input else {
+        return Ok(LogicalPlan::Filter { input, predicate });
+    };
+    let schema = executor.create_physical_plan(&input)?.schema();
+    let exists = |c: &String| {
+        let column = executor.resolve(c, &schema);
+        column.is_ok_and(|column| schema.index_of(&column).is_ok())
+    };
+    if predicate.column_names().iter().all(exists) {
+        let filter = LogicalPlan::Filter { input, predicate };
+        return Ok(LogicalPlan::Project {
+            input: Box::new(push_down_filter(filter)),
+            columns,
+        });
+    }
+    Ok(LogicalPlan::Filter {
+        input: Box::new(LogicalPlan::Project { input, columns }),
+        predicate,
+    })
+}
+
+/// Merge a filter directly over a scan into the scan's pushed-down filters, where it can
+/// also skip files and row groups
+fn push_down_filter(plan: LogicalPlan) -> LogicalPlan {
+    match plan {
+        LogicalPlan::Filter { input, predicate } => match *input {
+            LogicalPlan::Scan {
+                path,
+                projection,
+                mut filters,
+                options,
+            } => {
+                filters.push(predicate);
+                LogicalPlan::Scan {
+                    path,
+                    projection,
+                    filters,
+                    options,
+                }
+            }
+            input => LogicalPlan::Filter {
+                input: Box::new(input),
+                predicate,
+            },
+        },
+        other => other,
+    }
+}
+
+/// Replace a sort directly over an aggregate, on group columns only, with the aggregate
+/// emitting its groups in that order (it holds every group anyway)
+fn sort_in_aggregate(plan: LogicalPlan) -> LogicalPlan {
+    let LogicalPlan::Sort { input, order_by } = plan else {
+        return plan;
+    };
+    match *input {
+        LogicalPlan::Aggregate {
+            input,
+            group_by,
+            aggs,
+            order_by: existing,
+            estimated_groups,
+        } if existing.is_empty()
+            && order_by.iter().all(|o| group_by.iter().any(|g| g.output_name() == o.column)) =>
+        {
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggs,
+                order_by,
+                estimated_groups,
+            }
+        }
+        input => LogicalPlan::Sort {
+            input: Box::new(input),
+            order_by,
+        },
+    }
+}
+
+/// Collapse `Project(Project(x))` into one projection of `x`, and drop a projection that
+/// selects exactly its input's columns in order. Children must already be simplified.
+fn eliminate_projections(plan: &LogicalPlan, executor: &Executor) -> Result<LogicalPlan, String> {
+    let LogicalPlan::Project { input, columns } = plan else {
+        return Ok(plan.clone());
+    };
+    // The outer column list alone decides the output, if it only uses inner columns
+    if let LogicalPlan::Project {
+        input: inner_input,
+        columns: inner_columns,
+    } = input.as_ref()
+    {
+        if columns.iter().all(|c| inner_columns.contains(c)) {
+            let collapsed = LogicalPlan::Project {
+                input: inner_input.clone(),
+                columns: columns.clone(),
+            };
+            return eliminate_projections(&collapsed, executor);
+        }
+    }
+
+    let input_schema = executor.create_physical_plan(input)?.schema();
+    let is_identity = input_schema.fields().len() == columns.len()
+        && input_schema
+            .fields()
+            .iter()
+            .zip(columns)
+            .all(|(field, column)| {
+                executor.resolve(column, &input_schema).as_ref() == Ok(field.name())
+            });
+    if is_identity {
+        return Ok(input.as_ref().clone());
+    }
+    Ok(plan.clone())
+}
+
+/// Apply `f` to each direct child of `plan`, rebuilding the node around the results
+pub(crate) fn map_children(
+    plan: &LogicalPlan,
+    f: impl Fn(&LogicalPlan) -> Result<LogicalPlan, String>,
+) -> Result<LogicalPlan, String> {
+    let child = |input: &LogicalPlan| f(input).map(Box::new);
+    Ok(match plan {
+        LogicalPlan::Scan { .. } | LogicalPlan::Values { .. } => plan.clone(),
+        LogicalPlan::Project { input, columns } => LogicalPlan::Project {
+            input: child(input)?,
+            columns: columns.clone(),
+        },
+        LogicalPlan::ProjectExprs { input, exprs } => LogicalPlan::ProjectExprs {
+            input: child(input)?,
+            exprs: exprs.clone(),
+        },
+        LogicalPlan::Filter { input, predicate } => LogicalPlan::Filter {
+            input: child(input)?,
+            predicate: predicate.clone(),
+        },
+        LogicalPlan::FilterFn { input, predicate } => LogicalPlan::FilterFn {
+            input: child(input)?,
+            predicate: predicate.clone(),
+        },
+        LogicalPlan::Aggregate {
+            input,
+            group_by,
+            aggs,
+            order_by,
+            estimated_groups,
+        } => LogicalPlan::Aggregate {
+            input: child(input)?,
+            group_by: group_by.clone(),
+            aggs: aggs.clone(),
+            order_by: order_by.clone(),
+            estimated_groups: *estimated_groups,
+        },
+        LogicalPlan::Sort { input, order_by } => LogicalPlan::Sort {
+            input: child(input)?,
+            order_by: order_by.clone(),
+        },
+        LogicalPlan::Join {
+            left,
+            right,
+            join_type,
+            on,
+            broadcast_right,
+        } => LogicalPlan::Join {
+            left: child(left)?,
+            right: child(right)?,
+            join_type: *join_type,
+            on: on.clone(),
+            broadcast_right: *broadcast_right,
+        },
+        LogicalPlan::Window {
+            input,
+            partition_by,
+            order_by,
+            functions,
+        } => LogicalPlan::Window {
+            input: child(input)?,
+            partition_by: partition_by.clone(),
+            order_by: order_by.clone(),
+            functions: functions.clone(),
+        },
+        LogicalPlan::FillNull {
+            input,
+            column,
+            value,
+        } => LogicalPlan::FillNull {
+            input: child(input)?,
+            column: column.clone(),
+            value: value.clone(),
+        },
+        LogicalPlan::SetOperation { left, right, op } => LogicalPlan::SetOperation {
+            left: child(left)?,
+            right: child(right)?,
+            op: *op,
+        },
+        LogicalPlan::Rename { input, renames } => LogicalPlan::Rename {
+            input: child(input)?,
+            renames: renames.clone(),
+        },
+        LogicalPlan::Alias { input, alias } => LogicalPlan::Alias {
+            input: child(input)?,
+            alias: alias.clone(),
+        },
+        LogicalPlan::Limit { input, limit } => LogicalPlan::Limit {
+            input: child(input)?,
+            limit: *limit,
+        },
+        LogicalPlan::RowIndex { input, name } => LogicalPlan::RowIndex {
+            input: child(input)?,
+            name: name.clone(),
+        },
+        LogicalPlan::Unpivot {
+            input,
+            id_columns,
+            value_columns,
+        } => LogicalPlan::Unpivot {
+            input: child(input)?,
+            id_columns: id_columns.clone(),
+            value_columns: value_columns.clone(),
+        },
+    })
+}
+
+/// Flatten a left-deep chain of plain inner joins into its inputs (in order) and join keys
+fn flatten_inner_joins(
+    plan: &LogicalPlan,
+    inputs: &mut Vec<LogicalPlan>,
+    keys: &mut Vec<(String, String)>,
+) {
+    match plan {
+        LogicalPlan::Join {
+            left,
+            right,
+            join_type: JoinType::Inner,
+            on,
+            broadcast_right: false,
+        } => {
+            flatten_inner_joins(left, inputs, keys);
+            inputs.push(right.as_ref().clone());
+            keys.push(on.clone());
+        }
+        other => inputs.push(other.clone()),
+    }
+}
+
+/// Reorder a chain of three or more inner joins greedily: start from the input with the
+/// fewest estimated rows, then repeatedly join the connected input that keeps the
+/// intermediate result smallest. Conservative: the plan is left unchanged when column
+/// names are not unique across inputs, when a key cannot be traced to exactly one input,
+/// or when an estimate is unavailable. A projection restores the original column order.
+fn reorder_joins(plan: &LogicalPlan, executor: &Executor) -> Result<LogicalPlan, String> {
+    let mut inputs = Vec::new();
+    let mut keys = Vec::new();
+    flatten_inner_joins(plan, &mut inputs, &mut keys);
+    if inputs.len() < 3 {
+        return Ok(plan.clone());
+    }
+
+    // Column names of each input; they must be unique across the chain
+    let mut columns: Vec<Vec<String>> = Vec::new();
+    let mut seen = HashSet::new();
+    for input in &inputs {
+        let schema = executor.create_physical_plan(input)?.schema();
+        let names: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
+        if !names.iter().all(|name| seen.insert(name.clone())) {
+            return Ok(plan.clone());
+        }
+        columns.push(names);
+    }
+    let owner = |name: &str| columns.iter().position(|names| names.iter().any(|n| n == name));
+
+    // Each key pair becomes an edge between the two inputs it references
+    let mut edges = Vec::new();
+    for (left_key, right_key) in &keys {
+        match (owner(left_key), owner(right_key)) {
+            (Some(l), Some(r)) if l != r => {
+                edges.push((l, r, left_key.clone(), right_key.clone()))
+            }
+            _ => return Ok(plan.clone()),
+        }
+    }
+    let cache = executor.schema_cache();
+    let estimates = inputs.iter().map(|input| estimate_rows(input, cache));
+    let Some(estimates) = estimates.collect::<Option<Vec<f64>>>() else {
+        return Ok(plan.clone());
+    };
+
+    // Greedy order. Under the key/foreign-key assumption of `estimate_rows` each join
+    // keeps at most the smaller side, so joining the smallest connected input next
+    // keeps every intermediate result as small as possible.
+    let first = (0..inputs.len())
+        .min_by(|&a, &b| estimates[a].total_cmp(&estimates[b]))
+        .unwrap_or(0);
+    let mut order = vec![first];
+    let mut joined: Vec<(String, String)> = Vec::new();
+    while order.len() < inputs.len() {
+        // Edges from the joined set to an input not yet joined, oriented (joined, new)
+        let candidates = edges.iter().filter_map(|(l, r, lk, rk)| {
+            match (order.contains(l), order.contains(r)) {
+                (true, false) => Some((*r, lk.clone(), rk.clone())),
+                (false, true) => Some((*l, rk.clone(), lk.clone())),
+                _ => None,
+            }
+        });
+        let Some((next, left_key, right_key)) = candidates
+            .min_by(|a, b| estimates[a.0].total_cmp(&estimates[b.0]))
+        else {
+            return Ok(plan.clone());
+        };
+        order.push(next);
+        joined.push((left_key, right_key));
+    }
+    if order.iter().enumerate().all(|(i, &input)| i == input) {
+        return Ok(plan.clone());
+    }
+
+    let mut reordered = inputs[order[0]].clone();
+    for (&input, on) in order[1..].iter().zip(joined) {
+        reordered = LogicalPlan::Join {
+            left: Box::new(reordered),
+            right: Box::new(inputs[input].clone()),
+            join_type: JoinType::Inner,
+            on,
+            broadcast_right: false,
+        };
+    }
+    Ok(LogicalPlan::Project {
+        input: Box::new(reordered),
+        columns: columns.concat(),
+    })
+}
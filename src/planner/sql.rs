@@ -0,0 +1,752 @@
+// SQL subset parser
+
+use crate::dataframe::{DataFrame, ExprBuilder};
+use crate::planner::logical_plan::{
+    Aggregation, BinaryOp, LogicalExpr, LogicalValue, OrderByExpr,
+};
+use crate::types::ScalarValue;
+
+/// Parse a query in a small SQL subset into a DataFrame (nothing is read until it runs):
+///
+/// ```text
+/// SELECT <* | item [AS name], ...> FROM '<parquet path>'
+///     [WHERE <expr>] [GROUP BY col, ...] [ORDER BY col [ASC | DESC], ...] [LIMIT n]
+/// ```
+///
+/// Items are expressions over columns and literals (integers, floats, `'strings'`,
+/// TRUE / FALSE) with the engine's operators (`=`, `!=` / `<>`, `<`, `<=`, `>`, `>=`,
+/// AND, OR, `+`, `-`, `*`, `/`, `%`, `||`, IS [NOT] NULL, [NOT] IN (...)), or
+/// aggregates: COUNT(*), COUNT(col), SUM, AVG, MIN, MAX, MEDIAN(col). Keywords are
+/// case-insensitive; identifiers may be double-quoted. ORDER BY may name output columns
+/// or, without GROUP BY, input columns that are not selected. `SELECT *` cannot be
+/// combined with GROUP BY.
+///
+/// ```
+/// use mini_query_engine::planner::sql::parse_sql;
+///
+/// let df = parse_sql("SELECT id, name FROM 'people.parquet' WHERE age > 30 ORDER BY name");
+/// assert!(df.is_ok());
+/// ```
+pub fn parse_sql(query: &str) -> Result<DataFrame, String> {
+    let tokens = tokenize(query)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let query = parser.query()?;
+    query.into_dataframe()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Bare word: an identifier or keyword
+    Word(String),
+    /// Double-quoted identifier, never a keyword
+    QuotedIdent(String),
+    /// Single-quoted string
+    Str(String),
+    Number(String),
+    /// Operator or punctuation
+    Symbol(&'static str),
+    End,
+}
+
+/// Symbols, longest first so `<=` is not read as `<`
+const SYMBOLS: [&str; 16] = [
+    "<=", ">=", "<>", "!=", "||", "=", "<", ">", "+", "-", "*", "/", "%", "(", ")", ",",
+];
+
+/// Split a query into tokens, each with its byte offset (for error messages)
+fn tokenize(query: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let bytes = query.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let token = if c.is_ascii_alphabetic() || c == '_' {
+            while i < bytes.len() && is_word_char(bytes[i] as char) {
+                i += 1;
+            }
+            Token::Word(query[start..i].to_string())
+        } else if c.is_ascii_digit() {
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            Token::Number(query[start..i].to_string())
+        } else if c == '\'' || c == '"' {
+            let (text, end) = quoted(query, start)?;
+            i = end;
+            if c == '\'' {
+                Token::Str(text)
+            } else {
+                Token::QuotedIdent(text)
+            }
+        } else if let Some(symbol) = SYMBOLS.iter().find(|s| query[i..].starts_with(*s)) {
+            i += symbol.len();
+            Token::Symbol(symbol)
+        } else {
+            return Err(format!("Unexpected character '{}' at position {}", c, start));
+        };
+        tokens.push((token, start));
+    }
+    tokens.push((Token::End, query.len()));
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+/// Text of the quoted token starting at `start` (a doubled quote stands for one quote),
+/// and the offset just past its closing quote
+fn quoted(query: &str, start: usize) -> Result<(String, usize), String> {
+    let quote = query.as_bytes()[start] as char;
+    let mut text = String::new();
+    let mut chars = query[start + 1..].char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        if c != quote {
+            text.push(c);
+        } else if chars.peek().is_some_and(|&(_, next)| next == quote) {
+            text.push(quote);
+            chars.next();
+        } else {
+            return Ok((text, start + 1 + offset + 1));
+        }
+    }
+    Err(format!("Unterminated quote starting at position {}", start))
+}
+
+/// One item of the SELECT list
+enum SelectItem {
+    Expr(LogicalExpr),
+    Aggregate(Aggregation),
+}
+
+/// A parsed query, before it is turned into a plan
+struct Query {
+    /// None for `SELECT *`
+    select: Option<Vec<SelectItem>>,
+    from: String,
+    filter: Option<LogicalExpr>,
+    group_by: Vec<String>,
+    order_by: Vec<OrderByExpr>,
+    limit: Option<usize>,
+}
+
+impl Query {
+    /// Build the plan: scan, filter, aggregate or sort-then-project, then limit
+    fn into_dataframe(self) -> Result<DataFrame, String> {
+        let mut df = DataFrame::from_parquet(&self.from)?;
+        if let Some(predicate) = self.filter {
+            df = df.filter(predicate);
+        }
+        let items = self.select.unwrap_or_default();
+        let has_aggregates = items.iter().any(|i| matches!(i, SelectItem::Aggregate(_)));
+
+        if has_aggregates || !self.group_by.is_empty() {
+            let mut aggs = Vec::new();
+            let mut output = Vec::new();
+            for item in items {
+                match item {
+                    SelectItem::Aggregate(agg) => {
+                        output.push(agg.alias.clone());
+                        aggs.push(agg);
+                    }
+                    SelectItem::Expr(LogicalExpr::Column(name))
+                        if self.group_by.contains(&name) =>
+                    {
+                        output.push(name)
+                    }
+                    SelectItem::Expr(expr) => {
+                        return Err(format!(
+                            "'{}' must be an aggregate or a GROUP BY column",
+                            expr
+                        ))
+                    }
+                }
+            }
+            // The aggregate produces the group columns, then the aggregates
+            let natural: Vec<String> = self
+                .group_by
+                .iter()
+                .cloned()
+                .chain(aggs.iter().map(|a| a.alias.clone()))
+                .collect();
+            df = df.group_by(self.group_by).agg(aggs);
+            if !self.order_by.is_empty() {
+                df = df.order_by(self.order_by);
+            }
+            if output != natural {
+                df = df.select(output);
+            }
+        } else if items.is_empty() {
+            if !self.order_by.is_empty() {
+                df = df.order_by(self.order_by);
+            }
+        } else {
+            let exprs: Vec<LogicalExpr> = items
+                .into_iter()
+                .map(|item| match item {
+                    SelectItem::Expr(expr) => expr,
+                    SelectItem::Aggregate(_) => unreachable!("aggregates are handled above"),
+                })
+                .collect();
+            let names: Vec<String> = exprs.iter().map(|e| e.output_name()).collect();
+            // Sort on the output when it has every sort column, else on the input
+            let sort_output = self.order_by.iter().all(|o| names.contains(&o.column));
+            if !sort_output {
+                df = df.order_by(self.order_by.clone());
+            }
+            df = if exprs.iter().all(|e| matches!(e, LogicalExpr::Column(_))) {
+                df.select(names)
+            } else {
+                df.select_exprs(exprs)
+            };
+            if sort_output && !self.order_by.is_empty() {
+                df = df.order_by(self.order_by);
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            df = df.limit(limit);
+        }
+        Ok(df)
+    }
+}
+
+/// Recursive-descent parser over the token list
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Error naming what was expected and what was found, with its position
+    fn error<T>(&self, expected: &str) -> Result<T, String> {
+        let (token, position) = &self.tokens[self.pos];
+        let found = match token {
+            Token::Word(w) => format!("'{}'", w),
+            Token::QuotedIdent(s) => format!("\"{}\"", s),
+            Token::Str(s) => format!("'{}'", s),
+            Token::Number(n) => n.clone(),
+            Token::Symbol(s) => format!("'{}'", s),
+            Token::End => "end of query".to_string(),
+        };
+        Err(format!("Expected {}, found {} at position {}", expected, found, position))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Consume `keyword` if it is next
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            self.error(keyword)
+        }
+    }
+
+    /// Consume `symbol` if it is next
+    fn symbol(&mut self, symbol: &str) -> bool {
+        let found = *self.peek() == Token::Symbol(symbol_str(symbol));
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.symbol(symbol) {
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", symbol))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        match self.peek().clone() {
+            Token::Word(w) if !is_reserved(&w) => {
+                self.advance();
+                Ok(w)
+            }
+            Token::QuotedIdent(name) => {
+                self.advance();
+                Ok(name)
+            }
+            _ => self.error("a column name"),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, String> {
+        self.expect_keyword("SELECT")?;
+        let select = if self.symbol("*") {
+            None
+        } else {
+            let mut items = vec![self.select_item()?];
+            while self.symbol(",") {
+                items.push(self.select_item()?);
+            }
+            Some(items)
+        };
+
+        self.expect_keyword("FROM")?;
+        let from = match self.peek().clone() {
+            Token::Str(path) => path,
+            _ => return self.error("a quoted file path"),
+        };
+        self.advance();
+
+        let filter = if self.keyword("WHERE") {
+            Some(self.expr()?)
+        } else {
+            None
+        };
+
+        let mut group_by = Vec::new();
+        if select.is_none() && self.is_keyword("GROUP") {
+            return self.error("ORDER BY, LIMIT or end of query after SELECT *");
+        }
+        if self.keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            group_by.push(self.identifier()?);
+            while self.symbol(",") {
+                group_by.push(self.identifier()?);
+            }
+        }
+
+        let mut order_by = Vec::new();
+        if self.keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            loop {
+                let column = self.identifier()?;
+                let ascending = !self.keyword("DESC");
+                if ascending {
+                    self.keyword("ASC");
+                }
+                order_by.push(OrderByExpr { column, ascending });
+                if !self.symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let limit = if self.keyword("LIMIT") {
+            let limit = match self.peek() {
+                Token::Number(n) => n.parse::<usize>().ok(),
+                _ => None,
+            };
+            if limit.is_none() {
+                return self.error("a row count");
+            }
+            self.advance();
+            limit
+        } else {
+            None
+        };
+
+        if *self.peek() != Token::End {
+            return self.error("end of query");
+        }
+        Ok(Query {
+            select,
+            from,
+            filter,
+            group_by,
+            order_by,
+            limit,
+        })
+    }
+
+    fn select_item(&mut self) -> Result<SelectItem, String> {
+        if let Some(agg) = self.aggregate()? {
+            let alias = self.alias()?;
+            return Ok(SelectItem::Aggregate(match alias {
+                Some(alias) => Aggregation { alias, ..agg },
+                None => agg,
+            }));
+        }
+        let expr = self.expr()?;
+        Ok(SelectItem::Expr(match self.alias()? {
+            Some(name) => expr.alias(&name),
+            None => expr,
+        }))
+    }
+
+    fn alias(&mut self) -> Result<Option<String>, String> {
+        if self.keyword("AS") {
+            Ok(Some(self.identifier()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// An aggregate call such as `SUM(amount)`, named like `sum(amount)` unless aliased,
+    /// with an optional `FILTER (WHERE predicate)`
+    fn aggregate(&mut self) -> Result<Option<Aggregation>, String> {
+        let Token::Word(name) = self.peek().clone() else {
+            return Ok(None);
+        };
+        let function = name.to_ascii_lowercase();
+        let is_call = self.tokens[self.pos + 1].0 == Token::Symbol("(");
+        let known = ["count", "sum", "avg", "min", "max", "median"];
+        if !is_call || !known.contains(&function.as_str()) {
+            return Ok(None);
+        }
+        self.advance();
+        self.expect_symbol("(")?;
+        let agg = if function == "count" && self.symbol("*") {
+            self.expect_symbol(")")?;
+            Aggregation::count_star("count(*)")
+        } else {
+            let column = self.identifier()?;
+            self.expect_symbol(")")?;
+            let alias = format!("{}({})", function, column);
+            match function.as_str() {
+                "count" => Aggregation::count(&column, &alias),
+                "sum" => Aggregation::sum(&column, &alias),
+                "avg" => Aggregation::avg(&column, &alias),
+                "min" => Aggregation::min(&column, &alias),
+                "max" => Aggregation::max(&column, &alias),
+                _ => Aggregation::median(&column, &alias),
+            }
+        };
+        // FILTER (WHERE predicate), told apart from an alias named filter by the (
+        if self.is_keyword("FILTER") && self.tokens[self.pos + 1].0 == Token::Symbol("(") {
+            self.advance();
+            self.expect_symbol("(")?;
+            self.expect_keyword("WHERE")?;
+            let predicate = self.expr()?;
+            self.expect_symbol(")")?;
+            return Ok(Some(agg.with_filter(predicate)));
+        }
+        Ok(Some(agg))
+    }
+
+    /// expr := and (OR and)*
+    fn expr(&mut self) -> Result<LogicalExpr, String> {
+        let mut left = self.conjunction()?;
+        while self.keyword("OR") {
+            left = binary(left, BinaryOp::Or, self.conjunction()?);
+        }
+        Ok(left)
+    }
+
+    /// and := comparison (AND comparison)*
+    fn conjunction(&mut self) -> Result<LogicalExpr, String> {
+        let mut left = self.comparison()?;
+        while self.keyword("AND") {
+            left = binary(left, BinaryOp::And, self.comparison()?);
+        }
+        Ok(left)
+    }
+
+    /// comparison := sum [op sum | IS [NOT] NULL | [NOT] IN (literal, ...)]
+    fn comparison(&mut self) -> Result<LogicalExpr, String> {
+        let left = self.sum()?;
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(if negated {
+                left.is_not_null()
+            } else {
+                left.is_null()
+            });
+        }
+        let negated = self.keyword("NOT");
+        if negated || self.is_keyword("IN") {
+            self.expect_keyword("IN")?;
+            self.expect_symbol("(")?;
+            let mut list = vec![self.scalar()?];
+            while self.symbol(",") {
+                list.push(self.scalar()?);
+            }
+            self.expect_symbol(")")?;
+            return Ok(if negated {
+                left.not_in(list)
+            } else {
+                left.in_list(list)
+            });
+        }
+        let op = match self.peek() {
+            Token::Symbol("=") => BinaryOp::Eq,
+            Token::Symbol("!=") | Token::Symbol("<>") => BinaryOp::Neq,
+            Token::Symbol("<") => BinaryOp::Lt,
+            Token::Symbol("<=") => BinaryOp::Le,
+            Token::Symbol(">") => BinaryOp::Gt,
+            Token::Symbol(">=") => BinaryOp::Ge,
+            _ => return Ok(left),
+        };
+        self.advance();
+        Ok(binary(left, op, self.sum()?))
+    }
+
+    /// sum := product ((+ | - | ||) product)*
+    fn sum(&mut self) -> Result<LogicalExpr, String> {
+        let mut left = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Token::Symbol("+") => BinaryOp::Plus,
+                Token::Symbol("-") => BinaryOp::Minus,
+                Token::Symbol("||") => BinaryOp::Concat,
+                _ => return Ok(left),
+            };
+            self.advance();
+            left = binary(left, op, self.product()?);
+        }
+    }
+
+    /// product := primary ((* | / | %) primary)*
+    fn product(&mut self) -> Result<LogicalExpr, String> {
+        let mut left = self.primary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Symbol("*") => BinaryOp::Multiply,
+                Token::Symbol("/") => BinaryOp::Divide,
+                Token::Symbol("%") => BinaryOp::Modulo,
+                _ => return Ok(left),
+            };
+            self.advance();
+            left = binary(left, op, self.primary()?);
+        }
+    }
+
+    /// primary := column | literal | ( expr )
+    fn primary(&mut self) -> Result<LogicalExpr, String> {
+        if self.symbol("(") {
+            let expr = self.expr()?;
+            self.expect_symbol(")")?;
+            return Ok(expr);
+        }
+        if matches!(self.peek(), Token::Word(_) | Token::QuotedIdent(_))
+            && !self.is_keyword("TRUE")
+            && !self.is_keyword("FALSE")
+            && !self.is_keyword("NULL")
+        {
+            return Ok(LogicalExpr::Column(self.identifier()?));
+        }
+        let start = self.pos;
+        let value = match self.scalar()? {
+            ScalarValue::Int32(v) => LogicalValue::Int32(v),
+            ScalarValue::Int64(v) => LogicalValue::Int64(v),
+            ScalarValue::Float64(v) => LogicalValue::Float64(v),
+            ScalarValue::Utf8(v) => LogicalValue::String(v),
+            ScalarValue::Boolean(v) => LogicalValue::Boolean(v),
+            ScalarValue::Date32(v) => LogicalValue::Date32(v),
+            ScalarValue::TimestampMicros(v) => LogicalValue::TimestampMicros(v),
+            ScalarValue::Null => {
+                // Point the error at the NULL literal (and its sign, if any)
+                self.pos = start;
+                return self.error("an expression (use IS NULL to test for nulls)");
+            }
+        };
+        Ok(LogicalExpr::Literal(value))
+    }
+
+    /// A literal: integer (Int32 if it fits, else Int64), float, string, TRUE, FALSE or
+    /// NULL, optionally negative
+    fn scalar(&mut self) -> Result<ScalarValue, String> {
+        let negative = self.symbol("-");
+        let value = match self.peek().clone() {
+            Token::Number(n) => {
+                let n = if negative { format!("-{}", n) } else { n };
+                if let Ok(v) = n.parse::<i32>() {
+                    ScalarValue::Int32(v)
+                } else if let Ok(v) = n.parse::<i64>() {
+                    ScalarValue::Int64(v)
+                } else if let Ok(v) = n.parse::<f64>() {
+                    ScalarValue::Float64(v)
+                } else {
+                    return self.error("a number");
+                }
+            }
+            _ if negative => return self.error("a number"),
+            Token::Str(s) => ScalarValue::Utf8(s),
+            Token::Word(w) if w.eq_ignore_ascii_case("TRUE") => ScalarValue::Boolean(true),
+            Token::Word(w) if w.eq_ignore_ascii_case("FALSE") => ScalarValue::Boolean(false),
+            Token::Word(w) if w.eq_ignore_ascii_case("NULL") => ScalarValue::Null,
+            _ => return self.error("a literal"),
+        };
+        self.advance();
+        Ok(value)
+    }
+}
+
+fn binary(left: LogicalExpr, op: BinaryOp, right: LogicalExpr) -> LogicalExpr {
+    LogicalExpr::BinaryExpr {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    }
+}
+
+/// The static form of a symbol, for comparing against tokens
+fn symbol_str(symbol: &str) -> &'static str {
+    SYMBOLS.iter().find(|s| **s == symbol).copied().unwrap_or("")
+}
+
+/// Keywords that cannot be used as bare column names (double-quote them instead)
+fn is_reserved(word: &str) -> bool {
+    const RESERVED: [&str; 19] = [
+        "SELECT", "FROM", "WHERE", "GROUP", "ORDER", "BY", "LIMIT", "AS", "AND", "OR", "NOT",
+        "IS", "NULL", "IN", "ASC", "DESC", "TRUE", "FALSE", "DISTINCT",
+    ];
+    RESERVED.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{asc, col, count, desc, lit_int32, lit_string, sum};
+
+    /// Plans compare by their debug form (LogicalPlan has no PartialEq)
+    fn assert_same_plan(query: &str, expected: DataFrame) {
+        let parsed = parse_sql(query).unwrap();
+        assert_eq!(format!("{:?}", parsed.plan()), format!("{:?}", expected.plan()));
+    }
+
+    fn scan() -> DataFrame {
+        DataFrame::from_parquet("data.parquet").unwrap()
+    }
+
+    #[test]
+    fn test_select_where_order_by() {
+        assert_same_plan(
+            "SELECT a, b FROM 'data.parquet' WHERE a > 1 ORDER BY b",
+            scan()
+                .filter(col("a").gt(lit_int32(1)))
+                .select(vec!["a".to_string(), "b".to_string()])
+                .order_by(vec![asc("b")]),
+        );
+        // Sorting on a column that is not selected happens before the projection
+        assert_same_plan(
+            "select a from 'data.parquet' order by b desc limit 5",
+            scan()
+                .order_by(vec![desc("b")])
+                .select(vec!["a".to_string()])
+                .limit(5),
+        );
+        assert_same_plan("SELECT * FROM 'data.parquet'", scan());
+    }
+
+    #[test]
+    fn test_expressions_and_precedence() {
+        let predicate = binary(
+            col("a").eq(lit_int32(1)),
+            BinaryOp::Or,
+            binary(
+                col("b").ge(lit_int32(-2)),
+                BinaryOp::And,
+                col("name").neq(lit_string("it's")),
+            ),
+        );
+        assert_same_plan(
+            "SELECT (a + b) * 2 AS total, \"select\" FROM 'data.parquet' \
+             WHERE a = 1 OR b >= -2 AND name <> 'it''s'",
+            scan().filter(predicate).select_exprs(vec![
+                col("a").plus(col("b")).multiply(lit_int32(2)).alias("total"),
+                col("select"),
+            ]),
+        );
+        assert_same_plan(
+            "SELECT a FROM 'data.parquet' WHERE b IS NOT NULL AND a NOT IN (1, NULL)",
+            scan()
+                .filter(binary(
+                    col("b").is_not_null(),
+                    BinaryOp::And,
+                    col("a").not_in(vec![ScalarValue::Int32(1), ScalarValue::Null]),
+                ))
+                .select(vec!["a".to_string()]),
+        );
+    }
+
+    #[test]
+    fn test_group_by() {
+        assert_same_plan(
+            "SELECT region, COUNT(*) AS n, SUM(amount) FROM 'data.parquet' \
+             GROUP BY region ORDER BY n DESC",
+            scan()
+                .group_by(vec!["region".to_string()])
+                .agg(vec![count("n"), sum("amount", "sum(amount)")])
+                .order_by(vec![desc("n")]),
+        );
+        // Output columns in select order
+        assert_same_plan(
+            "SELECT COUNT(*) AS n, region FROM 'data.parquet' GROUP BY region",
+            scan()
+                .group_by(vec!["region".to_string()])
+                .agg(vec![count("n")])
+                .select(vec!["n".to_string(), "region".to_string()]),
+        );
+    }
+
+    #[test]
+    fn test_aggregate_filter() {
+        assert_same_plan(
+            "SELECT region, SUM(amount) FILTER (WHERE amount > 0) AS gains, \
+             COUNT(*) FILTER (WHERE amount < 0) FROM 'data.parquet' GROUP BY region",
+            scan().group_by(vec!["region".to_string()]).agg(vec![
+                sum("amount", "gains").with_filter(col("amount").gt(lit_int32(0))),
+                count("count(*)").with_filter(col("amount").lt(lit_int32(0))),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |query: &str| parse_sql(query).unwrap_err();
+        assert_eq!(
+            err("SELECT a FROM data.parquet"),
+            "Expected a quoted file path, found 'data.parquet' at position 14"
+        );
+        assert!(err("SELECT a FROM 'x.parquet' WHERE").contains("end of query"));
+        assert!(err("SELECT a FROM 'x.parquet' LIMIT 1 2").contains("Expected end of query"));
+        assert!(err("SELECT a, COUNT(*) FROM 'x.parquet'").contains("GROUP BY column"));
+        assert!(err("SELECT a FROM 'x.parquet").contains("Unterminated quote"));
+
+        // Errors at the end of the query name the end, not the token before it
+        assert_eq!(
+            err("SELECT a FROM"),
+            "Expected a quoted file path, found end of query at position 13"
+        );
+        assert_eq!(
+            err("SELECT a FROM 'x.parquet' LIMIT"),
+            "Expected a row count, found end of query at position 31"
+        );
+        assert_eq!(
+            err("SELECT a FROM 'x.parquet' LIMIT -1"),
+            "Expected a row count, found '-' at position 32"
+        );
+        assert_eq!(
+            err("SELECT a FROM 'x.parquet' WHERE a = NULL"),
+            "Expected an expression (use IS NULL to test for nulls), found 'NULL' at position 36"
+        );
+        assert_eq!(
+            err("SELECT * FROM 'x.parquet' GROUP BY a"),
+            "Expected ORDER BY, LIMIT or end of query after SELECT *, found 'GROUP' at position 26"
+        );
+    }
+}
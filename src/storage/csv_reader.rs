@@ -0,0 +1,133 @@
+// CSV file reading
+
+use crate::storage::DEFAULT_BATCH_SIZE;
+use arrow::csv::reader::Format;
+use arrow::csv::ReaderBuilder;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch as ArrowRecordBatch;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Configuration for reading CSV files
+#[derive(Debug, Clone)]
+pub struct CsvReaderConfig {
+    /// Whether the first line is a header with column names (default: true)
+    pub has_header: bool,
+    /// Field delimiter (default: b',')
+    pub delimiter: u8,
+    /// Batch size for reading (default: DEFAULT_BATCH_SIZE)
+    pub batch_size: usize,
+    /// Number of records sampled from the start of the file to infer the schema (default: 1000).
+    /// The rest of the file is read with that fixed schema, so inference cost is bounded.
+    pub infer_rows: usize,
+}
+
+impl Default for CsvReaderConfig {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            delimiter: b',',
+            batch_size: DEFAULT_BATCH_SIZE,
+            infer_rows: 1000,
+        }
+    }
+}
+
+/// CSV reader that infers a schema from a sample of rows and reads into Arrow RecordBatches.
+///
+/// Inferred types are Boolean, Int64, Float64 or Utf8; columns that are empty throughout the
+/// sample, and date/time columns, are read as Utf8. Empty fields are nulls. Later rows are
+/// parsed with the inferred type: a value that doesn't fit (e.g. `abc` in an Int64 column,
+/// or `1.5` in an Int64 column) fails the read with an `InvalidData` error naming the value
+/// and line. It is never silently replaced with null. Raise `infer_rows` if files have
+/// such late-changing columns.
+pub struct CsvReader {
+    file_path: PathBuf,
+    config: CsvReaderConfig,
+}
+
+impl CsvReader {
+    /// Create a new CSV reader from a file path
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_path_with_config(path, CsvReaderConfig::default())
+    }
+
+    /// Create a new CSV reader from a file path with configuration
+    pub fn from_path_with_config<P: AsRef<Path>>(path: P, config: CsvReaderConfig) -> Result<Self> {
+        let file_path = path.as_ref().to_path_buf();
+        Ok(Self { file_path, config })
+    }
+
+    fn format(&self) -> Format {
+        Format::default()
+            .with_header(self.config.has_header)
+            .with_delimiter(self.config.delimiter)
+    }
+
+    /// Infer the Arrow schema from the first `infer_rows` records of the file
+    pub fn schema(&self) -> Result<Schema> {
+        let file = File::open(&self.file_path)?;
+        let (schema, _) = self
+            .format()
+            .infer_schema(file, Some(self.config.infer_rows))
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("CSV schema: {}", e)))?;
+
+        let fields: Vec<Field> = schema
+            .fields()
+            .iter()
+            .map(|f| {
+                let data_type = match f.data_type() {
+                    DataType::Boolean | DataType::Int64 | DataType::Float64 => f.data_type().clone(),
+                    _ => DataType::Utf8,
+                };
+                Field::new(f.name(), data_type, true)
+            })
+            .collect();
+        Ok(Schema::new(fields))
+    }
+
+    /// Read all records, using the schema inferred from the sample
+    pub fn read_all(&self) -> Result<Vec<ArrowRecordBatch>> {
+        let schema = Arc::new(self.schema()?);
+        let file = File::open(&self.file_path)?;
+        let reader = ReaderBuilder::new(schema)
+            .with_format(self.format())
+            .with_batch_size(self.config.batch_size)
+            .build(file)
+            .map_err(|e| Error::other(format!("CSV build: {}", e)))?;
+
+        reader.map(|b| b.map_err(read_error)).collect()
+    }
+}
+
+/// A decoding error as an I/O error: values that fail to parse as their inferred type
+/// are reported as not fitting the schema, anything else (ragged rows, I/O) as itself
+fn read_error(error: ArrowError) -> Error {
+    match error {
+        ArrowError::ParseError(_) | ArrowError::CastError(_) => Error::new(
+            ErrorKind::InvalidData,
+            format!("CSV value does not fit inferred schema: {}", error),
+        ),
+        ArrowError::IoError(_, error) => Error::new(error.kind(), format!("CSV read: {}", error)),
+        ArrowError::CsvError(_) => {
+            Error::new(ErrorKind::InvalidData, format!("Malformed CSV: {}", error))
+        }
+        error => Error::other(format!("CSV read: {}", error)),
+    }
+}
+
+/// Convenience function to read a CSV file into RecordBatches
+pub fn read_csv<P: AsRef<Path>>(path: P) -> Result<Vec<ArrowRecordBatch>> {
+    CsvReader::from_path(path)?.read_all()
+}
+
+/// Convenience function to read a CSV file with custom configuration
+pub fn read_csv_with_config<P: AsRef<Path>>(
+    path: P,
+    config: CsvReaderConfig,
+) -> Result<Vec<ArrowRecordBatch>> {
+    CsvReader::from_path_with_config(path, config)?.read_all()
+}
@@ -1,2 +1,7 @@
+pub mod csv_reader;
 pub mod parquet_reader;
 pub mod predicate_pushdown;
+pub mod schema_cache;
+
+/// Default number of rows per RecordBatch produced by the readers
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
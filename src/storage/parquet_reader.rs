@@ -1,24 +1,73 @@
 // Parquet file reading
 
-use arrow::datatypes::{DataType, Schema};
+use arrow::array::{make_array, Array, ArrayRef, StructArray};
+use arrow::buffer::NullBuffer;
+use arrow::compute::filter_record_batch;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::error::ArrowError;
 use arrow::record_batch::RecordBatch as ArrowRecordBatch;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use parquet::arrow::arrow_reader::{
+    ArrowPredicateFn, ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReader,
+    ParquetRecordBatchReaderBuilder, RowFilter,
+};
 use parquet::arrow::ProjectionMask;
+use parquet::basic::Compression;
+use parquet::file::metadata::ParquetMetaData;
+use parquet::file::reader::{ChunkReader, Length};
 use rayon::prelude::*;
+use crate::execution::batch::RecordBatch;
+use crate::execution::expression::{evaluate_predicate, evaluate_predicate_with};
+use crate::planner::logical_plan::{BinaryOp, LogicalExpr};
+use crate::storage::DEFAULT_BATCH_SIZE;
 use std::fs::File;
-use std::io::{Error, ErrorKind, Result};
+use std::io::{BufReader, Error, ErrorKind, Result, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Configuration for reading Parquet files
 #[derive(Debug, Clone)]
 pub struct ParquetReaderConfig {
     /// Whether to read row groups in parallel (default: true)
     pub parallel: bool,
-    /// Optional list of column indices to read (for column pruning)
+    /// Optional list of top-level column indices to read (for column pruning)
     /// If None, all columns are read
     pub column_indices: Option<Vec<usize>>,
-    /// Batch size for reading (default: 8192)
+    /// Batch size for reading (default: DEFAULT_BATCH_SIZE)
     pub batch_size: usize,
+    /// Optional list of row group indices to read (for row group skipping)
+    /// If None, all row groups are read
+    pub row_groups: Option<Vec<usize>>,
+    /// What to do when a parallel read fails because the process ran out of file handles
+    /// (default: Retry)
+    pub on_parallel_error: ParallelErrorPolicy,
+    /// Split each top-level struct column into one column per field, named
+    /// `parent.field` (default: false). Structs nested in structs are an error.
+    pub flatten_structs: bool,
+    /// Keep only rows matching this predicate (default: None). A simple predicate
+    /// (see `is_simple_predicate`) on top-level columns is evaluated while decoding, so
+    /// the other columns are decoded only for matching rows; any other predicate is
+    /// applied to the batches after they are read.
+    pub predicate: Option<LogicalExpr>,
+    /// Report integer overflow in the predicate's arithmetic as an error instead of
+    /// wrapping (default: false)
+    pub checked_arithmetic: bool,
+    /// Capacity of the buffer each column chunk is read through (default: None, std's
+    /// 8 KiB). Larger buffers mean fewer, larger reads, which can help on slow disks.
+    pub read_buffer_size: Option<usize>,
+}
+
+/// Handling of file-handle exhaustion during a parallel read, where each task opens
+/// the file separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParallelErrorPolicy {
+    /// Retry the whole read sequentially, on a single file handle
+    Retry,
+    /// Return the error
+    Propagate,
 }
 
 impl Default for ParquetReaderConfig {
@@ -26,16 +75,64 @@ impl Default for ParquetReaderConfig {
         Self {
             parallel: true,
             column_indices: None,
-            batch_size: 8192,
+            batch_size: DEFAULT_BATCH_SIZE,
+            row_groups: None,
+            on_parallel_error: ParallelErrorPolicy::Retry,
+            flatten_structs: false,
+            predicate: None,
+            checked_arithmetic: false,
+            read_buffer_size: None,
         }
     }
 }
 
+impl ParquetReaderConfig {
+    /// Set the number of rows per batch
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set how a parallel read that runs out of file handles is handled
+    pub fn with_on_parallel_error(mut self, policy: ParallelErrorPolicy) -> Self {
+        self.on_parallel_error = policy;
+        self
+    }
+
+    /// Set whether struct columns are flattened into `parent.field` columns
+    pub fn with_flatten_structs(mut self, flatten_structs: bool) -> Self {
+        self.flatten_structs = flatten_structs;
+        self
+    }
+
+    /// Keep only the rows matching `predicate`
+    pub fn with_predicate(mut self, predicate: LogicalExpr) -> Self {
+        self.predicate = Some(predicate);
+        self
+    }
+
+    /// Set whether integer overflow in the predicate is an error
+    pub fn with_checked_arithmetic(mut self, checked_arithmetic: bool) -> Self {
+        self.checked_arithmetic = checked_arithmetic;
+        self
+    }
+
+    /// Set the capacity of the buffer column chunks are read through
+    pub fn with_read_buffer_size(mut self, read_buffer_size: usize) -> Self {
+        self.read_buffer_size = Some(read_buffer_size);
+        self
+    }
+}
+
 /// Parquet reader that reads files into Arrow RecordBatches
 /// Uses parquet 50 API with ParquetRecordBatchReaderBuilder
 pub struct ParquetReader {
-    file_path: PathBuf,
+    file: GunzippedPath,
     config: ParquetReaderConfig,
+    /// Opens the file in each parallel task (replaced in tests to inject errors)
+    open_file: fn(&Path) -> Result<File>,
+    /// Footer metadata parsed earlier (see `with_metadata`), used instead of parsing it
+    metadata: Option<ArrowReaderMetadata>,
 }
 
 impl ParquetReader {
@@ -44,105 +141,152 @@ impl ParquetReader {
         Self::from_path_with_config(path, ParquetReaderConfig::default())
     }
 
-    /// Create a new Parquet reader from a file path with configuration
+    /// Create a new Parquet reader from a file path with configuration.
+    /// A `.gz` path is a gzip-wrapped Parquet file, read via `gunzipped_path`.
     pub fn from_path_with_config<P: AsRef<Path>>(
         path: P,
         config: ParquetReaderConfig,
     ) -> Result<Self> {
-        let file_path = path.as_ref().to_path_buf();
-        Ok(Self { file_path, config })
+        Ok(Self::from_gunzipped(gunzipped_path(path.as_ref())?, config))
+    }
+
+    /// Create a reader of a file already resolved by `gunzipped_path`, so a gzip-wrapped
+    /// file read several times is decompressed once
+    pub fn from_gunzipped(file: GunzippedPath, config: ParquetReaderConfig) -> Self {
+        Self {
+            file,
+            config,
+            open_file: |path| File::open(path),
+            metadata: None,
+        }
+    }
+
+    /// Use `metadata`, parsed earlier from this file's footer (e.g. by a `SchemaCache`),
+    /// rather than parsing the footer again
+    pub fn with_metadata(mut self, metadata: ArrowReaderMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// The file's footer metadata: as given to `with_metadata`, or parsed from the file
+    pub fn metadata(&self) -> Result<ArrowReaderMetadata> {
+        if let Some(ref metadata) = self.metadata {
+            return Ok(metadata.clone());
+        }
+        let file = File::open(self.file.path())?;
+        ArrowReaderMetadata::load(&file, ArrowReaderOptions::default())
+            .map_err(|e| Error::other(format!("Parquet: {}", e)))
     }
 
-    /// Get the Arrow schema from the Parquet file
+    /// Get the Arrow schema from the Parquet file, with dictionary-encoded columns
+    /// reported as their value type (see `decode_dictionary_schema`)
     pub fn schema(&self) -> Result<Schema> {
-        let file = File::open(&self.file_path)?;
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Parquet: {}", e)))?;
-        Ok(builder.schema().as_ref().clone())
+        Ok(decode_dictionary_schema(self.metadata()?.schema()))
     }
 
-    /// Read all data from the Parquet file into RecordBatches
-    /// If parallel is enabled, reads row groups in parallel
-    pub fn read_all(&self) -> Result<Vec<ArrowRecordBatch>> {
-        let file = File::open(&self.file_path)?;
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Parquet: {}", e)))?;
+    /// Number of row groups in the Parquet file
+    pub fn num_row_groups(&self) -> Result<usize> {
+        Ok(self.metadata()?.metadata().num_row_groups())
+    }
 
-        let num_row_groups = builder.metadata().num_row_groups();
+    /// Number of rows in the Parquet file, from its footer metadata
+    pub fn num_rows(&self) -> Result<usize> {
+        Ok(self.metadata()?.metadata().file_metadata().num_rows() as usize)
+    }
 
-        if num_row_groups == 0 {
+    /// Read all data from the Parquet file into RecordBatches
+    /// If parallel is enabled, reads row groups in parallel. Either way the batches come
+    /// back in row group order (as listed in `row_groups`), as a sequential read returns them.
+    pub fn read_all(&self) -> Result<Vec<ArrowRecordBatch>> {
+        let metadata = self.metadata()?;
+        let builder = open_builder(File::open(self.file.path())?, &self.config, &metadata);
+        let row_groups = self.row_groups(&builder)?;
+        if row_groups.is_empty() {
             return Ok(Vec::new());
         }
 
-        if self.config.parallel && num_row_groups > 1 {
-            self.read_all_parallel(num_row_groups)
+        if self.config.parallel && row_groups.len() > 1 {
+            match self.read_all_parallel(row_groups.clone(), &metadata) {
+                Err(e)
+                    if self.config.on_parallel_error == ParallelErrorPolicy::Retry
+                        && is_file_handle_exhaustion(&e) =>
+                {
+                    self.read_all_sequential(builder, row_groups)
+                }
+                result => result,
+            }
         } else {
-            self.read_all_sequential(builder)
+            self.read_all_sequential(builder, row_groups)
         }
     }
 
+    /// Iterate over the file's batches, decoding each one only when it is asked for, so
+    /// at most one batch is held at a time. Row groups are read one after another, each
+    /// with its own decoder, so batches split at row groups as a parallel `read_all`'s do.
+    pub fn batch_iter(&self) -> Result<impl Iterator<Item = Result<ArrowRecordBatch>>> {
+        let metadata = self.metadata()?;
+        let builder = open_builder(File::open(self.file.path())?, &self.config, &metadata);
+        let row_groups = self.row_groups(&builder)?;
+        // The iterator holds its own handle, so a decompressed copy outlives the reader
+        let file = self.file.clone();
+        let config = self.config.clone();
+        Ok(row_groups.into_iter().flat_map(move |i| {
+            let batches: Box<dyn Iterator<Item = Result<ArrowRecordBatch>>> =
+                match read_row_group(file.path(), &config, &metadata, i) {
+                    Ok(batches) => Box::new(batches),
+                    Err(e) => Box::new(std::iter::once(Err(e))),
+                };
+            batches
+        }))
+    }
+
+    /// Row groups to read (all unless configured), checked for supported compression
+    fn row_groups(
+        &self,
+        builder: &ParquetRecordBatchReaderBuilder<BufferedFile>,
+    ) -> Result<Vec<usize>> {
+        let row_groups: Vec<usize> = match self.config.row_groups {
+            Some(ref row_groups) => row_groups.clone(),
+            None => (0..builder.metadata().num_row_groups()).collect(),
+        };
+        check_compression(builder.metadata(), &row_groups)?;
+        Ok(row_groups)
+    }
+
     /// Read all row groups sequentially
     fn read_all_sequential(
         &self,
-        builder: ParquetRecordBatchReaderBuilder<File>,
+        builder: ParquetRecordBatchReaderBuilder<BufferedFile>,
+        row_groups: Vec<usize>,
     ) -> Result<Vec<ArrowRecordBatch>> {
-        let builder = if let Some(ref indices) = self.config.column_indices {
-            let mask = ProjectionMask::leaves(builder.parquet_schema(), indices.clone());
-            builder.with_projection(mask)
-        } else {
-            builder
-        };
-        let reader = builder
-            .with_batch_size(self.config.batch_size)
-            .build()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Parquet build: {}", e)))?;
-
+        let (reader, filtered) = build_reader(builder, &self.config, row_groups)?;
         let batches: Vec<ArrowRecordBatch> = reader
-            .map(|b| b.map_err(|e| Error::new(ErrorKind::Other, format!("Parquet read: {}", e))))
+            .map(|b| b.map_err(|e| Error::other(format!("Parquet read: {}", e))))
             .collect::<Result<Vec<_>>>()?;
-
-        let mut out = Vec::new();
-        for batch in batches {
-            out.push(validate_record_batch(batch)?);
-        }
-        Ok(out)
+        finish_batches(batches, &self.config, filtered)
     }
 
-    /// Read all row groups in parallel using Rayon
-    fn read_all_parallel(&self, num_row_groups: usize) -> Result<Vec<ArrowRecordBatch>> {
-        let file_path = self.file_path.clone();
-        let column_indices = self.config.column_indices.clone();
-        let batch_size = self.config.batch_size;
+    /// Read the given row groups in parallel using Rayon. The indexed `collect` keeps
+    /// each row group's batches at its position in `row_groups`, whatever order the
+    /// reads finish in. Each task opens the file itself but shares `metadata`.
+    fn read_all_parallel(
+        &self,
+        row_groups: Vec<usize>,
+        metadata: &ArrowReaderMetadata,
+    ) -> Result<Vec<ArrowRecordBatch>> {
+        let file_path = self.file.path();
+        let config = &self.config;
+        let open_file = self.open_file;
 
-        let batch_results: Vec<Result<Vec<ArrowRecordBatch>>> = (0..num_row_groups)
+        let batch_results: Vec<Result<Vec<ArrowRecordBatch>>> = row_groups
             .into_par_iter()
             .map(|i| {
-                let file = File::open(&file_path)?;
-                let b = ParquetRecordBatchReaderBuilder::try_new(file)
-                    .map_err(|e| Error::new(ErrorKind::Other, format!("Parquet: {}", e)))?;
-                let b = if let Some(ref ind) = column_indices {
-                    let mask = ProjectionMask::leaves(b.parquet_schema(), ind.clone());
-                    b.with_projection(mask)
-                } else {
-                    b
-                };
-                let r = b
-                    .with_row_groups(vec![i])
-                    .with_batch_size(batch_size)
-                    .build()
-                    .map_err(|e| Error::new(ErrorKind::Other, format!("Parquet build: {}", e)))?;
+                let b = open_builder(open_file(file_path)?, config, metadata);
+                let (r, filtered) = build_reader(b, config, vec![i])?;
                 let batches: Vec<ArrowRecordBatch> = r
-                    .map(|b| {
-                        b.map_err(|e| {
-                            Error::new(ErrorKind::Other, format!("Parquet read: {}", e))
-                        })
-                    })
+                    .map(|b| b.map_err(|e| Error::other(format!("Parquet read: {}", e))))
                     .collect::<Result<Vec<_>>>()?;
-                let validated: Result<Vec<_>> = batches
-                    .into_iter()
-                    .map(validate_record_batch)
-                    .collect();
-                validated
+                finish_batches(batches, config, filtered)
             })
             .collect();
 
@@ -155,6 +299,299 @@ impl ParquetReader {
     }
 }
 
+/// A file whose column chunks are read through a buffer of the configured capacity
+struct BufferedFile {
+    file: File,
+    buffer_size: Option<usize>,
+}
+
+impl Length for BufferedFile {
+    fn len(&self) -> u64 {
+        self.file.len()
+    }
+}
+
+impl ChunkReader for BufferedFile {
+    type T = BufReader<File>;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        let mut file = self.file.try_clone()?;
+        file.seek(SeekFrom::Start(start))?;
+        Ok(match self.buffer_size {
+            Some(capacity) => BufReader::with_capacity(capacity, file),
+            None => BufReader::new(file),
+        })
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        self.file.get_bytes(start, length)
+    }
+}
+
+/// Reader builder over `file`, whose footer was already parsed into `metadata`, read
+/// with the configured buffer size
+fn open_builder(
+    file: File,
+    config: &ParquetReaderConfig,
+    metadata: &ArrowReaderMetadata,
+) -> ParquetRecordBatchReaderBuilder<BufferedFile> {
+    let file = BufferedFile {
+        file,
+        buffer_size: config.read_buffer_size,
+    };
+    ParquetRecordBatchReaderBuilder::new_with_metadata(file, metadata.clone())
+}
+
+/// Build a reader over `row_groups` with the configured column pruning and batch size,
+/// pushing the predicate into the decoder when possible. Also returns whether the
+/// predicate was pushed down.
+fn build_reader(
+    builder: ParquetRecordBatchReaderBuilder<BufferedFile>,
+    config: &ParquetReaderConfig,
+    row_groups: Vec<usize>,
+) -> Result<(ParquetRecordBatchReader, bool)> {
+    let mut builder = builder
+        .with_row_groups(row_groups)
+        .with_batch_size(config.batch_size);
+    if let Some(ref indices) = config.column_indices {
+        let mask = ProjectionMask::roots(builder.parquet_schema(), indices.clone());
+        builder = builder.with_projection(mask);
+    }
+    let row_filter = config
+        .predicate
+        .as_ref()
+        .and_then(|predicate| row_filter(&builder, predicate));
+    let filtered = row_filter.is_some();
+    if let Some(row_filter) = row_filter {
+        builder = builder.with_row_filter(row_filter);
+    }
+    let reader = builder
+        .build()
+        .map_err(|e| Error::other(format!("Parquet build: {}", e)))?;
+    Ok((reader, filtered))
+}
+
+/// Lazily decoded batches of one row group, finished as by `finish_batches`
+fn read_row_group(
+    path: &Path,
+    config: &ParquetReaderConfig,
+    metadata: &ArrowReaderMetadata,
+    row_group: usize,
+) -> Result<impl Iterator<Item = Result<ArrowRecordBatch>>> {
+    let builder = open_builder(File::open(path)?, config, metadata);
+    let (reader, filtered) = build_reader(builder, config, vec![row_group])?;
+    let config = config.clone();
+    Ok(reader.filter_map(move |batch| {
+        batch
+            .map_err(|e| Error::other(format!("Parquet read: {}", e)))
+            .and_then(|batch| finish_batch(batch, &config, filtered))
+            .transpose()
+    }))
+}
+
+/// Translate a simple predicate on top-level columns of supported types into a row
+/// filter evaluated during decoding. None if the predicate must be applied after reading.
+fn row_filter(
+    builder: &ParquetRecordBatchReaderBuilder<BufferedFile>,
+    predicate: &LogicalExpr,
+) -> Option<RowFilter> {
+    if !is_simple_predicate(predicate) {
+        return None;
+    }
+    let schema = builder.schema();
+    let mut indices = Vec::new();
+    for name in predicate.column_names() {
+        let index = schema.index_of(&name).ok()?;
+        if !is_supported_type(schema.field(index).data_type()) {
+            return None;
+        }
+        indices.push(index);
+    }
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+    let predicate = predicate.clone();
+    let filter = ArrowPredicateFn::new(mask, move |batch| {
+        evaluate_predicate(&predicate, &RecordBatch::from_arrow(batch))
+            .map_err(ArrowError::ComputeError)
+    });
+    Some(RowFilter::new(vec![Box::new(filter)]))
+}
+
+/// Whether a predicate can be pushed into the decoder: comparisons between a column and
+/// a literal, `IS [NOT] NULL` and `IN` lists on a column, combined with AND / OR
+fn is_simple_predicate(predicate: &LogicalExpr) -> bool {
+    let is_column = |expr: &LogicalExpr| matches!(expr, LogicalExpr::Column(_));
+    match predicate {
+        LogicalExpr::BinaryExpr { left, op, right } => match op {
+            BinaryOp::And | BinaryOp::Or => {
+                is_simple_predicate(left) && is_simple_predicate(right)
+            }
+            BinaryOp::Eq
+            | BinaryOp::Neq
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => matches!(
+                (left.as_ref(), right.as_ref()),
+                (LogicalExpr::Column(_), LogicalExpr::Literal(_))
+                    | (LogicalExpr::Literal(_), LogicalExpr::Column(_))
+            ),
+            _ => false,
+        },
+        LogicalExpr::IsNull(expr)
+        | LogicalExpr::IsNotNull(expr)
+        | LogicalExpr::InList { expr, .. } => is_column(expr),
+        _ => false,
+    }
+}
+
+/// Prepare the batches read (see `prepare_batch`), then apply the predicate if it was
+/// not already applied during decoding, dropping batches left empty
+fn finish_batches(
+    batches: Vec<ArrowRecordBatch>,
+    config: &ParquetReaderConfig,
+    filtered: bool,
+) -> Result<Vec<ArrowRecordBatch>> {
+    let mut out = Vec::new();
+    for batch in batches {
+        out.extend(finish_batch(batch, config, filtered)?);
+    }
+    Ok(out)
+}
+
+/// `finish_batches` for one batch: None if no rows are left
+fn finish_batch(
+    batch: ArrowRecordBatch,
+    config: &ParquetReaderConfig,
+    filtered: bool,
+) -> Result<Option<ArrowRecordBatch>> {
+    let batch = prepare_batch(batch, config.flatten_structs)?;
+    let batch = match config.predicate {
+        Some(ref predicate) if !filtered => {
+            let input = RecordBatch::from_arrow(batch.clone());
+            let mask = evaluate_predicate_with(predicate, &input, config.checked_arithmetic)
+                .map_err(Error::other)?;
+            filter_record_batch(&batch, &mask)
+                .map_err(|e| Error::other(format!("Parquet filter: {}", e)))?
+        }
+        _ => batch,
+    };
+    Ok((batch.num_rows() > 0).then_some(batch))
+}
+
+/// Whether an error means the process or system ran out of file handles
+/// (EMFILE / ENFILE on Unix)
+fn is_file_handle_exhaustion(error: &Error) -> bool {
+    matches!(error.raw_os_error(), Some(23) | Some(24))
+}
+
+/// Decode dictionary columns and flatten struct columns if requested, then check the
+/// batch's types are supported
+fn prepare_batch(batch: ArrowRecordBatch, flatten_structs: bool) -> Result<ArrowRecordBatch> {
+    let batch = decode_dictionaries(batch)?;
+    if flatten_structs {
+        validate_record_batch(flatten_record_batch(&batch)?)
+    } else {
+        validate_record_batch(batch)
+    }
+}
+
+/// Schema with each top-level struct column replaced by its fields, named `parent.field`.
+/// A field is nullable if it or its struct is.
+pub fn flatten_schema(schema: &Schema) -> Result<Schema> {
+    let mut fields = Vec::new();
+    for field in schema.fields() {
+        let DataType::Struct(children) = field.data_type() else {
+            fields.push(field.as_ref().clone());
+            continue;
+        };
+        for child in children {
+            if matches!(child.data_type(), DataType::Struct(_)) {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!(
+                        "Nested struct '{}.{}' cannot be flattened",
+                        field.name(),
+                        child.name()
+                    ),
+                ));
+            }
+            fields.push(Field::new(
+                format!("{}.{}", field.name(), child.name()),
+                child.data_type().clone(),
+                field.is_nullable() || child.is_nullable(),
+            ));
+        }
+    }
+    Ok(Schema::new(fields))
+}
+
+/// Value type of a dictionary column the reader decodes to plain values: dictionaries
+/// of supported types (e.g. a dictionary-encoded string column reads as `Utf8`)
+fn decoded_type(data_type: &DataType) -> Option<&DataType> {
+    match data_type {
+        DataType::Dictionary(_, value) if is_supported_type(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Schema with each top-level dictionary column of a supported value type replaced by
+/// that type, so files writing the same column with and without a dictionary agree
+pub fn decode_dictionary_schema(schema: &Schema) -> Schema {
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| match decoded_type(field.data_type()) {
+            Some(value) => field.as_ref().clone().with_data_type(value.clone()),
+            None => field.as_ref().clone(),
+        })
+        .collect();
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// Cast dictionary columns to their value type (see `decode_dictionary_schema`)
+fn decode_dictionaries(batch: ArrowRecordBatch) -> Result<ArrowRecordBatch> {
+    let schema = batch.schema();
+    if !schema.fields().iter().any(|f| decoded_type(f.data_type()).is_some()) {
+        return Ok(batch);
+    }
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| match decoded_type(column.data_type()) {
+            Some(value) => arrow::compute::cast(column, value),
+            None => Ok(column.clone()),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::other(format!("Decode dictionary: {}", e)))?;
+    ArrowRecordBatch::try_new(Arc::new(decode_dictionary_schema(&schema)), columns)
+        .map_err(|e| Error::other(format!("Decode dictionary: {}", e)))
+}
+
+/// Split struct columns into their child arrays (see `flatten_schema`). A child row is
+/// null wherever its struct row is null.
+fn flatten_record_batch(batch: &ArrowRecordBatch) -> Result<ArrowRecordBatch> {
+    let schema = Arc::new(flatten_schema(&batch.schema())?);
+    let mut columns: Vec<ArrayRef> = Vec::new();
+    for column in batch.columns() {
+        let Some(structs) = column.as_any().downcast_ref::<StructArray>() else {
+            columns.push(column.clone());
+            continue;
+        };
+        for child in structs.columns() {
+            let nulls = NullBuffer::union(structs.nulls(), child.nulls());
+            let data = child
+                .to_data()
+                .into_builder()
+                .nulls(nulls)
+                .build()
+                .map_err(|e| Error::other(format!("Flatten struct: {}", e)))?;
+            columns.push(make_array(data));
+        }
+    }
+    ArrowRecordBatch::try_new(schema, columns)
+        .map_err(|e| Error::other(format!("Flatten struct: {}", e)))
+}
+
 /// Validate that a RecordBatch contains only supported data types
 fn validate_record_batch(batch: ArrowRecordBatch) -> Result<ArrowRecordBatch> {
     let schema = batch.schema();
@@ -173,8 +610,33 @@ fn validate_record_batch(batch: ArrowRecordBatch) -> Result<ArrowRecordBatch> {
     Ok(batch)
 }
 
+/// Fail early, naming the codec and column, if a row group to be read uses a compression
+/// codec the Parquet crate cannot decompress (it otherwise fails mid-read with a generic error)
+fn check_compression(metadata: &ParquetMetaData, row_groups: &[usize]) -> Result<()> {
+    for &i in row_groups {
+        for column in metadata.row_group(i).columns() {
+            if !is_supported_compression(column.compression()) {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!(
+                        "Unsupported compression codec: {} in column '{}'",
+                        column.compression(),
+                        column.column_path()
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check if a compression codec can be decompressed on read
+fn is_supported_compression(compression: Compression) -> bool {
+    !matches!(compression, Compression::LZO)
+}
+
 /// Check if a data type is supported
-fn is_supported_type(data_type: &DataType) -> bool {
+pub(crate) fn is_supported_type(data_type: &DataType) -> bool {
     matches!(
         data_type,
         DataType::Int32
@@ -183,9 +645,124 @@ fn is_supported_type(data_type: &DataType) -> bool {
             | DataType::Utf8
             | DataType::LargeUtf8
             | DataType::Boolean
+            | DataType::Date32
+            | DataType::Timestamp(_, _)
     )
 }
 
+/// A Parquet file to read, as resolved by `gunzipped_path`: the path given, or a
+/// decompressed copy that is deleted once the last clone of this is dropped
+#[derive(Debug, Clone)]
+pub struct GunzippedPath {
+    path: PathBuf,
+    _copy: Option<Arc<TempFile>>,
+}
+
+impl GunzippedPath {
+    /// The file to open
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// A temp file, removed when dropped
+#[derive(Debug)]
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// File to read for the Parquet file at `path`: `path` itself, or for a gzip-wrapped file
+/// (`*.gz`, e.g. `data.parquet.gz`) a decompressed copy in the system temp dir. Parquet
+/// needs random access, so the whole file is decompressed to disk rather than buffered
+/// in memory; this costs temp space equal to the uncompressed size while the copy is
+/// held. Each call makes a new copy, readable only by the current user.
+pub fn gunzipped_path(path: &Path) -> Result<GunzippedPath> {
+    if path.extension().is_none_or(|ext| ext != "gz") {
+        return Ok(GunzippedPath {
+            path: path.to_path_buf(),
+            _copy: None,
+        });
+    }
+    let (target, mut out) = create_private_temp_file()?;
+    // Removes the copy if decompressing fails
+    let copy = TempFile(target.clone());
+    let mut decoder = GzDecoder::new(BufReader::new(File::open(path)?));
+    std::io::copy(&mut decoder, &mut out).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Failed to decompress '{}': {}", path.display(), e),
+        )
+    })?;
+    Ok(GunzippedPath {
+        path: target,
+        _copy: Some(Arc::new(copy)),
+    })
+}
+
+/// Create a new file in the system temp dir, under a name no file had. It is created
+/// exclusively (never an existing file or symlink) and, on Unix, with mode 0600.
+fn create_private_temp_file() -> Result<(PathBuf, File)> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    loop {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        let name = format!(
+            "mqe_gunzip_{}_{}_{}.parquet",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed),
+            nanos
+        );
+        let target = std::env::temp_dir().join(name);
+        let mut options = File::options();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&target) {
+            Ok(file) => return Ok((target, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Resolve a scan path to the Parquet files it covers: the file itself, or every
+/// `*.parquet` and gzip-wrapped `*.parquet.gz` file directly inside a directory, sorted
+/// by name
+pub fn list_parquet_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|p| p.is_file() && is_parquet_file_name(p))
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("No Parquet files found in directory '{}'", path.display()),
+        ));
+    }
+    Ok(files)
+}
+
+/// Whether a path is named as a Parquet file, `*.parquet` or `*.parquet.gz`
+fn is_parquet_file_name(path: &Path) -> bool {
+    let path = match path.extension() {
+        Some(ext) if ext == "gz" => Path::new(path.file_stem().unwrap_or_default()),
+        _ => path,
+    };
+    path.extension().is_some_and(|ext| ext == "parquet")
+}
+
 /// Convenience function to read a Parquet file into RecordBatches
 pub fn read_parquet<P: AsRef<Path>>(path: P) -> Result<Vec<ArrowRecordBatch>> {
     let reader = ParquetReader::from_path(path)?;
@@ -200,3 +777,234 @@ pub fn read_parquet_with_config<P: AsRef<Path>>(
     let reader = ParquetReader::from_path_with_config(path, config)?;
     reader.read_all()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{col, lit_int32, ExprBuilder};
+    use arrow::array::Int32Array;
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+
+    /// Write a 300-row Int32 file with three row groups
+    fn write_test_file(name: &str) -> PathBuf {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        let batch = ArrowRecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from((0..300).collect::<Vec<i32>>()))],
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("{}_{}", std::process::id(), name));
+        let props = WriterProperties::builder().set_max_row_group_size(100).build();
+        let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, Some(props))
+            .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        path
+    }
+
+    fn read_ids(path: &Path, config: ParquetReaderConfig) -> Vec<i32> {
+        read_parquet_with_config(path, config)
+            .unwrap()
+            .iter()
+            .flat_map(|b| {
+                let ids = b.column(0).as_any().downcast_ref::<Int32Array>().unwrap();
+                ids.values().to_vec()
+            })
+            .collect()
+    }
+
+    fn exhausted_open(_: &Path) -> Result<File> {
+        Err(Error::from_raw_os_error(24))
+    }
+
+    #[test]
+    fn test_parallel_read_falls_back_to_sequential() {
+        let path = write_test_file("parallel_fallback.parquet");
+
+        let mut reader = ParquetReader::from_path(&path).unwrap();
+        reader.open_file = exhausted_open;
+        let batches = reader.read_all().unwrap();
+        let ids: Vec<i32> = batches
+            .iter()
+            .flat_map(|b| {
+                let ids = b.column(0).as_any().downcast_ref::<Int32Array>().unwrap();
+                ids.values().to_vec()
+            })
+            .collect();
+        assert_eq!(ids, (0..300).collect::<Vec<_>>());
+
+        let config = ParquetReaderConfig::default()
+            .with_on_parallel_error(ParallelErrorPolicy::Propagate);
+        let mut reader = ParquetReader::from_path_with_config(&path, config).unwrap();
+        reader.open_file = exhausted_open;
+        let err = reader.read_all().unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(24));
+    }
+
+    #[test]
+    fn test_parallel_read_keeps_row_group_order() {
+        let path = write_test_file("parallel_order.parquet");
+        let config = ParquetReaderConfig::default().with_batch_size(7);
+        let sequential = ParquetReaderConfig { parallel: false, ..config.clone() };
+        let expected = read_ids(&path, sequential);
+        assert_eq!(expected, (0..300).collect::<Vec<_>>());
+        for _ in 0..20 {
+            assert_eq!(read_ids(&path, config.clone()), expected);
+        }
+
+        let reversed = ParquetReaderConfig { row_groups: Some(vec![2, 1, 0]), ..config };
+        let ids = read_ids(&path, reversed);
+        assert_eq!(ids[..100], expected[200..]);
+        assert_eq!(ids[200..], expected[..100]);
+    }
+
+    #[test]
+    fn test_other_parallel_errors_propagate() {
+        let path = write_test_file("parallel_error.parquet");
+        let mut reader = ParquetReader::from_path(&path).unwrap();
+        reader.open_file = |_| Err(Error::new(ErrorKind::PermissionDenied, "denied"));
+        let err = reader.read_all().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn test_batch_iter_matches_read_all() {
+        let path = write_test_file("batch_iter.parquet");
+        let config = ParquetReaderConfig::default()
+            .with_batch_size(7)
+            .with_predicate(col("id").ge(lit_int32(50)));
+        let reader = ParquetReader::from_path_with_config(&path, config).unwrap();
+        let expected = reader.read_all().unwrap();
+        let streamed = reader.batch_iter().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        let rows = |batches: &[ArrowRecordBatch]| -> usize {
+            batches.iter().map(|b| b.num_rows()).sum()
+        };
+        assert_eq!(rows(&streamed), 250);
+        assert_eq!(rows(&streamed), rows(&expected));
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_gunzipped_copy_is_private_and_removed() {
+        let plain = write_test_file("gunzip_source.parquet");
+        let gz = plain.with_extension("parquet.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&gz).unwrap(),
+            flate2::Compression::default(),
+        );
+        std::io::copy(&mut File::open(&plain).unwrap(), &mut encoder).unwrap();
+        encoder.finish().unwrap();
+
+        // Each call makes its own copy, never reusing a file found under a known name
+        let first = gunzipped_path(&gz).unwrap();
+        let second = gunzipped_path(&gz).unwrap();
+        assert_ne!(first.path(), second.path());
+        assert_eq!(
+            std::fs::read(first.path()).unwrap(),
+            std::fs::read(&plain).unwrap()
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(first.path())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // The copy lives as long as a reader or iterator holding it
+        let copy = first.path().to_path_buf();
+        let reader = ParquetReader::from_gunzipped(first, ParquetReaderConfig::default());
+        let batches = reader.batch_iter().unwrap();
+        drop(reader);
+        assert!(copy.exists());
+        assert_eq!(batches.map(|b| b.unwrap().num_rows()).sum::<usize>(), 300);
+        assert!(!copy.exists());
+        let second_copy = second.path().to_path_buf();
+        drop(second);
+        assert!(!second_copy.exists());
+        let _ = std::fs::remove_file(&gz);
+    }
+
+    #[test]
+    fn test_read_buffer_size_does_not_change_data() {
+        let path = write_test_file("read_buffer_size.parquet");
+        let expected: Vec<i32> = (0..300).collect();
+        for size in [1, 64, 1 << 20] {
+            let config = ParquetReaderConfig::default()
+                .with_batch_size(7)
+                .with_read_buffer_size(size);
+            let sequential = ParquetReaderConfig { parallel: false, ..config.clone() };
+            assert_eq!(read_ids(&path, config.clone()), expected, "buffer size {}", size);
+            assert_eq!(read_ids(&path, sequential), expected, "buffer size {}", size);
+            let reader = ParquetReader::from_path_with_config(&path, config).unwrap();
+            let streamed = reader.batch_iter().unwrap().collect::<Result<Vec<_>>>().unwrap();
+            assert_eq!(streamed.iter().map(|b| b.num_rows()).sum::<usize>(), 300);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_flatten_schema() {
+        let inner = Field::new("z", DataType::Int32, false);
+        let point = DataType::Struct(
+            vec![Field::new("x", DataType::Int32, false), inner.clone()].into(),
+        );
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("point", point, true),
+        ]);
+        let flat = flatten_schema(&schema).unwrap();
+        let names: Vec<&str> = flat.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, vec!["id", "point.x", "point.z"]);
+        // Fields of a nullable struct are nullable
+        assert!(flat.field(1).is_nullable());
+
+        let inner_struct = Field::new("inner", DataType::Struct(vec![inner].into()), false);
+        let nested = DataType::Struct(vec![inner_struct].into());
+        let schema = Schema::new(vec![Field::new("outer", nested, false)]);
+        assert!(flatten_schema(&schema).is_err());
+    }
+
+    #[test]
+    fn test_pushed_down_predicate_matches_post_filter() {
+        let path = write_test_file("predicate_pushdown.parquet");
+        let range = LogicalExpr::BinaryExpr {
+            left: Box::new(col("id").ge(lit_int32(50))),
+            op: BinaryOp::And,
+            right: Box::new(col("id").lt(lit_int32(120))),
+        };
+        let simple = LogicalExpr::BinaryExpr {
+            left: Box::new(range),
+            op: BinaryOp::Or,
+            right: Box::new(col("id").eq(lit_int32(250))),
+        };
+        assert!(is_simple_predicate(&simple));
+        // Same rows, but not simple: `id + 0` is not a column
+        let shifted = col("id").plus(lit_int32(0));
+        let complex = LogicalExpr::BinaryExpr {
+            left: Box::new(LogicalExpr::BinaryExpr {
+                left: Box::new(shifted.ge(lit_int32(50))),
+                op: BinaryOp::And,
+                right: Box::new(shifted.lt(lit_int32(120))),
+            }),
+            op: BinaryOp::Or,
+            right: Box::new(shifted.eq(lit_int32(250))),
+        };
+        assert!(!is_simple_predicate(&complex));
+
+        let expected: Vec<i32> = (50..120).chain([250]).collect();
+        for parallel in [false, true] {
+            let config = ParquetReaderConfig {
+                parallel,
+                ..ParquetReaderConfig::default()
+            };
+            let pushed = read_ids(&path, config.clone().with_predicate(simple.clone()));
+            let post_filtered = read_ids(&path, config.with_predicate(complex.clone()));
+            assert_eq!(pushed, expected);
+            assert_eq!(post_filtered, expected);
+        }
+    }
+}
@@ -1 +1,243 @@
 // Early filtering at storage level
+
+use crate::planner::logical_plan::{BinaryOp, LogicalExpr, LogicalValue};
+use crate::storage::parquet_reader::{gunzipped_path, ParquetReader};
+use parquet::basic::Type as PhysicalType;
+use parquet::file::metadata::{ParquetMetaData, RowGroupMetaData};
+use parquet::file::properties::ReaderProperties;
+use parquet::file::reader::RowGroupReader;
+use parquet::file::serialized_reader::SerializedRowGroupReader;
+use parquet::file::statistics::Statistics;
+use std::fs::File;
+use std::io::{Error, Result};
+use std::path::Path;
+use std::sync::Arc;
+
+/// An equality predicate `column = value` extracted from a pushed-down filter
+#[derive(Debug, Clone)]
+struct EqualityPredicate<'a> {
+    column: &'a str,
+    value: &'a LogicalValue,
+}
+
+/// Return the indices of the row groups in `path` that may contain rows matching
+/// all `filters`, using the column bloom filters stored in the file.
+///
+/// Only `col = literal` conjuncts are considered. A row group is skipped when the
+/// bloom filter for a predicate's column proves the value is absent. Row groups
+/// without a bloom filter for the column are always kept, so files written without
+/// bloom filters read every row group.
+pub fn prune_row_groups_with_bloom_filters(path: &Path, filters: &[LogicalExpr]) -> Result<Vec<usize>> {
+    let file = gunzipped_path(path)?;
+    let metadata = ParquetReader::from_gunzipped(file.clone(), Default::default()).metadata()?;
+    prune_row_groups_with_metadata(file.path(), metadata.metadata(), filters)
+}
+
+/// As `prune_row_groups_with_bloom_filters`, for a file whose footer was already parsed
+/// into `metadata`: only the bloom filters themselves are read from `path`, which must
+/// be the Parquet file itself (decompressed, see `gunzipped_path`)
+pub fn prune_row_groups_with_metadata(
+    path: &Path,
+    metadata: &ParquetMetaData,
+    filters: &[LogicalExpr],
+) -> Result<Vec<usize>> {
+    let predicates: Vec<EqualityPredicate> = filters
+        .iter()
+        .flat_map(split_conjunction)
+        .filter_map(as_equality_predicate)
+        .collect();
+    if predicates.is_empty() {
+        return Ok((0..metadata.num_row_groups()).collect());
+    }
+
+    let file = Arc::new(File::open(path)?);
+    let props = Arc::new(
+        ReaderProperties::builder()
+            .set_read_bloom_filter(true)
+            .build(),
+    );
+    let mut keep = Vec::new();
+    for (rg, row_group) in metadata.row_groups().iter().enumerate() {
+        let row_group = SerializedRowGroupReader::new(file.clone(), row_group, None, props.clone())
+            .map_err(|e| Error::other(format!("Parquet row group: {}", e)))?;
+        if predicates
+            .iter()
+            .all(|p| row_group_may_contain(&row_group, p))
+        {
+            keep.push(rg);
+        }
+    }
+    Ok(keep)
+}
+
+/// A comparison `column op value` extracted from a pushed-down filter
+#[derive(Debug, Clone)]
+struct ComparisonPredicate<'a> {
+    column: &'a str,
+    op: BinaryOp,
+    value: &'a LogicalValue,
+}
+
+/// Return whether the Parquet file at `path` may contain rows matching all `filters`,
+/// using the min/max statistics in its footer.
+///
+/// Only `col op literal` conjuncts with `=`, `<`, `<=`, `>`, `>=` are considered. The file
+/// can be skipped only when, for every row group, some predicate is proven false by that
+/// column's min/max. Missing statistics or mismatched literal types always keep the file.
+/// This is coarser (and cheaper) than row group pruning: only the footer is read.
+pub fn file_may_match_statistics(path: &Path, filters: &[LogicalExpr]) -> Result<bool> {
+    let metadata = ParquetReader::from_path(path)?.metadata()?;
+    Ok(metadata_may_match_statistics(metadata.metadata(), filters))
+}
+
+/// As `file_may_match_statistics`, for a file whose footer was already parsed into
+/// `metadata`
+pub fn metadata_may_match_statistics(metadata: &ParquetMetaData, filters: &[LogicalExpr]) -> bool {
+    let predicates: Vec<ComparisonPredicate> = filters
+        .iter()
+        .flat_map(split_conjunction)
+        .filter_map(as_comparison_predicate)
+        .collect();
+    if predicates.is_empty() {
+        return true;
+    }
+
+    // A file with no row groups has no rows to match
+    metadata.row_groups().iter().any(|row_group| {
+        predicates
+            .iter()
+            .all(|p| statistics_may_match(row_group, p))
+    })
+}
+
+/// Split a predicate into its top-level AND conjuncts
+fn split_conjunction(expr: &LogicalExpr) -> Vec<&LogicalExpr> {
+    match expr {
+        LogicalExpr::BinaryExpr {
+            left,
+            op: BinaryOp::And,
+            right,
+        } => {
+            let mut out = split_conjunction(left);
+            out.extend(split_conjunction(right));
+            out
+        }
+        _ => vec![expr],
+    }
+}
+
+/// Match `col = literal` or `literal = col`
+fn as_equality_predicate(expr: &LogicalExpr) -> Option<EqualityPredicate<'_>> {
+    match expr {
+        LogicalExpr::BinaryExpr {
+            left,
+            op: BinaryOp::Eq,
+            right,
+        } => match (left.as_ref(), right.as_ref()) {
+            (LogicalExpr::Column(column), LogicalExpr::Literal(value))
+            | (LogicalExpr::Literal(value), LogicalExpr::Column(column)) => {
+                Some(EqualityPredicate { column, value })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Match `col op literal` or `literal op col` (normalized to column on the left)
+fn as_comparison_predicate(expr: &LogicalExpr) -> Option<ComparisonPredicate<'_>> {
+    let LogicalExpr::BinaryExpr { left, op, right } = expr else {
+        return None;
+    };
+    match (left.as_ref(), right.as_ref()) {
+        (LogicalExpr::Column(column), LogicalExpr::Literal(value)) => Some(ComparisonPredicate {
+            column,
+            op: *op,
+            value,
+        }),
+        (LogicalExpr::Literal(value), LogicalExpr::Column(column)) => {
+            let op = match op {
+                BinaryOp::Lt => BinaryOp::Gt,
+                BinaryOp::Le => BinaryOp::Ge,
+                BinaryOp::Gt => BinaryOp::Lt,
+                BinaryOp::Ge => BinaryOp::Le,
+                other => *other,
+            };
+            Some(ComparisonPredicate { column, op, value })
+        }
+        _ => None,
+    }
+}
+
+/// Check the predicate against the row group's min/max for its column.
+/// Returns true (keep) whenever statistics are missing or not comparable with the literal.
+fn statistics_may_match(row_group: &RowGroupMetaData, predicate: &ComparisonPredicate) -> bool {
+    let Some(column) = row_group
+        .columns()
+        .iter()
+        .find(|c| c.column_path().string() == predicate.column)
+    else {
+        return true;
+    };
+    let Some(stats) = column.statistics() else {
+        return true;
+    };
+    if !stats.has_min_max_set() {
+        return true;
+    }
+    match (stats, predicate.value) {
+        (Statistics::Int32(s), LogicalValue::Int32(v)) => {
+            range_may_match(predicate.op, s.min(), s.max(), v)
+        }
+        (Statistics::Int64(s), LogicalValue::Int64(v)) => {
+            range_may_match(predicate.op, s.min(), s.max(), v)
+        }
+        (Statistics::Double(s), LogicalValue::Float64(v)) => {
+            range_may_match(predicate.op, s.min(), s.max(), v)
+        }
+        (Statistics::ByteArray(s), LogicalValue::String(v)) => {
+            match (s.min().as_utf8(), s.max().as_utf8()) {
+                (Ok(min), Ok(max)) => range_may_match(predicate.op, &min, &max, &v.as_str()),
+                _ => true,
+            }
+        }
+        _ => true,
+    }
+}
+
+/// Whether any value in `[min, max]` can satisfy `value_in_column op v`
+fn range_may_match<T: PartialOrd>(op: BinaryOp, min: &T, max: &T, v: &T) -> bool {
+    match op {
+        BinaryOp::Eq => min <= v && v <= max,
+        BinaryOp::Lt => min < v,
+        BinaryOp::Le => min <= v,
+        BinaryOp::Gt => max > v,
+        BinaryOp::Ge => max >= v,
+        _ => true,
+    }
+}
+
+/// Check the row group's bloom filter for the predicate's column.
+/// Returns true (keep) whenever the filter is missing or the literal type doesn't
+/// match the column's physical type, since the hash would not be comparable.
+fn row_group_may_contain(row_group: &dyn RowGroupReader, predicate: &EqualityPredicate) -> bool {
+    let metadata = row_group.metadata();
+    let Some(column_idx) = metadata
+        .columns()
+        .iter()
+        .position(|c| c.column_path().string() == predicate.column)
+    else {
+        return true;
+    };
+    let Some(sbbf) = row_group.get_column_bloom_filter(column_idx) else {
+        return true;
+    };
+    let physical_type = metadata.column(column_idx).column_descr().physical_type();
+    match (physical_type, predicate.value) {
+        (PhysicalType::INT32, LogicalValue::Int32(v)) => sbbf.check(v),
+        (PhysicalType::INT64, LogicalValue::Int64(v)) => sbbf.check(v),
+        (PhysicalType::DOUBLE, LogicalValue::Float64(v)) => sbbf.check(v),
+        (PhysicalType::BYTE_ARRAY, LogicalValue::String(v)) => sbbf.check(&v.as_str()),
+        _ => true,
+    }
+}
@@ -0,0 +1,77 @@
+// Cache of Parquet file footers keyed by path and modification time
+
+use crate::storage::parquet_reader::ParquetReader;
+use arrow::datatypes::Schema;
+use parquet::arrow::arrow_reader::ArrowReaderMetadata;
+use std::collections::HashMap;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// A parsed footer: the file's metadata and the Arrow schema it is read as
+#[derive(Debug, Clone)]
+struct Entry {
+    mtime: SystemTime,
+    metadata: ArrowReaderMetadata,
+    schema: Arc<Schema>,
+}
+
+/// Caches the metadata parsed from each Parquet file's footer, so repeated scans of an
+/// unchanged file skip re-parsing it, both to plan and to read (statistics, bloom
+/// filters and row groups are all located through it). An entry is re-read when the
+/// file's mtime changes.
+#[derive(Debug, Default)]
+pub struct SchemaCache {
+    entries: Mutex<HashMap<PathBuf, Entry>>,
+    /// Number of footers actually parsed (cache misses)
+    footer_reads: AtomicUsize,
+}
+
+impl SchemaCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schema of the Parquet file at `path`, parsing its footer only if the file is not
+    /// cached or has been modified since it was cached
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Result<Arc<Schema>> {
+        Ok(self.entry(path.as_ref())?.schema)
+    }
+
+    /// Footer metadata of the Parquet file at `path`, parsed as for `get`
+    pub fn metadata<P: AsRef<Path>>(&self, path: P) -> Result<ArrowReaderMetadata> {
+        Ok(self.entry(path.as_ref())?.metadata)
+    }
+
+    fn entry(&self, path: &Path) -> Result<Entry> {
+        let mtime = std::fs::metadata(path)?.modified()?;
+        if let Some(entry) = self.entries.lock().unwrap().get(path) {
+            if entry.mtime == mtime {
+                return Ok(entry.clone());
+            }
+        }
+
+        let reader = ParquetReader::from_path(path)?;
+        let metadata = reader.metadata()?;
+        let schema = Arc::new(reader.with_metadata(metadata.clone()).schema()?);
+        self.footer_reads.fetch_add(1, Ordering::Relaxed);
+        let entry = Entry {
+            mtime,
+            metadata,
+            schema,
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), entry.clone());
+        Ok(entry)
+    }
+
+    /// Number of Parquet footers parsed through this cache so far
+    pub fn footer_reads(&self) -> usize {
+        self.footer_reads.load(Ordering::Relaxed)
+    }
+}
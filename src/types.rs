@@ -1 +1,151 @@
 // Common types and schemas
+
+use arrow::array::{
+    new_null_array, ArrayRef, BooleanArray, Date32Array, Float64Array, Int32Array, Int64Array,
+    StringArray, TimestampMicrosecondArray,
+};
+use arrow::datatypes::{DataType, TimeUnit};
+use arrow::temporal_conversions::{date32_to_datetime, timestamp_us_to_datetime};
+use std::fmt;
+use std::sync::Arc;
+
+/// A single typed value, used where a constant is needed at execution time
+/// (e.g. fill values). `Null` is an untyped null.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalarValue {
+    Int32(i32),
+    Int64(i64),
+    Float64(f64),
+    Utf8(String),
+    Boolean(bool),
+    /// Days since 1970-01-01
+    Date32(i32),
+    /// Microseconds since 1970-01-01 00:00:00 UTC
+    TimestampMicros(i64),
+    Null,
+}
+
+impl ScalarValue {
+    /// Arrow data type of this value
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ScalarValue::Int32(_) => DataType::Int32,
+            ScalarValue::Int64(_) => DataType::Int64,
+            ScalarValue::Float64(_) => DataType::Float64,
+            ScalarValue::Utf8(_) => DataType::Utf8,
+            ScalarValue::Boolean(_) => DataType::Boolean,
+            ScalarValue::Date32(_) => DataType::Date32,
+            ScalarValue::TimestampMicros(_) => DataType::Timestamp(TimeUnit::Microsecond, None),
+            ScalarValue::Null => DataType::Null,
+        }
+    }
+
+    /// Whether this is the null value
+    pub fn is_null(&self) -> bool {
+        matches!(self, ScalarValue::Null)
+    }
+
+    /// Build an array with this value repeated `len` times
+    pub fn to_array(&self, len: usize) -> ArrayRef {
+        match self {
+            ScalarValue::Int32(v) => Arc::new(Int32Array::from(vec![*v; len])),
+            ScalarValue::Int64(v) => Arc::new(Int64Array::from(vec![*v; len])),
+            ScalarValue::Float64(v) => Arc::new(Float64Array::from(vec![*v; len])),
+            ScalarValue::Utf8(v) => Arc::new(StringArray::from(vec![v.as_str(); len])),
+            ScalarValue::Boolean(v) => Arc::new(BooleanArray::from(vec![*v; len])),
+            ScalarValue::Date32(v) => Arc::new(Date32Array::from(vec![*v; len])),
+            ScalarValue::TimestampMicros(v) => {
+                Arc::new(TimestampMicrosecondArray::from(vec![*v; len]))
+            }
+            ScalarValue::Null => new_null_array(&DataType::Null, len),
+        }
+    }
+}
+
+impl fmt::Display for ScalarValue {
+    /// SQL literal form: strings single-quoted with embedded quotes doubled, null as NULL
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalarValue::Int32(v) => write!(f, "{}", v),
+            ScalarValue::Int64(v) => write!(f, "{}", v),
+            ScalarValue::Float64(v) => write!(f, "{:?}", v),
+            ScalarValue::Utf8(v) => write!(f, "'{}'", v.replace('\'', "''")),
+            ScalarValue::Boolean(v) => write!(f, "{}", v),
+            ScalarValue::Date32(v) => match date32_to_datetime(*v) {
+                Some(date) => write!(f, "DATE '{}'", date.date()),
+                None => write!(f, "DATE {}", v),
+            },
+            ScalarValue::TimestampMicros(v) => match timestamp_us_to_datetime(*v) {
+                Some(timestamp) => write!(f, "TIMESTAMP '{}'", timestamp),
+                None => write!(f, "TIMESTAMP {}", v),
+            },
+            ScalarValue::Null => write!(f, "NULL"),
+        }
+    }
+}
+
+macro_rules! scalar_try_from {
+    ($variant:ident, $ty:ty) => {
+        impl TryFrom<ScalarValue> for $ty {
+            type Error = String;
+
+            fn try_from(value: ScalarValue) -> Result<Self, Self::Error> {
+                match value {
+                    ScalarValue::$variant(v) => Ok(v),
+                    other => Err(format!(
+                        "Expected {} value, got {:?}",
+                        stringify!($variant),
+                        other
+                    )),
+                }
+            }
+        }
+    };
+}
+
+scalar_try_from!(Int32, i32);
+scalar_try_from!(Int64, i64);
+scalar_try_from!(Float64, f64);
+scalar_try_from!(Utf8, String);
+scalar_try_from!(Boolean, bool);
+
+/// Common type for an arithmetic operation on two numeric types: the wider of the two
+/// (Int32 < Int64 < Float64). Returns None if either type is not numeric.
+pub fn numeric_coercion(left: &DataType, right: &DataType) -> Option<DataType> {
+    fn rank(data_type: &DataType) -> Option<u8> {
+        match data_type {
+            DataType::Int32 => Some(0),
+            DataType::Int64 => Some(1),
+            DataType::Float64 => Some(2),
+            _ => None,
+        }
+    }
+    let (l, r) = (rank(left)?, rank(right)?);
+    Some(if l >= r { left.clone() } else { right.clone() })
+}
+
+/// Common type of two string types: LargeUtf8 if either side is.
+/// Returns None if either type is not a string.
+pub fn string_coercion(left: &DataType, right: &DataType) -> Option<DataType> {
+    match (left, right) {
+        (DataType::Utf8, DataType::Utf8) => Some(DataType::Utf8),
+        (DataType::Utf8 | DataType::LargeUtf8, DataType::Utf8 | DataType::LargeUtf8) => {
+            Some(DataType::LargeUtf8)
+        }
+        _ => None,
+    }
+}
+
+/// Common type for comparing two date/time types: Date32 for two dates, otherwise a
+/// timezone-less microsecond timestamp, so dates compare as midnight UTC and timestamps
+/// in any unit or timezone compare as UTC instants. Returns None if either is not temporal.
+pub fn temporal_coercion(left: &DataType, right: &DataType) -> Option<DataType> {
+    match (left, right) {
+        (DataType::Date32, DataType::Date32) => Some(DataType::Date32),
+        (
+            DataType::Date32 | DataType::Timestamp(_, _),
+            DataType::Date32 | DataType::Timestamp(_, _),
+        ) => Some(DataType::Timestamp(TimeUnit::Microsecond, None)),
+        _ => None,
+    }
+}
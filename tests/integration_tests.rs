@@ -1 +1,2167 @@
 // Integration tests
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, Date32Array, DictionaryArray, Int32Array, Int64Array,
+    LargeStringArray, StringArray, TimestampMicrosecondArray,
+};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch as ArrowRecordBatch;
+use flate2::write::GzEncoder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use mini_query_engine::dataframe::{
+    asc, col, count, desc, lit_date, lit_int32, lit_int64, lit_string, lit_timestamp,
+    lit_null, lit_timestamp_micros, param, sum, DataFrame, ExprBuilder, FromRow, GROUP_COUNT_COLUMN,
+    MAX_CROSS_TAB_COLUMNS,
+};
+use mini_query_engine::execution::batch::RecordBatch;
+use mini_query_engine::execution::{Executor, ExecutorConfig, PhysicalPlan};
+use mini_query_engine::planner::logical_plan::{
+    JoinType, LogicalPlan, LogicalValue, OrderByExpr, ScanOptions,
+};
+use mini_query_engine::planner::optimizer::{estimate_rows, optimize_with};
+use mini_query_engine::storage::csv_reader::{read_csv_with_config, CsvReader, CsvReaderConfig};
+use mini_query_engine::storage::parquet_reader::{list_parquet_files, read_parquet};
+use mini_query_engine::storage::predicate_pushdown::{
+    file_may_match_statistics, prune_row_groups_with_bloom_filters,
+};
+use mini_query_engine::storage::schema_cache::SchemaCache;
+use mini_query_engine::types::ScalarValue;
+
+/// Path for a test file in the system temp dir, unique per test process
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("mqe_{}_{}", std::process::id(), name))
+}
+
+/// Write batches to a Parquet file with the given writer properties
+fn write_parquet(name: &str, batches: &[ArrowRecordBatch], props: WriterProperties) -> PathBuf {
+    let path = temp_path(name);
+    let file = File::create(&path).unwrap();
+    let mut writer = ArrowWriter::try_new(file, batches[0].schema(), Some(props)).unwrap();
+    for batch in batches {
+        writer.write(batch).unwrap();
+    }
+    writer.close().unwrap();
+    path
+}
+
+/// Small id/name batch: ids `start..start + len`
+fn id_name_batch(start: i32, len: i32) -> ArrowRecordBatch {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    let ids: Vec<i32> = (start..start + len).collect();
+    let names: Vec<String> = ids.iter().map(|i| format!("name_{}", i)).collect();
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from(ids)),
+        Arc::new(StringArray::from(names)),
+    ];
+    ArrowRecordBatch::try_new(schema, columns).unwrap()
+}
+
+fn total_rows(batches: &[RecordBatch]) -> usize {
+    batches.iter().map(|b| b.num_rows()).sum()
+}
+
+#[test]
+fn test_bloom_filter_skips_row_groups() {
+    let props = WriterProperties::builder()
+        .set_bloom_filter_enabled(true)
+        .set_max_row_group_size(100)
+        .build();
+    let path = write_parquet("bloom.parquet", &[id_name_batch(0, 300)], props);
+
+    // A value present in exactly one row group keeps only that group
+    let present = col("id").eq(lit_int32(150));
+    let groups = prune_row_groups_with_bloom_filters(&path, std::slice::from_ref(&present)).unwrap();
+    assert_eq!(groups, vec![1]);
+
+    // A missing value skips every row group
+    let missing = col("name").eq(lit_string("nobody"));
+    let groups = prune_row_groups_with_bloom_filters(&path, std::slice::from_ref(&missing)).unwrap();
+    assert!(groups.is_empty());
+
+    // Filters pushed into the Scan still return exactly the matching rows
+    let executor = Executor::new();
+    let scan = |filters| LogicalPlan::Scan {
+        path: path.clone(),
+        projection: Some(vec!["name".to_string()]),
+        filters,
+        options: ScanOptions::default(),
+    };
+    let batches = executor.execute(&scan(vec![present])).unwrap();
+    assert_eq!(total_rows(&batches), 1);
+    assert_eq!(batches[0].num_columns(), 1);
+    let batches = executor.execute(&scan(vec![missing])).unwrap();
+    assert_eq!(total_rows(&batches), 0);
+}
+
+#[test]
+fn test_bloom_filter_missing_keeps_all_row_groups() {
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(100)
+        .build();
+    let path = write_parquet("no_bloom.parquet", &[id_name_batch(0, 300)], props);
+
+    let missing = col("id").eq(lit_int32(1000));
+    let groups = prune_row_groups_with_bloom_filters(&path, &[missing]).unwrap();
+    assert_eq!(groups, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_physical_plan_operator_selection() {
+    let path = write_parquet(
+        "physical_plan.parquet",
+        &[id_name_batch(0, 10)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("id").gt(lit_int32(4)))
+        .select(vec!["name".to_string()])
+        .order_by(vec![asc("name")]);
+
+    let executor = Executor::new();
+    let plan = executor.create_physical_plan(df.plan()).unwrap();
+    let PhysicalPlan::Sort { input, .. } = &plan else {
+        panic!("expected Sort at the root");
+    };
+    // The projection over the filter runs as one fused operator
+    let PhysicalPlan::FilterProject { input, .. } = input.as_ref() else {
+        panic!("expected FilterProject under Sort");
+    };
+    assert!(matches!(input.as_ref(), PhysicalPlan::Scan(_)));
+
+    // Schemas are bound at planning time
+    assert_eq!(plan.schema().fields().len(), 1);
+    assert_eq!(plan.schema().field(0).name(), "name");
+    assert_eq!(total_rows(&plan.execute().unwrap()), 5);
+
+    // Unfused, the same query runs as a Project over a Filter with the same output
+    let unfused = Executor::with_config(ExecutorConfig {
+        fuse_filter_project: false,
+        ..ExecutorConfig::default()
+    });
+    let unfused_plan = unfused.create_physical_plan(df.plan()).unwrap();
+    let PhysicalPlan::Sort { input, .. } = &unfused_plan else {
+        panic!("expected Sort at the root");
+    };
+    let PhysicalPlan::Project { input, .. } = input.as_ref() else {
+        panic!("expected Project under Sort");
+    };
+    assert!(matches!(input.as_ref(), PhysicalPlan::Filter { .. }));
+    let fused = RecordBatch::concat(&plan.execute().unwrap()).unwrap();
+    let expected = RecordBatch::concat(&unfused_plan.execute().unwrap()).unwrap();
+    assert_eq!(fused.to_arrow().unwrap(), expected.to_arrow().unwrap());
+
+    let scan = || LogicalPlan::Scan {
+        path: path.clone(),
+        projection: None,
+        filters: vec![],
+        options: ScanOptions::default(),
+    };
+    let join = LogicalPlan::Join {
+        left: Box::new(scan()),
+        right: Box::new(scan()),
+        join_type: JoinType::Inner,
+        on: ("id".to_string(), "id".to_string()),
+        broadcast_right: false,
+    };
+    let plan = executor.create_physical_plan(&join).unwrap();
+    assert!(matches!(plan, PhysicalPlan::HashJoin { .. }));
+    assert_eq!(plan.schema().fields().len(), 4);
+
+    // The config flag switches joins to sort-merge, with identical results
+    let merge_executor = Executor::with_config(ExecutorConfig {
+        prefer_sort_merge_join: true,
+        ..ExecutorConfig::default()
+    });
+    let merge_plan = merge_executor.create_physical_plan(&join).unwrap();
+    assert!(matches!(merge_plan, PhysicalPlan::SortMergeJoin { .. }));
+    let expected = plan.execute().unwrap();
+    let actual = merge_plan.execute().unwrap();
+    assert_eq!(expected[0].to_arrow().unwrap(), actual[0].to_arrow().unwrap());
+
+    // A broadcast hint keeps the hash join, even when sort-merge is preferred
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let broadcast = df.join_broadcast(&df, "id", "id", JoinType::Inner);
+    let broadcast_plan = merge_executor.create_physical_plan(broadcast.plan()).unwrap();
+    assert!(matches!(broadcast_plan, PhysicalPlan::HashJoin { .. }));
+    assert_eq!(total_rows(&broadcast_plan.execute().unwrap()), total_rows(&expected));
+
+    // Inputs sorted on the join keys get a sort-merge join, with names matched as the
+    // executor resolves them
+    let sorted = |column: &str| LogicalPlan::Sort {
+        input: Box::new(scan()),
+        order_by: vec![OrderByExpr {
+            column: column.to_string(),
+            ascending: true,
+        }],
+    };
+    let sorted_join = LogicalPlan::Join {
+        left: Box::new(sorted("ID")),
+        right: Box::new(sorted("id")),
+        join_type: JoinType::Inner,
+        on: ("Id".to_string(), "iD".to_string()),
+        broadcast_right: false,
+    };
+    let case_insensitive = Executor::with_config(ExecutorConfig {
+        case_insensitive: true,
+        ..ExecutorConfig::default()
+    });
+    let sorted_plan = case_insensitive.create_physical_plan(&sorted_join).unwrap();
+    assert!(matches!(sorted_plan, PhysicalPlan::SortMergeJoin { .. }));
+    let rows = total_rows(&sorted_plan.execute().unwrap());
+    assert_eq!(rows, total_rows(&expected));
+}
+
+/// Nullable id/name/score file: each row has nulls in a different column
+fn write_nullable_parquet(name: &str) -> PathBuf {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("score", DataType::Int32, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from(vec![1, 2, 3, 4])),
+        Arc::new(StringArray::from(vec![Some("a"), None, Some("c"), None])),
+        Arc::new(Int32Array::from(vec![Some(10), Some(20), None, None])),
+    ];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    write_parquet(name, &[batch], WriterProperties::builder().build())
+}
+
+fn ids(batches: &[RecordBatch]) -> Vec<i32> {
+    batches
+        .iter()
+        .flat_map(|b| {
+            let ids = b.column_by_name("id").unwrap();
+            let ids = ids.as_any().downcast_ref::<Int32Array>().unwrap();
+            ids.values().to_vec()
+        })
+        .collect()
+}
+
+#[test]
+fn test_drop_nulls() {
+    let path = write_nullable_parquet("drop_nulls.parquet");
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    let single = df.drop_nulls(vec!["name".to_string()]).unwrap();
+    assert_eq!(ids(&single.collect().unwrap()), vec![1, 3]);
+
+    let multi = df
+        .drop_nulls(vec!["name".to_string(), "score".to_string()])
+        .unwrap();
+    assert_eq!(ids(&multi.collect().unwrap()), vec![1]);
+
+    // No columns: drop rows with a null anywhere
+    let any = df.drop_nulls(vec![]).unwrap();
+    assert_eq!(ids(&any.collect().unwrap()), vec![1]);
+
+    // The predicate can also be used directly
+    let only_nulls = df.filter(col("score").is_null()).collect().unwrap();
+    assert_eq!(ids(&only_nulls), vec![3, 4]);
+}
+
+#[test]
+fn test_drop_columns() {
+    let path = write_nullable_parquet("drop_columns.parquet");
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    let dropped = df.drop(vec!["score".to_string(), "name".to_string()]).unwrap();
+    let batch = dropped.collect_one().unwrap();
+    assert_eq!(batch.schema().fields().len(), 1);
+    assert_eq!(ids(&[batch]), vec![1, 2, 3, 4]);
+    assert!(matches!(dropped.plan(), LogicalPlan::Project { .. }));
+
+    let err = df.drop(vec!["name".to_string(), "nope".to_string()]).unwrap_err();
+    assert_eq!(err, "Cannot drop column 'nope': not found");
+}
+
+#[test]
+fn test_filter_fn_with_row_closure() {
+    let path = write_parquet(
+        "filter_fn.parquet",
+        &[id_name_batch(0, 200)],
+        WriterProperties::builder().set_max_row_group_size(50).build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    // Names longer than their id: name_0 .. name_5
+    let longer_than_id = |batch: &RecordBatch, row: usize| {
+        let id = batch.i32_column("id").unwrap().value(row);
+        let name = batch.str_column("name").unwrap().value(row);
+        name.len() as i32 > id
+    };
+    let filtered = df.filter_fn(longer_than_id);
+    assert_eq!(ids(&filtered.collect().unwrap()), vec![0, 1, 2, 3, 4, 5]);
+    assert!(filtered.explain(false).unwrap().starts_with("FilterFn: <closure>"));
+
+    // Combines with expression filters, which still reach the scan
+    let both = df.filter(col("id").ge(lit_int32(2))).filter_fn(longer_than_id).optimize();
+    let both = both.unwrap();
+    assert!(both.explain(false).unwrap().contains("filters=[id >= 2]"));
+    let mut streamed = Vec::new();
+    both.for_each_batch(|batch| {
+        streamed.extend(ids(&[batch]));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(streamed, vec![2, 3, 4, 5]);
+}
+
+#[test]
+fn test_fill_null() {
+    let path = write_nullable_parquet("fill_null.parquet");
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    let filled = df
+        .fill_null("score", ScalarValue::Int32(0))
+        .fill_null("name", ScalarValue::Utf8("unknown".to_string()))
+        .collect()
+        .unwrap();
+    let batch = &filled[0];
+    let score = batch.column_by_name("score").unwrap();
+    let score = score.as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(score.null_count(), 0);
+    assert_eq!(score.values().to_vec(), vec![10, 20, 0, 0]);
+    let name = batch.column_by_name("name").unwrap();
+    let name = name.as_any().downcast_ref::<StringArray>().unwrap();
+    let names: Vec<&str> = (0..name.len()).map(|i| name.value(i)).collect();
+    assert_eq!(names, vec!["a", "unknown", "c", "unknown"]);
+    assert!(!batch.schema().field_with_name("score").unwrap().is_nullable());
+
+    // Mismatched types are rejected
+    assert!(df.fill_null("score", ScalarValue::Utf8("x".to_string())).collect().is_err());
+
+    // fill_null_all only touches columns of the value's type
+    let all = df.fill_null_all(ScalarValue::Int32(-1)).collect().unwrap();
+    let score = all[0].column_by_name("score").unwrap();
+    let score = score.as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(score.values().to_vec(), vec![10, 20, -1, -1]);
+    assert_eq!(all[0].column_by_name("name").unwrap().null_count(), 2);
+}
+
+#[test]
+fn test_compressed_files_read_identically() {
+    let batch = id_name_batch(0, 1000);
+    let codecs = [
+        ("uncompressed", Compression::UNCOMPRESSED),
+        ("snappy", Compression::SNAPPY),
+        ("gzip", Compression::GZIP(GzipLevel::default())),
+        ("zstd", Compression::ZSTD(ZstdLevel::default())),
+    ];
+    for (name, codec) in codecs {
+        let props = WriterProperties::builder()
+            .set_compression(codec)
+            .set_max_row_group_size(300)
+            .build();
+        let path = write_parquet(&format!("{}.parquet", name), std::slice::from_ref(&batch), props);
+
+        let batches = read_parquet(&path).unwrap();
+        let read = arrow::compute::concat_batches(&batch.schema(), &batches).unwrap();
+        assert_eq!(read, batch, "{} file read back differently", name);
+
+        // Through the engine as well
+        let df = DataFrame::from_parquet(&path)
+            .unwrap()
+            .filter(col("id").ge(lit_int32(900)));
+        assert_eq!(total_rows(&df.collect().unwrap()), 100, "{}", name);
+    }
+}
+
+#[test]
+fn test_file_pruning_with_statistics() {
+    let dir = temp_path("multi_file");
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, start) in [("part-0.parquet", 0), ("part-1.parquet", 100)] {
+        let path = write_parquet(
+            name,
+            &[id_name_batch(start, 100)],
+            WriterProperties::builder().build(),
+        );
+        std::fs::rename(&path, dir.join(name)).unwrap();
+    }
+
+    // ids 0..100 cannot satisfy id > 150; ids 100..200 can
+    let filter = col("id").gt(lit_int32(150));
+    let first = dir.join("part-0.parquet");
+    let second = dir.join("part-1.parquet");
+    assert!(!file_may_match_statistics(&first, std::slice::from_ref(&filter)).unwrap());
+    assert!(file_may_match_statistics(&second, std::slice::from_ref(&filter)).unwrap());
+
+    // Flipped literal/column order and equality
+    let flipped = lit_int32(50).gt(col("id"));
+    assert!(file_may_match_statistics(&first, std::slice::from_ref(&flipped)).unwrap());
+    assert!(!file_may_match_statistics(&second, &[flipped]).unwrap());
+    assert!(!file_may_match_statistics(&first, &[col("id").eq(lit_int32(100))]).unwrap());
+
+    // The directory scan reads both files without filters, only matching rows with them
+    let df = DataFrame::from_parquet(&dir).unwrap();
+    assert_eq!(total_rows(&df.collect().unwrap()), 200);
+    let scan = LogicalPlan::Scan {
+        path: dir.clone(),
+        projection: None,
+        filters: vec![filter],
+        options: ScanOptions::default(),
+    };
+    let batches = Executor::new().execute(&scan).unwrap();
+    assert_eq!(total_rows(&batches), 49);
+}
+
+#[test]
+fn test_incremental_scan_skips_processed_files() {
+    let dir = temp_path("incremental");
+    std::fs::create_dir_all(&dir).unwrap();
+    let add_file = |name: &str, start: i32| {
+        let path = write_parquet(
+            name,
+            &[id_name_batch(start, 10)],
+            WriterProperties::builder().build(),
+        );
+        std::fs::rename(&path, dir.join(name)).unwrap();
+    };
+    add_file("part-0.parquet", 0);
+
+    // First run reads everything there is
+    let first = DataFrame::from_parquet(&dir).unwrap();
+    assert_eq!(total_rows(&first.collect().unwrap()), 10);
+    let processed: HashSet<PathBuf> = list_parquet_files(&dir).unwrap().into_iter().collect();
+
+    // A new file appears; the next run reads only it
+    add_file("part-1.parquet", 100);
+    let options = ScanOptions::default().with_skip_files(processed);
+    let next = DataFrame::from_parquet_with_options(&dir, options).unwrap();
+    let batches = next.collect().unwrap();
+    assert_eq!(total_rows(&batches), 10);
+    let ids = batches[0].column_by_name("id").unwrap();
+    let ids = ids.as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(ids.value(0), 100);
+
+    // Nothing new: no batches, no error
+    let all: HashSet<PathBuf> = list_parquet_files(&dir).unwrap().into_iter().collect();
+    let options = ScanOptions::default().with_skip_files(all);
+    let none = DataFrame::from_parquet_with_options(&dir, options).unwrap();
+    assert!(none.collect().unwrap().is_empty());
+}
+
+#[test]
+fn test_collect_one_concatenates_batches() {
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(100)
+        .build();
+    let path = write_parquet("collect_one.parquet", &[id_name_batch(0, 300)], props);
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    // One batch per row group
+    let batches = df.collect().unwrap();
+    assert_eq!(batches.len(), 3);
+
+    let batch = df.collect_one().unwrap();
+    assert_eq!(batch.num_rows(), total_rows(&batches));
+    assert_eq!(batch.num_columns(), 2);
+
+    // No matching rows: a zero-row batch that still has the output schema
+    let empty = df
+        .filter(col("id").gt(lit_int32(1000)))
+        .select(vec!["name".to_string()])
+        .collect_one()
+        .unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.schema().field(0).name(), "name");
+}
+
+#[test]
+fn test_select_no_columns_keeps_row_count() {
+    let path = write_parquet(
+        "select_none.parquet",
+        &[id_name_batch(0, 30)],
+        WriterProperties::builder().set_max_row_group_size(8).build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    for none in [df.select(vec![]), df.select_exprs(vec![])] {
+        let batch = none.collect_one().unwrap();
+        assert_eq!((batch.num_rows(), batch.num_columns()), (30, 0));
+        let filtered = none.filter(lit_int32(1).eq(lit_int32(1))).limit(25);
+        assert_eq!(total_rows(&filtered.collect().unwrap()), 25);
+
+        let counted = none.group_by(vec![]).agg(vec![count("n")]);
+        assert_eq!(counted.collect_scalar().unwrap(), ScalarValue::Int64(30));
+        let counted = df.filter(col("id").lt(lit_int32(10))).select(vec![]);
+        let counted = counted.group_by(vec![]).agg(vec![count("n")]);
+        assert_eq!(counted.collect_scalar().unwrap(), ScalarValue::Int64(10));
+    }
+}
+
+#[test]
+fn test_project_typed_null_literal() {
+    let path = write_parquet(
+        "null_literal.parquet",
+        &[id_name_batch(0, 5)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let projected = df.select_exprs(vec![col("id"), lit_null(DataType::Float64).alias("score")]);
+    let batch = projected.collect_one().unwrap();
+    let field = batch.schema().field(1).clone();
+    assert_eq!((field.name().as_str(), field.data_type()), ("score", &DataType::Float64));
+    assert!(field.is_nullable());
+    let score = batch.column_by_name("score").unwrap();
+    assert_eq!((score.data_type(), score.null_count()), (&DataType::Float64, 5));
+    assert_eq!(projected.schema().unwrap().field(1), &field);
+
+    // Comparisons with NULL are null, so no row passes, before or after pushdown
+    let null = || lit_null(DataType::Int32);
+    for predicate in [col("id").gt(null()), col("id").eq(null())] {
+        let filtered = df.filter(predicate);
+        assert_eq!(total_rows(&filtered.collect().unwrap()), 0);
+        assert_eq!(total_rows(&filtered.optimize().unwrap().collect().unwrap()), 0);
+    }
+    assert_eq!(lit_null(DataType::Utf8).to_string(), "NULL");
+}
+
+#[test]
+fn test_cross_tab_counts_pairs() {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("sex", DataType::Utf8, false),
+        Field::new("smoker", DataType::Utf8, false),
+    ]));
+    let batch = ArrowRecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(StringArray::from(vec!["m", "f", "m", "f", "m"])),
+            Arc::new(StringArray::from(vec!["yes", "no", "no", "no", "yes"])),
+        ],
+    )
+    .unwrap();
+    let path = write_parquet("cross_tab.parquet", &[batch], WriterProperties::builder().build());
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    let table = df.cross_tab("sex", "smoker").unwrap();
+    let names: Vec<&str> = table.schema().fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, vec!["sex", "no", "yes"]);
+    let sex = table.str_column("sex").unwrap();
+    assert_eq!((sex.value(0), sex.value(1)), ("f", "m"));
+    // f never smokes, so that cell is 0 rather than missing
+    assert_eq!(table.i64_column("no").unwrap().values().to_vec(), vec![2, 1]);
+    assert_eq!(table.i64_column("yes").unwrap().values().to_vec(), vec![0, 2]);
+
+    assert!(df.cross_tab("sex", "sex").is_err());
+    let many = write_parquet(
+        "cross_tab_many.parquet",
+        &[id_name_batch(0, MAX_CROSS_TAB_COLUMNS as i32 + 1)],
+        WriterProperties::builder().build(),
+    );
+    let err = DataFrame::from_parquet(&many).unwrap().cross_tab("name", "id").unwrap_err();
+    assert!(err.contains("more than 1000 distinct values"), "{}", err);
+}
+
+#[test]
+fn test_join_against_values_lookup() {
+    let path = write_parquet(
+        "values_join.parquet",
+        &[id_name_batch(0, 5)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("code", DataType::Int32, false),
+        Field::new("label", DataType::Utf8, true),
+    ]));
+    let row = |code: i32, label: Option<&str>| {
+        let label = match label {
+            Some(s) => LogicalValue::String(s.to_string()),
+            None => LogicalValue::Null(DataType::Utf8),
+        };
+        vec![LogicalValue::Int32(code), label]
+    };
+    let lookup = DataFrame::from_values(
+        schema.clone(),
+        vec![row(1, Some("one")), row(3, Some("three")), row(4, None), row(9, Some("nine"))],
+    )
+    .unwrap();
+    assert_eq!(lookup.schema().unwrap(), schema);
+
+    let joined = df
+        .join(&lookup, "id", "code", JoinType::Inner)
+        .order_by(vec![asc("id")])
+        .collect_one()
+        .unwrap();
+    assert_eq!(joined.i32_column("id").unwrap().values().to_vec(), vec![1, 3, 4]);
+    let labels = joined.str_column("label").unwrap();
+    assert_eq!((labels.value(0), labels.value(1), labels.is_null(2)), ("one", "three", true));
+
+    // Rows are checked against the schema up front
+    let err = DataFrame::from_values(schema, vec![vec![LogicalValue::Int32(1)]]).unwrap_err();
+    assert!(err.contains("has 1 values"), "{}", err);
+}
+
+#[test]
+fn test_assert_schema_reports_differences() {
+    let path = write_parquet(
+        "assert_schema.parquet",
+        &[id_name_batch(0, 3)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    df.assert_schema(&[("id", DataType::Int32), ("name", DataType::Utf8)]).unwrap();
+    let doubled = df.select_exprs(vec![col("id").multiply(lit_int64(2)).alias("twice")]);
+    doubled.assert_schema(&[("twice", DataType::Int64)]).unwrap();
+
+    let err = df
+        .assert_schema(&[("id", DataType::Int64), ("name", DataType::Utf8)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        "Schema differs from the expected one (left is actual): 'id' is Int32 vs Int64"
+    );
+    let err = df
+        .assert_schema(&[("name", DataType::Utf8), ("label", DataType::Utf8)])
+        .unwrap_err();
+    assert!(err.contains("missing in right: id"), "{}", err);
+    assert!(err.contains("missing in left: label"), "{}", err);
+}
+
+#[test]
+fn test_with_column_renamed() {
+    let path = write_parquet(
+        "rename.parquet",
+        &[id_name_batch(0, 5)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let renames = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect::<std::collections::HashMap<_, _>>()
+    };
+
+    let renamed = df
+        .with_column_renamed(renames(&[("id", "user_id"), ("name", "user_name")]))
+        .collect_one()
+        .unwrap();
+    assert_eq!(renamed.num_rows(), 5);
+    assert!(renamed.column_by_name("user_id").is_some());
+    assert!(renamed.column_by_name("user_name").is_some());
+
+    // id -> name collides with the existing name column
+    let err = df.with_column_renamed(renames(&[("id", "name")])).collect();
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_rename_all_lowercases_columns() {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("UserId", DataType::Int32, false),
+        Field::new("NAME", DataType::Utf8, false),
+        Field::new("city", DataType::Utf8, false),
+    ]));
+    let batch = ArrowRecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int32Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec!["a", "b"])),
+            Arc::new(StringArray::from(vec!["x", "y"])),
+        ],
+    )
+    .unwrap();
+    let path = write_parquet("rename_all.parquet", &[batch], WriterProperties::builder().build());
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    let lower = df.rename_all(|name| name.to_lowercase()).unwrap();
+    let names: Vec<String> =
+        lower.schema().unwrap().fields().iter().map(|f| f.name().clone()).collect();
+    assert_eq!(names, vec!["userid", "name", "city"]);
+    assert_eq!(lower.filter(col("userid").eq(lit_int32(2))).collect_one().unwrap().num_rows(), 1);
+
+    let err = df.rename_all(|_| "same".to_string()).err().unwrap();
+    assert!(err.contains("'UserId' and 'NAME'"), "{}", err);
+}
+
+/// Write a CSV with id/price/name/active/note columns; `line` overrides the given data row
+fn write_csv(name: &str, rows: usize, line: Option<(usize, &str)>) -> PathBuf {
+    let path = temp_path(name);
+    let mut csv = String::from("id,price,name,active,note\n");
+    for i in 0..rows {
+        match line {
+            Some((row, text)) if row == i => csv.push_str(text),
+            _ => csv.push_str(&format!("{},{}.5,name_{},{},\n", i, i, i, i % 2 == 0)),
+        }
+    }
+    std::fs::write(&path, csv).unwrap();
+    path
+}
+
+#[test]
+fn test_csv_inference_from_sample() {
+    let path = write_csv("sample.csv", 10_000, None);
+    let config = CsvReaderConfig {
+        infer_rows: 100,
+        ..CsvReaderConfig::default()
+    };
+
+    let schema = CsvReader::from_path_with_config(&path, config.clone())
+        .unwrap()
+        .schema()
+        .unwrap();
+    let types: Vec<(&str, &DataType)> = schema
+        .fields()
+        .iter()
+        .map(|f| (f.name().as_str(), f.data_type()))
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            ("id", &DataType::Int64),
+            ("price", &DataType::Float64),
+            ("name", &DataType::Utf8),
+            ("active", &DataType::Boolean),
+            // Empty throughout the sample: read as nullable strings
+            ("note", &DataType::Utf8),
+        ]
+    );
+
+    let batches = read_csv_with_config(&path, config.clone()).unwrap();
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 10_000);
+    assert_eq!(batches[0].schema().as_ref(), &schema);
+
+    // A later value that doesn't fit the sampled type is an error, not a null
+    let bad = write_csv("late_mismatch.csv", 10_000, Some((5_000, "abc,1.5,x,true,\n")));
+    let err = read_csv_with_config(&bad, config.clone()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("abc"), "{}", err);
+
+    // A row with too few fields is reported as such, not as a type mismatch
+    let ragged = write_csv("ragged.csv", 10_000, Some((5_000, "1,1.5\n")));
+    let err = read_csv_with_config(&ragged, config).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("Malformed CSV"), "{}", err);
+}
+
+#[test]
+fn test_scan_batch_size_propagates() {
+    let path = write_parquet(
+        "batch_size.parquet",
+        &[id_name_batch(0, 1000)],
+        WriterProperties::builder().build(),
+    );
+
+    // Default: the single row group fits in one batch
+    let df = DataFrame::from_parquet(&path).unwrap();
+    assert_eq!(df.collect().unwrap().len(), 1);
+
+    let options = ScanOptions::default().with_batch_size(100);
+    let batches = DataFrame::from_parquet_with_options(&path, options)
+        .unwrap()
+        .collect()
+        .unwrap();
+    assert_eq!(batches.len(), 10);
+    assert!(batches.iter().all(|b| b.num_rows() == 100));
+
+    let zero = ScanOptions::default().with_batch_size(0);
+    assert!(DataFrame::from_parquet_with_options(&path, zero).unwrap().collect().is_err());
+}
+
+#[test]
+fn test_for_each_batch_streams_results() {
+    let dir = temp_path("for_each_batch");
+    std::fs::create_dir_all(&dir).unwrap();
+    for (i, start) in [0, 500].iter().enumerate() {
+        let path = write_parquet(
+            &format!("for_each_batch_{}.parquet", i),
+            &[id_name_batch(*start, 500)],
+            WriterProperties::builder().build(),
+        );
+        std::fs::rename(path, dir.join(format!("part_{}.parquet", i))).unwrap();
+    }
+    let options = ScanOptions::default().with_batch_size(100);
+    let df = DataFrame::from_parquet_with_options(&dir, options)
+        .unwrap()
+        .filter(col("id").ge(lit_int32(250)))
+        .select(vec!["id".to_string()]);
+
+    let (mut batches, mut rows) = (0, 0);
+    df.for_each_batch(|batch| {
+        assert_eq!(batch.num_columns(), 1);
+        batches += 1;
+        rows += batch.num_rows();
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(rows, 750);
+    assert!(batches >= 8);
+
+    // An error from the callback stops execution and is returned
+    let mut calls = 0;
+    let err = df
+        .for_each_batch(|_| {
+            calls += 1;
+            Err("stop".to_string())
+        })
+        .unwrap_err();
+    assert_eq!((err.as_str(), calls), ("stop", 1));
+}
+
+#[test]
+fn test_ordered_limit_returns_first_rows() {
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(100)
+        .build();
+    let path = write_parquet("limit.parquet", &[id_name_batch(0, 1000)], props);
+
+    // Row groups are read in file order, so the first 250 ids come back in order
+    let df = DataFrame::from_parquet(&path).unwrap().limit(250);
+    assert_eq!(ids(&df.collect().unwrap()), (0..250).collect::<Vec<_>>());
+    assert_eq!(df.collect_one().unwrap().num_rows(), 250);
+
+    // Pushed-down filters are applied before the limit counts rows
+    let scan = LogicalPlan::Scan {
+        path: path.clone(),
+        projection: None,
+        filters: vec![col("id").ge(lit_int32(420))],
+        options: ScanOptions::default(),
+    };
+    let limited = LogicalPlan::Limit {
+        input: Box::new(scan),
+        limit: 50,
+    };
+    let batches = Executor::new().execute(&limited).unwrap();
+    assert_eq!(ids(&batches), (420..470).collect::<Vec<_>>());
+
+    // Without preserve_order the limit still yields exactly N rows
+    let options = ScanOptions::default().with_preserve_order(false);
+    let df = DataFrame::from_parquet_with_options(&path, options).unwrap().limit(250);
+    assert_eq!(total_rows(&df.collect().unwrap()), 250);
+
+    let empty = DataFrame::from_parquet(&path).unwrap().limit(0);
+    assert_eq!(empty.collect_one().unwrap().num_rows(), 0);
+}
+
+#[test]
+fn test_column_comparison_matches_across_paths() {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("cap", DataType::Int64, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from(vec![1, 5, 3, 8])),
+        Arc::new(Int64Array::from(vec![2, 4, 6, 8])),
+    ];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    let path = write_parquet("col_cmp.parquet", &[batch], WriterProperties::builder().build());
+
+    // Int32 column against Int64 column
+    let predicate = col("id").le(col("cap"));
+    let via_dataframe = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(predicate.clone())
+        .collect()
+        .unwrap();
+
+    let scan = |filters| LogicalPlan::Scan {
+        path: path.clone(),
+        projection: None,
+        filters,
+        options: ScanOptions::default(),
+    };
+    let executor = Executor::new();
+    let via_executor = executor
+        .execute(&LogicalPlan::Filter {
+            input: Box::new(scan(vec![])),
+            predicate: predicate.clone(),
+        })
+        .unwrap();
+    let via_pushdown = executor.execute(&scan(vec![predicate])).unwrap();
+
+    assert_eq!(ids(&via_dataframe), vec![1, 3, 8]);
+    assert_eq!(ids(&via_executor), ids(&via_dataframe));
+    assert_eq!(ids(&via_pushdown), ids(&via_dataframe));
+}
+
+#[test]
+fn test_large_utf8_group_by_and_join() {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("city", DataType::LargeUtf8, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from(vec![1, 2, 3, 4])),
+        Arc::new(LargeStringArray::from(vec![Some("oslo"), Some("rome"), Some("oslo"), None])),
+    ];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    let path = write_parquet("large_utf8.parquet", &[batch], WriterProperties::builder().build());
+    let df = DataFrame::from_parquet(&path).unwrap();
+    assert_eq!(df.schema().unwrap().field(1).data_type(), &DataType::LargeUtf8);
+
+    // Group keys keep the LargeUtf8 type
+    let grouped = df
+        .group_by(vec!["city".to_string()])
+        .agg(vec![count("n")])
+        .order_by(vec![asc("city")])
+        .collect_one()
+        .unwrap();
+    let cities = grouped.column_by_name("city").unwrap();
+    let cities = cities.as_any().downcast_ref::<LargeStringArray>().unwrap();
+    let cities: Vec<Option<&str>> = cities.iter().collect();
+    assert_eq!(cities, vec![None, Some("oslo"), Some("rome")]);
+
+    // Join on the LargeUtf8 column; nulls come back as LargeUtf8 on the right side
+    let joined = df
+        .join(&df.filter(col("city").eq(lit_string("oslo"))), "city", "city", JoinType::Left)
+        .collect_one()
+        .unwrap();
+    assert_eq!(joined.num_rows(), 6);
+    assert_eq!(joined.column(3).unwrap().data_type(), &DataType::LargeUtf8);
+}
+
+#[test]
+fn test_not_in_null_semantics() {
+    let path = write_nullable_parquet("not_in.parquet");
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    // score = [10, 20, NULL, NULL]: null scores are never NOT IN anything
+    let list = vec![ScalarValue::Int32(20), ScalarValue::Int32(30)];
+    let rows = df.filter(col("score").not_in(list.clone())).collect().unwrap();
+    assert_eq!(ids(&rows), vec![1]);
+    let rows = df.filter(col("score").in_list(list)).collect().unwrap();
+    assert_eq!(ids(&rows), vec![2]);
+
+    // A null in the list makes NOT IN unknown for every row
+    let with_null = vec![ScalarValue::Int32(20), ScalarValue::Null];
+    let rows = df.filter(col("id").not_in(with_null)).collect().unwrap();
+    assert_eq!(total_rows(&rows), 0);
+}
+
+/// File with two Int32 columns: `<prefix>_id` (0..rows) and `<prefix>_val` (id * 10)
+fn write_keyed_parquet(prefix: &str, rows: i32) -> PathBuf {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(format!("{}_id", prefix), DataType::Int32, false),
+        Field::new(format!("{}_val", prefix), DataType::Int32, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from((0..rows).collect::<Vec<_>>())),
+        Arc::new(Int32Array::from((0..rows).map(|i| i * 10).collect::<Vec<_>>())),
+    ];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    let name = format!("join_order_{}.parquet", prefix);
+    write_parquet(&name, &[batch], WriterProperties::builder().build())
+}
+
+#[test]
+fn test_join_reordering_shrinks_intermediate() {
+    let a = DataFrame::from_parquet(write_keyed_parquet("a", 1000)).unwrap();
+    let b = DataFrame::from_parquet(write_keyed_parquet("b", 1000)).unwrap();
+    let c = DataFrame::from_parquet(write_keyed_parquet("c", 1000))
+        .unwrap()
+        .filter(col("c_id").lt(lit_int32(10)));
+
+    // (a JOIN b) JOIN c: the first join is estimated at 1000 rows
+    let query = a
+        .join(&b, "a_id", "b_id", JoinType::Inner)
+        .join(&c, "a_id", "c_id", JoinType::Inner);
+    let optimized = query.optimize().unwrap();
+
+    let first_join = |plan: &LogicalPlan| -> LogicalPlan {
+        let mut plan = plan.clone();
+        if let LogicalPlan::Project { input, .. } = plan {
+            plan = *input;
+        }
+        match plan {
+            LogicalPlan::Join { left, .. } => *left,
+            other => panic!("expected a join, got {:?}", other),
+        }
+    };
+    let cache = SchemaCache::new();
+    let before = estimate_rows(&first_join(query.plan()), &cache).unwrap();
+    let after = estimate_rows(&first_join(optimized.plan()), &cache).unwrap();
+    assert_eq!(before, 1000.0);
+    assert!(after < before, "{} >= {}", after, before);
+
+    // Same rows, same column order
+    let expected = query.order_by(vec![asc("a_id")]).collect_one().unwrap();
+    let actual = optimized.order_by(vec![asc("a_id")]).collect_one().unwrap();
+    assert_eq!(expected.num_rows(), 10);
+    assert_eq!(expected.to_arrow().unwrap(), actual.to_arrow().unwrap());
+
+    // Two-way joins are left alone
+    let pair = a.join(&b, "a_id", "b_id", JoinType::Inner);
+    assert!(matches!(pair.optimize().unwrap().plan(), LogicalPlan::Join { .. }));
+
+    // Optimizing for an executor reads each footer once, through its schema cache
+    let executor = Executor::new();
+    optimize_with(query.plan(), &executor).unwrap();
+    assert_eq!(executor.schema_cache().footer_reads(), 3);
+}
+
+#[test]
+fn test_optimizer_eliminates_redundant_projections() {
+    let path = write_parquet(
+        "projections.parquet",
+        &[id_name_batch(0, 5)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let names = |cols: &[&str]| cols.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+
+    // Selecting every column in schema order is a no-op
+    let identity = df.select(names(&["id", "name"])).filter(col("id").gt(lit_int32(1)));
+    let optimized = identity.optimize().unwrap();
+    // (with the projection gone, the filter is pushed into the scan)
+    match optimized.plan() {
+        LogicalPlan::Scan { filters, .. } => assert_eq!(filters.len(), 1),
+        other => panic!("expected a filtered scan, got {:?}", other),
+    }
+    assert_eq!(
+        identity.collect_one().unwrap().to_arrow().unwrap(),
+        optimized.collect_one().unwrap().to_arrow().unwrap()
+    );
+
+    // Chained selects collapse into one projection; a reorder is kept
+    let chained = df
+        .select(names(&["id", "name"]))
+        .select(names(&["name", "id"]))
+        .select(names(&["name"]));
+    let optimized = chained.optimize().unwrap();
+    match optimized.plan() {
+        LogicalPlan::Project { input, columns } => {
+            assert_eq!(columns, &names(&["name"]));
+            assert!(matches!(input.as_ref(), LogicalPlan::Scan { .. }));
+        }
+        other => panic!("expected one projection, got {:?}", other),
+    }
+    assert_eq!(
+        chained.collect_one().unwrap().to_arrow().unwrap(),
+        optimized.collect_one().unwrap().to_arrow().unwrap()
+    );
+    let reordered = df.select(names(&["name", "id"])).optimize().unwrap();
+    assert!(matches!(reordered.plan(), LogicalPlan::Project { .. }));
+
+    // Names are resolved as the executor resolves them
+    let executor = Executor::with_config(ExecutorConfig {
+        case_insensitive: true,
+        ..ExecutorConfig::default()
+    });
+    let upper = df
+        .select(names(&["ID", "Name"]))
+        .filter(col("NAME").is_not_null());
+    let optimized = optimize_with(upper.plan(), &executor).unwrap();
+    assert!(matches!(optimized, LogicalPlan::Scan { .. }));
+    let expected = executor.execute_single(upper.plan()).unwrap();
+    let actual = executor.execute_single(&optimized).unwrap();
+    assert_eq!(expected.to_arrow().unwrap(), actual.to_arrow().unwrap());
+}
+
+#[test]
+fn test_optimizer_filters_before_projection() {
+    let path = write_parquet(
+        "filter_below_project.parquet",
+        &[id_name_batch(0, 5)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    // The filter reads `id`, which the projection dropped
+    let query = df.select(vec!["name".to_string()]).filter(col("id").gt(lit_int32(2)));
+    assert!(query.collect().is_err());
+    let optimized = query.optimize().unwrap();
+    match optimized.plan() {
+        LogicalPlan::Project { input, columns } => {
+            assert_eq!(columns, &vec!["name".to_string()]);
+            match input.as_ref() {
+                LogicalPlan::Scan { filters, .. } => assert_eq!(filters.len(), 1),
+                other => panic!("expected a filtered scan, got {:?}", other),
+            }
+        }
+        other => panic!("expected a projection over the scan, got {:?}", other),
+    }
+    let batch = optimized.collect_one().unwrap();
+    assert_eq!(batch.num_columns(), 1);
+    assert_eq!(batch.num_rows(), 2);
+
+    // A column missing below the projection too is still an error
+    let missing = df.select(vec!["name".to_string()]).filter(col("nope").gt(lit_int32(2)));
+    assert!(missing.optimize().unwrap().collect().is_err());
+}
+
+#[test]
+fn test_coalesce_small_filter_batches() {
+    let path = write_parquet(
+        "coalesce.parquet",
+        &[id_name_batch(0, 200)],
+        WriterProperties::builder().set_max_row_group_size(10).build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    // One match per row group, so one single-row batch each
+    let query = df.filter(col("id").modulo(lit_int32(10)).eq(lit_int32(0)));
+    let sizes = |batches: &[RecordBatch]| batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(sizes(&query.collect().unwrap()), vec![1; 20]);
+
+    let executor = Executor::with_config(ExecutorConfig {
+        coalesce_batch_rows: Some(8),
+        ..ExecutorConfig::default()
+    });
+    let batches = executor.execute(query.plan()).unwrap();
+    assert_eq!(sizes(&batches), vec![8, 8, 4]);
+    let ids: Vec<i32> = batches
+        .iter()
+        .flat_map(|b| b.i32_column("id").unwrap().values().to_vec())
+        .collect();
+    assert_eq!(ids, (0..200).step_by(10).collect::<Vec<_>>());
+
+    // Streaming coalesces the same way, and so does a filter pushed into the scan
+    let mut streamed = Vec::new();
+    executor
+        .execute_each(query.plan(), &mut |batch| {
+            streamed.push(batch.num_rows());
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(streamed, vec![8, 8, 4]);
+    let pushed = query.optimize().unwrap();
+    assert!(matches!(pushed.plan(), LogicalPlan::Scan { .. }));
+    assert_eq!(sizes(&executor.execute(pushed.plan()).unwrap()), vec![8, 8, 4]);
+}
+
+#[test]
+fn test_and_or_follow_three_valued_logic() {
+    // Every (p, q) pair of true, false and null, numbered by id
+    let values = [Some(true), Some(false), None];
+    let pairs: Vec<(Option<bool>, Option<bool>)> =
+        values.iter().flat_map(|&p| values.iter().map(move |&q| (p, q))).collect();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("p", DataType::Boolean, true),
+        Field::new("q", DataType::Boolean, true),
+    ]));
+    let batch = ArrowRecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int32Array::from((0..pairs.len() as i32).collect::<Vec<_>>())),
+            Arc::new(BooleanArray::from(pairs.iter().map(|p| p.0).collect::<Vec<_>>())),
+            Arc::new(BooleanArray::from(pairs.iter().map(|p| p.1).collect::<Vec<_>>())),
+        ],
+    )
+    .unwrap();
+    let path = write_parquet("kleene.parquet", &[batch], WriterProperties::builder().build());
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    let values = df
+        .select_exprs(vec![
+            col("p").and(col("q")).alias("p_and_q"),
+            col("p").or(col("q")).alias("p_or_q"),
+        ])
+        .collect_one()
+        .unwrap();
+    let show = |array: &BooleanArray| -> Vec<String> {
+        (0..array.len())
+            .map(|i| if array.is_null(i) { "null".into() } else { array.value(i).to_string() })
+            .collect()
+    };
+    // Rows: TT TF TN FT FF FN NT NF NN
+    let and = show(values.bool_column("p_and_q").unwrap());
+    let or = show(values.bool_column("p_or_q").unwrap());
+    let (t, f, n) = ("true", "false", "null");
+    assert_eq!(and, [t, f, n, f, f, f, n, f, n]);
+    assert_eq!(or, [t, t, t, t, f, n, t, n, n]);
+
+    // Filters keep exactly the rows that are true, with and without pushdown
+    let ids = |df: DataFrame| -> Vec<i32> {
+        let batch = df.order_by(vec![asc("id")]).collect_one().unwrap();
+        batch.i32_column("id").unwrap().values().to_vec()
+    };
+    for (predicate, expected) in [
+        (col("p").and(col("q")), vec![0]),
+        (col("p").or(col("q")), vec![0, 1, 2, 3, 6]),
+    ] {
+        let filtered = df.filter(predicate);
+        assert_eq!(ids(filtered.clone()), expected);
+        assert_eq!(ids(filtered.optimize().unwrap()), expected);
+    }
+}
+
+#[test]
+fn test_explain_shows_pushed_down_filter() {
+    let path = write_parquet(
+        "explain.parquet",
+        &[id_name_batch(0, 10)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("id").gt(lit_int32(6)));
+
+    let scan = format!("Scan: {}", path.display());
+    assert_eq!(df.explain(false).unwrap(), format!("Filter: id > 6\n  {}", scan));
+    assert_eq!(df.explain(true).unwrap(), format!("{} filters=[id > 6]", scan));
+
+    let optimized = df.optimize().unwrap();
+    assert_eq!(total_rows(&optimized.collect().unwrap()), 3);
+}
+
+#[test]
+fn test_with_row_index_spans_batches() {
+    let path = write_parquet(
+        "row_index.parquet",
+        &[id_name_batch(0, 25)],
+        WriterProperties::builder().build(),
+    );
+    let options = ScanOptions::default().with_batch_size(10);
+    let df = DataFrame::from_parquet_with_options(&path, options)
+        .unwrap()
+        .filter(col("id").ge(lit_int32(5)))
+        .with_row_index("idx");
+    let batches = df.collect().unwrap();
+    assert!(batches.len() > 1);
+
+    let mut indices = Vec::new();
+    let mut ids = Vec::new();
+    for batch in &batches {
+        let idx = batch.column_by_name("idx").unwrap();
+        indices.extend(idx.as_any().downcast_ref::<Int64Array>().unwrap().values().to_vec());
+        let id = batch.column_by_name("id").unwrap();
+        ids.extend(id.as_any().downcast_ref::<Int32Array>().unwrap().values().to_vec());
+    }
+    assert_eq!(indices, (0..20).collect::<Vec<i64>>());
+    assert_eq!(ids, (5..25).collect::<Vec<i32>>());
+}
+
+#[derive(Debug, PartialEq)]
+struct Person {
+    id: i32,
+    name: String,
+}
+
+impl FromRow for Person {
+    fn from_row(batch: &RecordBatch, row: usize) -> Result<Self, String> {
+        Ok(Person {
+            id: batch.get_value("id", row)?.try_into()?,
+            name: batch.get_value("name", row)?.try_into()?,
+        })
+    }
+}
+
+#[test]
+fn test_collect_as_typed_rows() {
+    let path = write_parquet(
+        "collect_as.parquet",
+        &[id_name_batch(0, 3)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let people: Vec<Person> = df.filter(col("id").ge(lit_int32(1))).collect_as().unwrap();
+    assert_eq!(
+        people,
+        vec![
+            Person { id: 1, name: "name_1".to_string() },
+            Person { id: 2, name: "name_2".to_string() },
+        ]
+    );
+
+    // Type mismatches surface as errors rather than panics
+    struct WrongType {
+        _id: i64,
+    }
+    impl FromRow for WrongType {
+        fn from_row(batch: &RecordBatch, row: usize) -> Result<Self, String> {
+            Ok(WrongType { _id: batch.get_value("id", row)?.try_into()? })
+        }
+    }
+    assert!(df.collect_as::<WrongType>().is_err());
+}
+
+#[test]
+fn test_executor_caches_parquet_schema() {
+    let path = write_parquet(
+        "schema_cache.parquet",
+        &[id_name_batch(0, 4)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let plan = df.intersect(&df).plan().clone();
+
+    let executor = Executor::new();
+    assert_eq!(executor.execute_single(&plan).unwrap().num_rows(), 4);
+    assert_eq!(executor.schema_cache().footer_reads(), 1);
+    executor.execute_single(&plan).unwrap();
+    assert_eq!(executor.schema_cache().footer_reads(), 1);
+
+    // A modified file is re-read
+    let file = File::options().write(true).open(&path).unwrap();
+    let later = file.metadata().unwrap().modified().unwrap() + std::time::Duration::from_secs(5);
+    file.set_modified(later).unwrap();
+    executor.execute_single(&plan).unwrap();
+    assert_eq!(executor.schema_cache().footer_reads(), 2);
+}
+
+#[test]
+fn test_scan_reads_footer_once_through_cache() {
+    let props = WriterProperties::builder()
+        .set_bloom_filter_enabled(true)
+        .set_max_row_group_size(100)
+        .build();
+    let path = write_parquet("footer_once.parquet", &[id_name_batch(0, 300)], props);
+    let df = DataFrame::from_parquet(&path).unwrap();
+    // Statistics, bloom filters and the limit's row group listing all need the footer
+    let filtered = df.filter(col("id").eq(lit_int32(150))).optimize().unwrap();
+    let limited = df.limit(5);
+
+    let executor = Executor::new();
+    for _ in 0..2 {
+        assert_eq!(ids(&executor.execute(filtered.plan()).unwrap()), vec![150]);
+        assert_eq!(total_rows(&executor.execute(limited.plan()).unwrap()), 5);
+    }
+    assert_eq!(executor.schema_cache().footer_reads(), 1);
+}
+
+
+#[test]
+fn test_scan_with_unsupported_columns() {
+    use arrow::array::{Float32Array, StructArray};
+    // A nested column first, so its leaf columns shift the positions of later columns
+    let point = Arc::new(StructArray::from(vec![
+        (
+            Arc::new(Field::new("x", DataType::Int32, false)),
+            Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("y", DataType::Int32, false)),
+            Arc::new(Int32Array::from(vec![3, 4])) as ArrayRef,
+        ),
+    ]));
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("point", point.data_type().clone(), false),
+        Field::new("score", DataType::Float32, false),
+        Field::new("id", DataType::Int32, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        point,
+        Arc::new(Float32Array::from(vec![0.5, 1.5])),
+        Arc::new(Int32Array::from(vec![7, 8])),
+    ];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    let path = write_parquet("unsupported.parquet", &[batch], WriterProperties::builder().build());
+
+    // Only the projected columns need a supported type
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let ids = df.select(vec!["id".to_string()]).collect_one().unwrap();
+    let ids = ids.column(0).unwrap().as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(ids.values().to_vec(), vec![7, 8]);
+
+    // Reading every column fails unless unsupported ones are skipped
+    assert!(df.collect().is_err());
+    let options = ScanOptions::default().with_skip_unsupported_columns(true);
+    let df = DataFrame::from_parquet_with_options(&path, options).unwrap();
+    let batch = df.collect_one().unwrap();
+    assert_eq!(batch.schema().fields().len(), 1);
+    assert_eq!(batch.schema().field(0).name(), "id");
+    assert_eq!(batch.num_rows(), 2);
+}
+
+#[test]
+fn test_flatten_struct_columns() {
+    use arrow::array::StructArray;
+    use arrow::buffer::NullBuffer;
+    let address_fields = vec![
+        Field::new("city", DataType::Utf8, false),
+        Field::new("zip", DataType::Int32, true),
+    ];
+    let address = StructArray::new(
+        address_fields.clone().into(),
+        vec![
+            Arc::new(StringArray::from(vec!["Oslo", "Lima", "Rome"])) as ArrayRef,
+            Arc::new(Int32Array::from(vec![Some(150), None, Some(118)])) as ArrayRef,
+        ],
+        Some(NullBuffer::from(vec![true, true, false])),
+    );
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("address", DataType::Struct(address_fields.into()), true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![Arc::new(Int32Array::from(vec![1, 2, 3])), Arc::new(address)];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    let path = write_parquet("structs.parquet", &[batch], WriterProperties::builder().build());
+
+    // Without flattening the struct column is rejected
+    assert!(DataFrame::from_parquet(&path).unwrap().collect().is_err());
+
+    let options = ScanOptions::default().with_flatten_structs(true);
+    let df = DataFrame::from_parquet_with_options(&path, options).unwrap();
+    let schema = df.schema().unwrap();
+    let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, vec!["id", "address.city", "address.zip"]);
+
+    let cities = df
+        .filter(col("address.zip").gt(lit_int32(100)))
+        .select(vec!["address.city".to_string()])
+        .collect_one()
+        .unwrap();
+    let cities = cities.column(0).unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(cities.len(), 1);
+    assert_eq!(cities.value(0), "Oslo");
+
+    // A null struct makes its fields null
+    let all = df.select(vec!["address.city".to_string()]).collect_one().unwrap();
+    assert!(all.column(0).unwrap().is_null(2));
+}
+
+/// One-batch Parquet file with Int32 columns of the given names (values: the row number)
+fn write_int_columns(name: &str, columns: &[&str], rows: i32) -> PathBuf {
+    let fields: Vec<Field> = columns
+        .iter()
+        .map(|c| Field::new(*c, DataType::Int32, false))
+        .collect();
+    let arrays: Vec<ArrayRef> = columns
+        .iter()
+        .map(|_| Arc::new(Int32Array::from((0..rows).collect::<Vec<i32>>())) as ArrayRef)
+        .collect();
+    let batch = ArrowRecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap();
+    write_parquet(name, &[batch], WriterProperties::builder().build())
+}
+
+#[test]
+fn test_case_insensitive_column_resolution() {
+    let path = write_int_columns("upper_case.parquet", &["ID", "Value"], 10);
+    let df = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("id").ge(lit_int32(5)))
+        .order_by(vec![asc("VALUE")])
+        .select(vec!["id".to_string()]);
+
+    // Case-sensitive by default
+    assert!(Executor::new().execute(df.plan()).is_err());
+
+    let executor = Executor::with_config(ExecutorConfig {
+        case_insensitive: true,
+        ..ExecutorConfig::default()
+    });
+    let batches = executor.execute(df.plan()).unwrap();
+    assert_eq!(batches[0].schema().field(0).name(), "ID");
+    assert_eq!(total_rows(&batches), 5);
+
+    let grouped = DataFrame::from_parquet(&path)
+        .unwrap()
+        .group_by(vec!["value".to_string()])
+        .agg(vec![count("n")]);
+    assert_eq!(total_rows(&executor.execute(grouped.plan()).unwrap()), 10);
+
+    // An exact match wins; otherwise several matches are ambiguous
+    let path = write_int_columns("mixed_case.parquet", &["id", "ID"], 3);
+    let exact = DataFrame::from_parquet(&path).unwrap().select(vec!["ID".to_string()]);
+    assert_eq!(total_rows(&executor.execute(exact.plan()).unwrap()), 3);
+    let ambiguous = DataFrame::from_parquet(&path).unwrap().select(vec!["Id".to_string()]);
+    let err = executor.execute(ambiguous.plan()).unwrap_err();
+    assert!(err.contains("ambiguous"), "{}", err);
+}
+
+#[test]
+fn test_filter_timestamp_and_date_ranges() {
+    // One row per hour of 2024-03-01, starting at midnight UTC
+    let start = 1_709_251_200_000_000i64;
+    let hour = 3_600_000_000i64;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("ts", DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), false),
+        Field::new("day", DataType::Date32, false),
+    ]));
+    let timestamps: Vec<i64> = (0..24).map(|h| start + h * hour).collect();
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from((0..24).collect::<Vec<i32>>())),
+        Arc::new(TimestampMicrosecondArray::from(timestamps).with_timezone("UTC")),
+        Arc::new(Date32Array::from(vec![19_783; 24])),
+    ];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    let path = write_parquet("timestamps.parquet", &[batch], WriterProperties::builder().build());
+
+    let morning = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("ts").ge(lit_timestamp("2024-03-01T06:00:00Z").unwrap()))
+        .filter(col("ts").lt(lit_timestamp("2024-03-01T14:00:00+02:00").unwrap()));
+    assert_eq!(ids(&morning.collect().unwrap()), (6..12).collect::<Vec<_>>());
+
+    let micros = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("ts").gt(lit_timestamp_micros(start + 20 * hour)));
+    assert_eq!(ids(&micros.collect().unwrap()), vec![21, 22, 23]);
+
+    // Dates compare against dates, and against timestamps as midnight UTC
+    let day = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("day").eq(lit_date(19_783)))
+        .filter(col("ts").le(col("day")));
+    assert_eq!(ids(&day.collect().unwrap()), vec![0]);
+
+    assert!(lit_timestamp("not a timestamp").is_err());
+}
+
+#[test]
+fn test_group_join_and_collect_dates_and_timestamps() {
+    let start = 1_709_251_200_000_000i64;
+    let hour = 3_600_000_000i64;
+    let temporal_batch = |names: [&str; 3], ids: Vec<i32>, days: Vec<i32>, ts: Vec<i64>| {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(names[0], DataType::Int32, false),
+            Field::new(names[1], DataType::Date32, false),
+            Field::new(
+                names[2],
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                false,
+            ),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(Date32Array::from(days)),
+            Arc::new(TimestampMicrosecondArray::from(ts)),
+        ];
+        ArrowRecordBatch::try_new(schema, columns).unwrap()
+    };
+    // Two rows per day and three per hour
+    let events = temporal_batch(
+        ["id", "day", "ts"],
+        (0..6).collect(),
+        (0..6).map(|i| 19_783 + i / 2).collect(),
+        (0..6).map(|i| start + i64::from(i / 3) * hour).collect(),
+    );
+    let props = WriterProperties::builder().build();
+    let events = write_parquet("temporal_events.parquet", &[events], props);
+    let marks = temporal_batch(
+        ["mark", "holiday", "at"],
+        vec![0],
+        vec![19_784],
+        vec![start + hour],
+    );
+    let props = WriterProperties::builder().build();
+    let marks = write_parquet("temporal_marks.parquet", &[marks], props);
+    let events = DataFrame::from_parquet(&events).unwrap();
+    let marks = DataFrame::from_parquet(&marks).unwrap();
+
+    let by_day = events
+        .group_by(vec!["day".to_string()])
+        .agg(vec![count("n")])
+        .order_by(vec![asc("day")]);
+    let rows: Vec<Vec<ScalarValue>> = by_day.collect().unwrap()[0]
+        .rows()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        rows,
+        (19_783..19_786)
+            .map(|day| vec![ScalarValue::Date32(day), ScalarValue::Int64(2)])
+            .collect::<Vec<_>>()
+    );
+    let by_hour = events
+        .group_by(vec!["ts".to_string()])
+        .agg(vec![count("n")])
+        .order_by(vec![asc("ts")]);
+    let rows: Vec<Vec<ScalarValue>> = by_hour.collect().unwrap()[0]
+        .rows()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            vec![ScalarValue::TimestampMicros(start), ScalarValue::Int64(3)],
+            vec![
+                ScalarValue::TimestampMicros(start + hour),
+                ScalarValue::Int64(3)
+            ],
+        ]
+    );
+
+    let on_day = events
+        .join(&marks, "day", "holiday", JoinType::Inner)
+        .order_by(vec![asc("id")]);
+    assert_eq!(ids(&on_day.collect().unwrap()), vec![2, 3]);
+    let on_ts = events
+        .join(&marks, "ts", "at", JoinType::Inner)
+        .order_by(vec![asc("id")]);
+    assert_eq!(ids(&on_ts.collect().unwrap()), vec![3, 4, 5]);
+
+    let fourth = events.filter(col("id").eq(lit_int32(4)));
+    let day = fourth
+        .select(vec!["day".to_string()])
+        .collect_scalar()
+        .unwrap();
+    assert_eq!(day, ScalarValue::Date32(19_785));
+    let ts = fourth
+        .select(vec!["ts".to_string()])
+        .collect_scalar()
+        .unwrap();
+    assert_eq!(ts, ScalarValue::TimestampMicros(start + hour));
+}
+
+#[test]
+fn test_semi_and_anti_join() {
+    let customers = write_parquet(
+        "anti_customers.parquet",
+        &[id_name_batch(0, 10)],
+        WriterProperties::builder().build(),
+    );
+    let schema = Arc::new(Schema::new(vec![Field::new("customer_id", DataType::Int32, false)]));
+    let orders = ArrowRecordBatch::try_new(
+        schema,
+        vec![Arc::new(Int32Array::from(vec![1, 1, 1, 3, 5, 5, 12]))],
+    )
+    .unwrap();
+    let props = WriterProperties::builder().build();
+    let orders = write_parquet("anti_orders.parquet", &[orders], props);
+    let customers = DataFrame::from_parquet(&customers).unwrap();
+    let orders = DataFrame::from_parquet(&orders).unwrap();
+
+    // Customers without orders: duplicate orders do not repeat or drop rows
+    let anti = customers.anti_join(&orders, "id", "customer_id");
+    assert_eq!(anti.schema().unwrap(), customers.schema().unwrap());
+    assert_eq!(ids(&anti.collect().unwrap()), vec![0, 2, 4, 6, 7, 8, 9]);
+
+    let semi = customers.semi_join(&orders, "id", "customer_id");
+    assert_eq!(ids(&semi.collect().unwrap()), vec![1, 3, 5]);
+
+    // Sort-merge join gives the same rows
+    let merge = Executor::with_config(ExecutorConfig {
+        prefer_sort_merge_join: true,
+        ..ExecutorConfig::default()
+    });
+    assert!(matches!(
+        merge.create_physical_plan(anti.plan()).unwrap(),
+        PhysicalPlan::SortMergeJoin { .. }
+    ));
+    assert_eq!(ids(&merge.execute(anti.plan()).unwrap()), vec![0, 2, 4, 6, 7, 8, 9]);
+    assert_eq!(ids(&merge.execute(semi.plan()).unwrap()), vec![1, 3, 5]);
+}
+
+#[test]
+fn test_limit_after_aggregate_keeps_first_groups() {
+    let props = WriterProperties::builder().set_max_row_group_size(250).build();
+    let path = write_parquet("limit_aggregate.parquet", &[id_name_batch(0, 1000)], props);
+
+    // 100 groups (id % 100), first seen in key order; every row is still aggregated
+    let grouped = DataFrame::from_parquet(&path)
+        .unwrap()
+        .select_exprs(vec![col("id").modulo(lit_int32(100)).alias("id")])
+        .group_by(vec!["id".to_string()])
+        .agg(vec![count("n")]);
+    let first = grouped.limit(10).collect().unwrap();
+    assert_eq!(first.len(), 1);
+    assert_eq!(ids(&first), (0..10).collect::<Vec<_>>());
+    let counts = first[0].column_by_name("n").unwrap();
+    let counts = counts.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert!(counts.values().iter().all(|&n| n == 10));
+
+    // Deterministic: the same groups on every run, and a prefix of the full result
+    let all = ids(&grouped.collect().unwrap());
+    assert_eq!(all.len(), 100);
+    assert_eq!(ids(&grouped.limit(10).collect().unwrap()), all[..10].to_vec());
+}
+
+#[test]
+fn test_late_projection_reads_filter_columns() {
+    let path = write_int_columns("late_projection.parquet", &["a", "b", "c"], 10);
+    let df = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("b").gt(lit_int32(6)))
+        .select(vec!["a".to_string()]);
+
+    // The scan reads a and b (not c); b is dropped after filtering
+    let plan = Executor::new().create_physical_plan(df.plan()).unwrap();
+    let PhysicalPlan::FilterProject { input: scan, .. } = &plan else {
+        panic!("expected a fused filter and projection");
+    };
+    let names = |schema: &Schema| -> Vec<String> {
+        schema.fields().iter().map(|f| f.name().clone()).collect()
+    };
+    assert_eq!(names(&scan.schema()), vec!["a", "b"]);
+    assert_eq!(names(&plan.schema()), vec!["a"]);
+
+    let batch = df.collect_one().unwrap();
+    assert_eq!(batch.num_columns(), 1);
+    let a = batch.column(0).unwrap().as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(a.values().to_vec(), vec![7, 8, 9]);
+}
+
+#[test]
+fn test_expression_projection_reads_only_used_columns() {
+    let names: Vec<String> = (0..40).map(|i| format!("c{}", i)).collect();
+    let refs: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    let path = write_int_columns("wide_exprs.parquet", &refs, 1000);
+    let df = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("c3").lt(lit_int32(4)))
+        .select_exprs(vec![col("c7").plus(col("c12")).alias("sum"), col("c7")]);
+
+    // The scan decodes c7, c12 and the filter's c3, not the other 37 columns
+    let plan = Executor::new().create_physical_plan(df.plan()).unwrap();
+    let PhysicalPlan::FilterProject { input: scan, .. } = &plan else {
+        panic!("expected a fused filter and projection");
+    };
+    let scanned: Vec<String> = scan.schema().fields().iter().map(|f| f.name().clone()).collect();
+    assert_eq!(scanned, vec!["c7", "c12", "c3"]);
+
+    let batch = df.collect_one().unwrap();
+    assert_eq!(batch.num_columns(), 2);
+    assert_eq!(batch.i32_column("sum").unwrap().values().to_vec(), vec![0, 2, 4, 6]);
+}
+
+#[test]
+fn test_natural_join_on_shared_columns() {
+    let batch = |columns: Vec<(&str, ArrayRef)>| {
+        let fields: Vec<Field> = columns
+            .iter()
+            .map(|(name, array)| Field::new(*name, array.data_type().clone(), false))
+            .collect();
+        let arrays = columns.into_iter().map(|(_, array)| array).collect();
+        ArrowRecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap()
+    };
+    let left = batch(vec![
+        ("id", Arc::new(Int32Array::from(vec![1, 2, 3]))),
+        ("region", Arc::new(StringArray::from(vec!["east", "west", "east"]))),
+        ("name", Arc::new(StringArray::from(vec!["a", "b", "c"]))),
+    ]);
+    // id 2 and 3 match on id but not on region
+    let right = batch(vec![
+        ("region", Arc::new(StringArray::from(vec!["east", "east", "west", "east"]))),
+        ("amount", Arc::new(Int64Array::from(vec![10, 20, 30, 40]))),
+        ("id", Arc::new(Int32Array::from(vec![1, 2, 3, 1]))),
+    ]);
+    let props = || WriterProperties::builder().build();
+    let left = write_parquet("natural_left.parquet", &[left], props());
+    let right = write_parquet("natural_right.parquet", &[right], props());
+    let left = DataFrame::from_parquet(&left).unwrap();
+    let right = DataFrame::from_parquet(&right).unwrap();
+
+    let joined = left.natural_join(&right).unwrap().order_by(vec![asc("amount")]);
+    let batch = joined.collect_one().unwrap();
+    let names: Vec<String> = batch.schema().fields().iter().map(|f| f.name().clone()).collect();
+    assert_eq!(names, vec!["id", "region", "name", "amount"]);
+    let amounts = batch.column_by_name("amount").unwrap();
+    let amounts = amounts.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(amounts.values().to_vec(), vec![10, 40]);
+    assert_eq!(ids(&[batch]), vec![1, 1]);
+
+    let amounts = right.select(vec!["amount".to_string()]);
+    let err = left.natural_join(&amounts).unwrap_err();
+    assert!(err.contains("right has [amount]"), "{}", err);
+}
+
+#[test]
+fn test_self_join_with_alias() {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("manager_id", DataType::Int32, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from(vec![1, 2, 3, 4])),
+        Arc::new(StringArray::from(vec!["ada", "bob", "cy", "dee"])),
+        Arc::new(Int32Array::from(vec![None, Some(1), Some(1), Some(2)])),
+    ];
+    let batch = ArrowRecordBatch::try_new(schema, columns).unwrap();
+    let path = write_parquet("employees.parquet", &[batch], WriterProperties::builder().build());
+    let employees = DataFrame::from_parquet(&path).unwrap();
+
+    let managers = employees.alias("m");
+    let joined = employees
+        .join(&managers, "manager_id", "m.id", JoinType::Inner)
+        .order_by(vec![asc("id")]);
+    let batch = joined.collect_one().unwrap();
+    let names: Vec<String> = batch.schema().fields().iter().map(|f| f.name().clone()).collect();
+    assert_eq!(names, vec!["id", "name", "manager_id", "m.id", "m.name", "m.manager_id"]);
+    let strings = |name: &str| -> Vec<String> {
+        let column = batch.column_by_name(name).unwrap();
+        let column = column.as_any().downcast_ref::<StringArray>().unwrap();
+        column.iter().map(|s| s.unwrap().to_string()).collect()
+    };
+    assert_eq!(strings("name"), vec!["bob", "cy", "dee"]);
+    assert_eq!(strings("m.name"), vec!["ada", "ada", "bob"]);
+    assert!(joined.explain(false).unwrap().contains("Alias: m"));
+}
+
+#[test]
+fn test_sort_on_group_keys_runs_in_aggregate() {
+    let path = write_parquet(
+        "sorted_aggregate.parquet",
+        &[id_name_batch(0, 20)],
+        WriterProperties::builder().build(),
+    );
+    let grouped = DataFrame::from_parquet(&path)
+        .unwrap()
+        .group_by_exprs(vec![col("id").modulo(lit_int32(7)).alias("id")])
+        .agg(vec![count("n")]);
+
+    // Groups are first seen as 0..7; sorting them descending happens in the aggregate
+    let sorted = grouped.order_by(vec![desc("id")]);
+    let optimized = sorted.optimize().unwrap();
+    match optimized.plan() {
+        LogicalPlan::Aggregate { order_by, .. } => {
+            assert_eq!(order_by.len(), 1);
+            assert!(!order_by[0].ascending);
+        }
+        other => panic!("expected the sort to be removed, got {:?}", other),
+    }
+    assert!(optimized.explain(false).unwrap().contains("order_by=[id DESC]"));
+    let expected = sorted.collect_one().unwrap();
+    assert_eq!(ids(std::slice::from_ref(&expected)), vec![6, 5, 4, 3, 2, 1, 0]);
+    assert_eq!(
+        optimized.collect_one().unwrap().to_arrow().unwrap(),
+        expected.to_arrow().unwrap()
+    );
+
+    // A sort on an aggregate column still needs its own pass
+    let by_count = grouped.order_by(vec![asc("n")]).optimize().unwrap();
+    assert!(matches!(by_count.plan(), LogicalPlan::Sort { .. }));
+}
+
+#[test]
+fn test_estimated_groups_hint_is_kept_through_optimization() {
+    let path = write_parquet(
+        "estimated_groups.parquet",
+        &[id_name_batch(0, 50)],
+        WriterProperties::builder().build(),
+    );
+    let grouped = || {
+        DataFrame::from_parquet(&path)
+            .unwrap()
+            .group_by_exprs(vec![col("id").modulo(lit_int32(5)).alias("id")])
+    };
+    let hinted = grouped()
+        .with_estimated_groups(5)
+        .agg(vec![count("n")])
+        .order_by(vec![asc("id")]);
+    let optimized = hinted.optimize().unwrap();
+    match optimized.plan() {
+        LogicalPlan::Aggregate {
+            estimated_groups, ..
+        } => assert_eq!(*estimated_groups, Some(5)),
+        other => panic!("expected the sort to be removed, got {:?}", other),
+    }
+
+    let plain = grouped().agg(vec![count("n")]).order_by(vec![asc("id")]);
+    assert_eq!(
+        optimized.collect_one().unwrap().to_arrow().unwrap(),
+        plain.collect_one().unwrap().to_arrow().unwrap()
+    );
+}
+
+#[test]
+fn test_collect_with_batch_info() {
+    let props = WriterProperties::builder().set_max_row_group_size(40).build();
+    let path = write_parquet("batch_info.parquet", &[id_name_batch(0, 100)], props);
+    let df = DataFrame::from_parquet(&path).unwrap();
+
+    // One batch per row group
+    let info = df.collect_with_batch_info().unwrap();
+    assert_eq!(info, vec![(0, 40), (1, 40), (2, 20)]);
+    let filtered = df.filter(col("id").lt(lit_int32(50)));
+    assert_eq!(filtered.collect_with_batch_info().unwrap(), vec![(0, 40), (1, 10)]);
+}
+
+#[test]
+fn test_top_groups_by_aggregate() {
+    // Group g appears 10 - g times, spread over several row groups
+    let values: Vec<i32> = (0..10).flat_map(|g| (g..10).map(move |_| g)).collect();
+    let schema = Arc::new(Schema::new(vec![Field::new("g", DataType::Int32, false)]));
+    let batch = ArrowRecordBatch::try_new(schema, vec![Arc::new(Int32Array::from(values))]);
+    let props = WriterProperties::builder().set_max_row_group_size(7).build();
+    let path = write_parquet("top_groups.parquet", &[batch.unwrap()], props);
+
+    let top = DataFrame::from_parquet(&path)
+        .unwrap()
+        .group_by(vec!["g".to_string()])
+        .agg(vec![count("n")])
+        .order_by(vec![desc("n")])
+        .limit(3);
+    let plan = Executor::new().create_physical_plan(top.optimize().unwrap().plan()).unwrap();
+    let PhysicalPlan::Limit { input, .. } = plan else {
+        panic!("expected a limit on top");
+    };
+    let PhysicalPlan::Sort { input, .. } = *input else {
+        panic!("expected the sort below the limit");
+    };
+    assert!(matches!(*input, PhysicalPlan::Aggregate { .. }));
+
+    let batch = top.collect_one().unwrap();
+    assert_eq!(batch.i64_column("n").unwrap().values().to_vec(), vec![10, 9, 8]);
+    assert_eq!(batch.i32_column("g").unwrap().values().to_vec(), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_count_and_agg_appends_group_count() {
+    let path = write_parquet(
+        "group_count.parquet",
+        &[id_name_batch(0, 100)],
+        WriterProperties::builder().set_max_row_group_size(30).build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let grouped = || df.group_by_exprs(vec![col("id").modulo(lit_int32(7)).alias("bucket")]);
+    let with_count = grouped()
+        .count_and_agg(vec![sum("id", "total")])
+        .order_by(vec![asc("bucket")])
+        .collect_one()
+        .unwrap();
+    let names: Vec<&str> = with_count.schema().fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, vec!["bucket", "total", GROUP_COUNT_COLUMN]);
+
+    let counts = grouped()
+        .agg(vec![count("n")])
+        .order_by(vec![asc("bucket")])
+        .collect_one()
+        .unwrap();
+    let group_counts = with_count.i64_column(GROUP_COUNT_COLUMN).unwrap();
+    assert_eq!(group_counts, counts.i64_column("n").unwrap());
+    assert_eq!(group_counts.values().iter().sum::<i64>(), 100);
+}
+
+#[test]
+fn test_checked_arithmetic_config() {
+    let path = write_int_columns("checked_arithmetic.parquet", &["a"], 3);
+    let sum = || vec![col("a").plus(lit_int32(i32::MAX)).alias("sum")];
+    let df = DataFrame::from_parquet(&path).unwrap();
+    let filtered = df.filter(col("a").lt(lit_int32(1))).select_exprs(sum());
+    let df = df.select_exprs(sum());
+
+    // Default: 2 + i32::MAX wraps around
+    let batch = df.collect_one().unwrap();
+    let sums = batch.column(0).unwrap().as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(sums.values().to_vec(), vec![i32::MAX, i32::MIN, i32::MIN + 1]);
+
+    let checked = Executor::with_config(ExecutorConfig {
+        checked_arithmetic: true,
+        ..ExecutorConfig::default()
+    });
+    let err = checked.execute(df.plan()).unwrap_err();
+    assert!(err.contains("Overflow"), "{}", err);
+    // Rows removed by the filter are never added, so they cannot overflow
+    assert_eq!(total_rows(&checked.execute(filtered.plan()).unwrap()), 1);
+}
+
+#[test]
+fn test_collect_scalar() {
+    let path = write_parquet(
+        "collect_scalar.parquet",
+        &[id_name_batch(0, 10)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    // A global aggregate over no rows still has its one row
+    let none = df
+        .filter(col("id").gt(lit_int32(100)))
+        .group_by(vec![])
+        .agg(vec![count("n"), sum("id", "total")]);
+    let row = none.collect().unwrap()[0].rows().next().unwrap().unwrap();
+    assert_eq!(row, vec![ScalarValue::Int64(0), ScalarValue::Null]);
+    let none = df
+        .filter(col("id").gt(lit_int32(100)))
+        .group_by(vec![])
+        .agg(vec![count("n")]);
+    assert_eq!(none.collect_scalar().unwrap(), ScalarValue::Int64(0));
+
+    let count = df
+        .filter(col("id").ge(lit_int32(4)))
+        .group_by(vec![])
+        .agg(vec![count("n")]);
+    assert!(matches!(count.collect_scalar().unwrap(), ScalarValue::Int64(6)));
+    let name = df.filter(col("id").eq(lit_int32(3))).select(vec!["name".to_string()]);
+    assert!(matches!(name.collect_scalar().unwrap(), ScalarValue::Utf8(_)));
+
+    let err = df.select(vec!["id".to_string()]).collect_scalar().unwrap_err();
+    assert!(err.contains("10 row(s) and 1 column(s)"), "{}", err);
+    let err = df.limit(1).collect_scalar().unwrap_err();
+    assert!(err.contains("1 row(s) and 2 column(s)"), "{}", err);
+}
+
+#[test]
+fn test_left_join_against_empty_aggregate() {
+    let path = write_parquet(
+        "empty_aggregate_join.parquet",
+        &[id_name_batch(0, 5)],
+        WriterProperties::builder().build(),
+    );
+    let df = DataFrame::from_parquet(&path).unwrap();
+    // No rows survive the filter, so the aggregate (and the join below) has no groups
+    let counts = df
+        .filter(col("id").gt(lit_int32(100)))
+        .group_by(vec!["name".to_string()])
+        .agg(vec![count("n")]);
+    let empty_join = counts.join(&counts, "name", "name", JoinType::Inner);
+
+    for right in [&counts, &empty_join] {
+        let joined = df.join(right, "name", "name", JoinType::Left);
+        let batch = joined.collect_one().unwrap();
+        assert_eq!(batch.schema(), &joined.schema().unwrap());
+        assert_eq!(batch.num_rows(), 5);
+        assert_eq!(batch.column_by_name("n").unwrap().null_count(), 5);
+    }
+}
+
+#[test]
+fn test_bind_parameters() {
+    let path = write_parquet(
+        "bind_parameters.parquet",
+        &[id_name_batch(0, 10)],
+        WriterProperties::builder().build(),
+    );
+    let prepared = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("id").ge(param(0)))
+        .select_exprs(vec![col("id"), col("id").plus(param(1)).alias("shifted")]);
+    assert!(prepared.explain(false).unwrap().contains("Filter: id >= $0"));
+
+    // The same plan, bound to different values
+    let bound = prepared.bind(vec![LogicalValue::Int32(7), LogicalValue::Int32(100)]);
+    assert_eq!(ids(&bound.collect().unwrap()), vec![7, 8, 9]);
+    let bound = prepared.bind(vec![LogicalValue::Int32(9), LogicalValue::Int32(0)]);
+    assert_eq!(ids(&bound.collect().unwrap()), vec![9]);
+
+    // Running with a parameter left unbound is an error
+    let err = prepared.collect().unwrap_err();
+    assert!(err.contains("$0 is not bound"), "{}", err);
+    let err = prepared.bind(vec![LogicalValue::Int32(1)]).collect().unwrap_err();
+    assert!(err.contains("$1 is not bound"), "{}", err);
+}
+
+#[test]
+fn test_unpivot_numeric_columns() {
+    let path = write_int_columns("unpivot.parquet", &["id", "jan", "feb", "mar"], 4);
+    let names = |cols: &[&str]| cols.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    let long = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("id").lt(lit_int32(2)))
+        .unpivot(names(&["id"]), names(&["jan", "feb", "mar"]));
+    assert!(long.explain(false).unwrap().starts_with("Unpivot: ids=[id] values=[jan, feb, mar]"));
+
+    // Within a batch, rows are grouped by value column
+    let batch = long.collect_one().unwrap();
+    let schema = batch.schema();
+    let columns: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(columns, vec!["id", "variable", "value"]);
+    assert_eq!(ids(std::slice::from_ref(&batch)), vec![0, 1, 0, 1, 0, 1]);
+    let variables = batch.column_by_name("variable").unwrap();
+    let variables = variables.as_any().downcast_ref::<StringArray>().unwrap();
+    let variables: Vec<&str> = variables.iter().flatten().collect();
+    assert_eq!(variables, vec!["jan", "jan", "feb", "feb", "mar", "mar"]);
+    let values = batch.column_by_name("value").unwrap();
+    let values = values.as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(values.values().to_vec(), vec![0, 1, 0, 1, 0, 1]);
+}
+
+#[test]
+fn test_scan_mixes_dictionary_and_plain_strings() {
+    let dir = temp_path("dictionary_mix");
+    std::fs::create_dir_all(&dir).unwrap();
+    let dictionary_schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new(
+            "name",
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+            false,
+        ),
+    ]));
+    let names: DictionaryArray<Int32Type> = vec!["a", "b", "a"].into_iter().collect();
+    let dictionary = ArrowRecordBatch::try_new(
+        dictionary_schema,
+        vec![Arc::new(Int32Array::from(vec![0, 1, 2])), Arc::new(names)],
+    )
+    .unwrap();
+    for (name, batch) in [("part-0.parquet", dictionary), ("part-1.parquet", id_name_batch(3, 2))] {
+        let path = write_parquet(name, &[batch], WriterProperties::builder().build());
+        std::fs::rename(&path, dir.join(name)).unwrap();
+    }
+
+    let df = DataFrame::from_parquet(&dir).unwrap();
+    assert_eq!(df.schema().unwrap().field(1).data_type(), &DataType::Utf8);
+    let batch = df.filter(col("name").neq(lit_string("b"))).collect_one().unwrap();
+    let names = batch.to_arrow().unwrap();
+    let names = names.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+    let names: Vec<&str> = names.iter().map(|n| n.unwrap()).collect();
+    assert_eq!(names, vec!["a", "a", "name_3", "name_4"]);
+}
+
+#[test]
+fn test_read_gzip_wrapped_parquet() {
+    let props = WriterProperties::builder().build();
+    let plain = write_parquet("gzip_wrapped.parquet", &[id_name_batch(0, 50)], props);
+    let path = temp_path("gzip_wrapped.parquet.gz");
+    let mut encoder = GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+    std::io::copy(&mut File::open(&plain).unwrap(), &mut encoder).unwrap();
+    encoder.finish().unwrap();
+
+    let expected = read_parquet(&plain).unwrap();
+    assert_eq!(read_parquet(&path).unwrap(), expected);
+    let df = DataFrame::from_parquet(&path)
+        .unwrap()
+        .filter(col("id").ge(lit_int32(40)));
+    assert_eq!(total_rows(&df.collect().unwrap()), 10);
+
+    // A directory scan reads gzip-wrapped files alongside plain ones, and no other files
+    let dir = temp_path("gzip_wrapped_dir");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(&plain, dir.join("part-0.parquet")).unwrap();
+    std::fs::copy(&path, dir.join("part-1.parquet.gz")).unwrap();
+    std::fs::write(dir.join("notes.txt.gz"), b"not parquet").unwrap();
+    let files = list_parquet_files(&dir).unwrap();
+    let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap()).collect();
+    assert_eq!(names, vec!["part-0.parquet", "part-1.parquet.gz"]);
+    let df = DataFrame::from_parquet(&dir).unwrap();
+    assert_eq!(total_rows(&df.collect().unwrap()), 100);
+
+    // Not gzip data: the error names the file
+    let bogus = temp_path("bogus.parquet.gz");
+    std::fs::copy(&plain, &bogus).unwrap();
+    let err = read_parquet(&bogus).unwrap_err().to_string();
+    assert!(err.contains("Failed to decompress"), "{}", err);
+}
+
+#[test]
+fn test_scan_with_explicit_schema_casts_columns() {
+    let dir = temp_path("explicit_schema");
+    std::fs::create_dir_all(&dir).unwrap();
+    // The second file stores id as Int64, with its columns in the other order
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("id", DataType::Int64, false),
+    ]));
+    let wide = ArrowRecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(StringArray::from(vec!["x", "y"])),
+            Arc::new(Int64Array::from(vec![100, 101])),
+        ],
+    )
+    .unwrap();
+    for (name, batch) in [("part-0.parquet", id_name_batch(0, 5)), ("part-1.parquet", wide)] {
+        let path = write_parquet(name, &[batch], WriterProperties::builder().build());
+        std::fs::rename(&path, dir.join(name)).unwrap();
+    }
+    assert!(DataFrame::from_parquet(&dir).unwrap().collect().is_err());
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    let df = DataFrame::from_parquet_with_schema(&dir, schema.clone()).unwrap();
+    assert_eq!(df.schema().unwrap(), schema);
+    let batch = df.collect_one().unwrap();
+    assert_eq!(batch.schema(), &schema);
+    let ids = batch.column(0).unwrap();
+    let ids = ids.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(ids.values().to_vec(), vec![0, 1, 2, 3, 4, 100, 101]);
+
+    // Filters run on the cast columns; the projection leaves the filter column out
+    let names = df.filter(col("id").ge(lit_int64(3))).select(vec!["name".to_string()]);
+    let names = names.collect_one().unwrap();
+    let names = names.column(0).unwrap();
+    let names = names.as_any().downcast_ref::<StringArray>().unwrap();
+    let names: Vec<&str> = names.iter().map(|n| n.unwrap()).collect();
+    assert_eq!(names, vec!["name_3", "name_4", "x", "y"]);
+
+    let missing = Arc::new(Schema::new(vec![Field::new("age", DataType::Int64, true)]));
+    let err = DataFrame::from_parquet_with_schema(&dir, missing).unwrap().collect().unwrap_err();
+    assert!(err.contains("has no column 'age'"), "{}", err);
+}